@@ -1,7 +1,9 @@
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Keypair;
-use solana_sdk::signer::Signer;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::{Signer, SignerError};
+use std::mem::ManuallyDrop;
 use std::sync::Arc;
+use std::thread::ThreadId;
 
 /// Auxiliary data structure to align the types of the solana-clap-utils with anchor-client.
 pub struct DynSigner(pub Arc<dyn Signer>);
@@ -31,6 +33,65 @@ impl Signer for DynSigner {
     }
 }
 
+/// Signer that is not `Send` (e.g., a Ledger `usb://` signer loaded by solana-clap-utils) wrapped to be stored
+/// in the thread-safe transaction builders. The wrapped signer is used and dropped only at the thread it was loaded at,
+/// signing at another thread fails with [SignerError::Custom] and dropping there leaks the signer.
+pub struct ThreadSafeSigner {
+    pubkey: Pubkey,
+    thread_id: ThreadId,
+    signer: ManuallyDrop<Box<dyn Signer>>,
+}
+
+// SAFETY: the wrapped signer is never accessed nor dropped at other thread than the one it was created at
+unsafe impl Send for ThreadSafeSigner {}
+unsafe impl Sync for ThreadSafeSigner {}
+
+impl ThreadSafeSigner {
+    pub fn new(signer: Box<dyn Signer>) -> Self {
+        Self {
+            pubkey: signer.pubkey(),
+            thread_id: std::thread::current().id(),
+            signer: ManuallyDrop::new(signer),
+        }
+    }
+
+    fn signer(&self) -> Result<&dyn Signer, SignerError> {
+        if std::thread::current().id() == self.thread_id {
+            Ok(self.signer.as_ref())
+        } else {
+            Err(SignerError::Custom(format!(
+                "signer {} can be used only at the thread it was loaded at",
+                self.pubkey
+            )))
+        }
+    }
+}
+
+impl Signer for ThreadSafeSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.signer()?.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.signer()
+            .map(|signer| signer.is_interactive())
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for ThreadSafeSigner {
+    fn drop(&mut self) {
+        if std::thread::current().id() == self.thread_id {
+            // SAFETY: the signer is dropped only once, here
+            unsafe { ManuallyDrop::drop(&mut self.signer) }
+        }
+    }
+}
+
 /// Keypair or Pubkey depending, could be one of that based on parameters of the CLI command.
 /// When --print and --simulate are set, a pubkey instead of a valid keypair can be passed.
 #[derive(Debug, Clone)]
@@ -0,0 +1,71 @@
+use crate::transactions::execution_journal::ExecutionJournalError;
+use crate::transactions::transaction_builder::TransactionBuildError;
+use crate::transactions::transaction_diagnostic::TransactionFailure;
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+/// Error of the public API of the crate, the variants permit the callers to decide on retries or alerts.
+#[derive(Debug, Error)]
+pub enum MarinadeClientError {
+    /// The pubkey provided to sign the transaction is not the authority stored in the Marinade state.
+    #[error("{authority} {provided} to sign the transaction mismatches Marinade state {authority} {expected}")]
+    AuthorityMismatch {
+        authority: &'static str,
+        expected: Pubkey,
+        provided: Pubkey,
+    },
+    #[error("Rent payer {rent_payer} must be a system account, the account is owned by {owner}")]
+    InvalidRentPayer { rent_payer: Pubkey, owner: Pubkey },
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("Account {account} balance {balance} lamports is lower than the required {required} lamports")]
+    InsufficientBalance {
+        account: Pubkey,
+        balance: u64,
+        required: u64,
+    },
+    #[error("Execution has to be confirmed, confirm it explicitly when not running in a terminal")]
+    ConfirmationRequired,
+    #[error("Execution was not confirmed")]
+    ExecutionDeclined,
+    #[error("Cannot deserialize {name}: {error}")]
+    Deserialization { name: String, error: String },
+    #[error("{list} index {index} is out of the account data of {len} records")]
+    ListIndex {
+        list: &'static str,
+        index: u32,
+        len: usize,
+    },
+    #[error("RPC error: {0}")]
+    Rpc(#[from] SolanaClientError),
+    #[error("Anchor client error: {0}")]
+    AnchorClient(#[from] anchor_client::ClientError),
+    #[error("Marinade program error: {0}")]
+    Program(#[from] anchor_lang::error::Error),
+    #[error(transparent)]
+    TransactionBuild(#[from] TransactionBuildError),
+    #[error(transparent)]
+    Execution(#[from] TransactionFailure),
+    #[error(transparent)]
+    Journal(#[from] ExecutionJournalError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl MarinadeClientError {
+    pub fn deserialization(name: impl Into<String>, error: impl ToString) -> Self {
+        Self::Deserialization {
+            name: name.into(),
+            error: error.to_string(),
+        }
+    }
+
+    /// The error is caused by the RPC node or the network, the operation may succeed when retried.
+    pub fn is_rpc_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Rpc(_) | Self::AnchorClient(anchor_client::ClientError::SolanaClientError(_))
+        )
+    }
+}
@@ -1,5 +1,6 @@
 #![cfg_attr(not(debug_assertions), deny(warnings))]
 
+pub mod error;
 pub mod marinade;
 pub mod transactions;
 
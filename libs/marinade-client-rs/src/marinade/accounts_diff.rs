@@ -0,0 +1,479 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::chained_simulation::{
+    simulate_prepared_transaction_chained, AccountsOverlay,
+};
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use anchor_lang::AccountDeserialize;
+use marinade_finance::state::State;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::solana_program::program_pack::Pack;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Change of the decoded account data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccountDataDiff {
+    TokenAccount {
+        mint: String,
+        owner: String,
+        amount_before: u64,
+        amount_after: u64,
+    },
+    Mint {
+        supply_before: u64,
+        supply_after: u64,
+    },
+    MarinadeState {
+        changes: Vec<FieldChange>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountDiff {
+    pub pubkey: String,
+    pub lamports_before: u64,
+    pub lamports_after: u64,
+    pub lamports_delta: i64,
+    pub created: bool,
+    pub closed: bool,
+    pub data: Option<AccountDataDiff>,
+}
+
+impl AccountDiff {
+    pub fn new(pubkey: &Pubkey, before: Option<&Account>, after: Option<&Account>) -> Self {
+        let lamports_before = before.map(|account| account.lamports).unwrap_or(0);
+        let lamports_after = after.map(|account| account.lamports).unwrap_or(0);
+        Self {
+            pubkey: pubkey.to_string(),
+            lamports_before,
+            lamports_after,
+            lamports_delta: delta(lamports_before, lamports_after),
+            created: before.is_none() && after.is_some(),
+            closed: before.is_some() && after.is_none(),
+            data: data_diff(before, after),
+        }
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.lamports_delta != 0
+            || self.created
+            || self.closed
+            || match &self.data {
+                Some(AccountDataDiff::TokenAccount {
+                    amount_before,
+                    amount_after,
+                    ..
+                }) => amount_before != amount_after,
+                Some(AccountDataDiff::Mint {
+                    supply_before,
+                    supply_after,
+                }) => supply_before != supply_after,
+                Some(AccountDataDiff::MarinadeState { changes }) => !changes.is_empty(),
+                None => false,
+            }
+    }
+}
+
+/// Changes of the writable accounts of the transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AccountsDiff {
+    pub accounts: Vec<AccountDiff>,
+}
+
+impl AccountsDiff {
+    pub fn new(
+        addresses: &[Pubkey],
+        before: &[Option<Account>],
+        after: &[Option<Account>],
+    ) -> Self {
+        Self {
+            accounts: addresses
+                .iter()
+                .enumerate()
+                .map(|(index, pubkey)| {
+                    AccountDiff::new(
+                        pubkey,
+                        before.get(index).and_then(Option::as_ref),
+                        after.get(index).and_then(Option::as_ref),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Display for AccountsDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for account in &self.accounts {
+            let lifecycle = if account.created {
+                " (created)"
+            } else if account.closed {
+                " (closed)"
+            } else {
+                ""
+            };
+            writeln!(
+                f,
+                "Account {}{}: lamports {} -> {} ({:+})",
+                account.pubkey,
+                lifecycle,
+                account.lamports_before,
+                account.lamports_after,
+                account.lamports_delta
+            )?;
+            match &account.data {
+                Some(AccountDataDiff::TokenAccount {
+                    mint,
+                    owner,
+                    amount_before,
+                    amount_after,
+                }) => writeln!(
+                    f,
+                    "  token account of mint {} owned by {}: amount {} -> {} ({:+})",
+                    mint,
+                    owner,
+                    amount_before,
+                    amount_after,
+                    delta(*amount_before, *amount_after)
+                )?,
+                Some(AccountDataDiff::Mint {
+                    supply_before,
+                    supply_after,
+                }) => writeln!(
+                    f,
+                    "  mint supply {} -> {} ({:+})",
+                    supply_before,
+                    supply_after,
+                    delta(*supply_before, *supply_after)
+                )?,
+                Some(AccountDataDiff::MarinadeState { changes }) => {
+                    for change in changes {
+                        writeln!(
+                            f,
+                            "  state.{}: {} -> {}",
+                            change.field, change.before, change.after
+                        )?;
+                    }
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accounts diff print formats selectable by name, see [AccountsDiffFormat::FORMATS].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountsDiffFormat {
+    Text,
+    Json,
+}
+
+impl AccountsDiffFormat {
+    pub const FORMATS: [&'static str; 2] = ["text", "json"];
+
+    pub fn print(&self, accounts_diff: &AccountsDiff) {
+        match self {
+            Self::Text => print!("{}", accounts_diff),
+            Self::Json => println!(
+                "{}",
+                accounts_diff
+                    .to_json()
+                    .expect("accounts diff is serializable")
+            ),
+        }
+    }
+}
+
+impl FromStr for AccountsDiffFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown accounts diff format '{}', expected one of {:?}",
+                format,
+                Self::FORMATS
+            )),
+        }
+    }
+}
+
+fn delta(before: u64, after: u64) -> i64 {
+    (after as i128 - before as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn data_diff(before: Option<&Account>, after: Option<&Account>) -> Option<AccountDataDiff> {
+    let owner = after.or(before)?.owner;
+    if owner == spl_token::id() {
+        let token_account = |account: Option<&Account>| {
+            account.and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+        };
+        let (token_before, token_after) = (token_account(before), token_account(after));
+        if let Some(token) = token_after.as_ref().or(token_before.as_ref()) {
+            return Some(AccountDataDiff::TokenAccount {
+                mint: token.mint.to_string(),
+                owner: token.owner.to_string(),
+                amount_before: token_before.map(|token| token.amount).unwrap_or(0),
+                amount_after: token_after.map(|token| token.amount).unwrap_or(0),
+            });
+        }
+        let mint = |account: Option<&Account>| {
+            account.and_then(|account| spl_token::state::Mint::unpack(&account.data).ok())
+        };
+        let (mint_before, mint_after) = (mint(before), mint(after));
+        if mint_before.is_some() || mint_after.is_some() {
+            return Some(AccountDataDiff::Mint {
+                supply_before: mint_before.map(|mint| mint.supply).unwrap_or(0),
+                supply_after: mint_after.map(|mint| mint.supply).unwrap_or(0),
+            });
+        }
+        return None;
+    }
+
+    // the discriminator is checked, only the Marinade state account is decoded
+    let state = |account: Option<&Account>| {
+        account.and_then(|account| State::try_deserialize(&mut account.data.as_slice()).ok())
+    };
+    match (state(before), state(after)) {
+        (Some(state_before), Some(state_after)) => Some(AccountDataDiff::MarinadeState {
+            changes: state_changes(&state_before, &state_after),
+        }),
+        _ => None,
+    }
+}
+
+/// Change of the field when the debug representations of the values differ.
+fn field_change<T: Debug>(field: &str, before: &T, after: &T) -> Option<FieldChange> {
+    let (before, after) = (format!("{:?}", before), format!("{:?}", after));
+    (before != after).then(|| FieldChange {
+        field: field.to_string(),
+        before,
+        after,
+    })
+}
+
+/// Pushes the changes of the listed fields, the field name is prefixed with the path of the nested structure.
+macro_rules! push_field_changes {
+    ($changes:expr, $prefix:literal, $before:expr, $after:expr, [$($field:ident),* $(,)?]) => {
+        $(
+            $changes.extend(field_change(
+                concat!($prefix, stringify!($field)),
+                &$before.$field,
+                &$after.$field,
+            ));
+        )*
+    };
+}
+
+/// Changes of the Marinade state fields, the list addresses and record sizes are fixed
+/// and left out, the lists are reported by their record counts.
+pub fn state_changes(before: &State, after: &State) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    push_field_changes!(
+        changes,
+        "",
+        before,
+        after,
+        [
+            msol_mint,
+            admin_authority,
+            operational_sol_account,
+            treasury_msol_account,
+            rent_exempt_for_token_acc,
+            reward_fee,
+            available_reserve_balance,
+            msol_supply,
+            msol_price,
+            circulating_ticket_count,
+            circulating_ticket_balance,
+            lent_from_reserve,
+            min_deposit,
+            min_withdraw,
+            staking_sol_cap,
+            emergency_cooling_down,
+            pause_authority,
+            paused,
+            delayed_unstake_fee,
+            withdraw_stake_account_fee,
+            withdraw_stake_account_enabled,
+            last_stake_move_epoch,
+            stake_moved,
+            max_stake_moved_per_epoch,
+        ]
+    );
+    changes.extend(field_change(
+        "stake_system.stake_count",
+        &before.stake_system.stake_count(),
+        &after.stake_system.stake_count(),
+    ));
+    push_field_changes!(
+        changes,
+        "stake_system.",
+        before.stake_system,
+        after.stake_system,
+        [
+            delayed_unstake_cooling_down,
+            slots_for_stake_delta,
+            last_stake_delta_epoch,
+            min_stake,
+            extra_stake_delta_runs,
+        ]
+    );
+    changes.extend(field_change(
+        "validator_system.validator_count",
+        &before.validator_system.validator_count(),
+        &after.validator_system.validator_count(),
+    ));
+    push_field_changes!(
+        changes,
+        "validator_system.",
+        before.validator_system,
+        after.validator_system,
+        [
+            manager_authority,
+            total_validator_score,
+            total_active_balance,
+            auto_add_validator_enabled,
+        ]
+    );
+    push_field_changes!(
+        changes,
+        "liq_pool.",
+        before.liq_pool,
+        after.liq_pool,
+        [
+            lp_mint,
+            msol_leg,
+            lp_liquidity_target,
+            lp_max_fee,
+            lp_min_fee,
+            treasury_cut,
+            lp_supply,
+            lent_from_sol_leg,
+            liquidity_sol_cap,
+        ]
+    );
+    changes
+}
+
+/// Simulates the transaction requesting its writable accounts and compares them with the cluster state
+/// the simulation runs over, see [simulate_prepared_transaction_chained].
+pub fn simulate_accounts_diff(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    simulate_config: RpcSimulateTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+) -> Result<(RpcSimulateTransactionResult, AccountsDiff), MarinadeClientError> {
+    let simulation = simulate_prepared_transaction_chained(
+        prepared_transaction,
+        rpc_client,
+        rpc_client,
+        simulate_config,
+        blockhash_commitment,
+        &mut AccountsOverlay::default(),
+        true,
+    );
+    Ok((
+        simulation.result?.value,
+        simulation.accounts_diff.unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::state::AccountState;
+
+    #[test]
+    fn test_state_changes() {
+        // zeroed account data past the discriminator deserializes to the zeroed state
+        let data = vec![0; 4096];
+        let before = State::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        let mut after = State::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        assert!(state_changes(&before, &after).is_empty());
+
+        after.msol_price = 2;
+        after.liq_pool.lp_supply = 5;
+        assert_eq!(
+            state_changes(&before, &after),
+            vec![
+                FieldChange {
+                    field: "msol_price".to_string(),
+                    before: "0".to_string(),
+                    after: "2".to_string(),
+                },
+                FieldChange {
+                    field: "liq_pool.lp_supply".to_string(),
+                    before: "0".to_string(),
+                    after: "5".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_account_diff() {
+        let mint = Pubkey::new_unique();
+        let token_account = |amount: u64| {
+            let mut data = vec![0; spl_token::state::Account::LEN];
+            spl_token::state::Account {
+                mint,
+                owner: Pubkey::new_unique(),
+                amount,
+                state: AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            Account {
+                lamports: 2_039_280,
+                data,
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            }
+        };
+        let pubkey = Pubkey::new_unique();
+        let diff = AccountsDiff::new(
+            &[pubkey],
+            &[Some(token_account(5))],
+            &[Some(token_account(12))],
+        );
+        let account_diff = &diff.accounts[0];
+        assert_eq!(account_diff.lamports_delta, 0);
+        assert!(account_diff.is_changed());
+        match &account_diff.data {
+            Some(AccountDataDiff::TokenAccount {
+                mint: diff_mint,
+                amount_before,
+                amount_after,
+                ..
+            }) => {
+                assert_eq!(*diff_mint, mint.to_string());
+                assert_eq!((*amount_before, *amount_after), (5, 12));
+            }
+            other => panic!("Expected token account diff, got {:?}", other),
+        }
+        assert!(diff.to_string().contains("amount 5 -> 12 (+7)"));
+    }
+}
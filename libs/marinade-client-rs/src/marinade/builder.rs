@@ -1,4 +1,5 @@
 #![allow(clippy::too_many_arguments)]
+use crate::error::MarinadeClientError;
 use crate::marinade::instructions::{
     add_liquidity, add_validator, change_authority, claim, config_lp, config_marinade,
     config_validator_system, create_canonical_stake, deactivate_stake, deposit,
@@ -28,7 +29,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         validator_vote: Pubkey,
         score: u32,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn set_validator_score(
         &'a self,
@@ -36,13 +37,13 @@ pub trait MarinadeRequestBuilder<'a, C> {
         validator_vote: Pubkey,
         validator_index: u32,
         score: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn config_validator_system(
         &'a self,
         validator_manager_authority: &'a PubkeyOrKeypair,
         extra_runs: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn emergency_unstake(
         &'a self,
@@ -50,27 +51,27 @@ pub trait MarinadeRequestBuilder<'a, C> {
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn remove_validator(
         &'a self,
         validator_manager_authority: &'a PubkeyOrKeypair,
         validator_vote: Pubkey,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn add_liquidity(
         &'a self,
         transfer_from: &'a PubkeyOrKeypair,
         mint_to: Pubkey,
         lamports: u64,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn change_authority(
         &'a self,
         admin_authority: &'a PubkeyOrKeypair,
         params: ChangeAuthorityData,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn deactivate_stake(
         &'a self,
@@ -79,14 +80,14 @@ pub trait MarinadeRequestBuilder<'a, C> {
         split_stake_rent_payer: &'a PubkeyOrKeypair,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn deposit(
         &'a self,
         transfer_from: &'a PubkeyOrKeypair,
         mint_to: Pubkey,
         lamports: u64,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn deposit_stake_account(
         &'a self,
@@ -96,7 +97,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         validator_index: u32,
         validator_vote: Pubkey,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn partial_unstake(
         &'a self,
@@ -107,7 +108,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         split_stake_account: &'a PubkeyOrKeypair,
         split_stake_rent_payer: &'a PubkeyOrKeypair,
         desired_amount: u64,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn initialize(
         &'a self,
@@ -120,7 +121,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         lp_mint: Pubkey,
         liq_pool_msol_leg: Pubkey,
         data: marinade_finance::instructions::InitializeData,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn liquid_unstake(
         &'a self,
@@ -128,7 +129,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         get_msol_from_authority: &'a PubkeyOrKeypair,
         transfer_sol_to: Pubkey,
         msol_amount: u64,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn merge_stakes(
         &'a self,
@@ -137,7 +138,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         source_stake: Pubkey,
         source_stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn create_canonical_stake(
         &'a self,
@@ -145,7 +146,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         source_stake_index: u32,
         validator_index: u32,
         validator_vote: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn remove_liquidity(
         &'a self,
@@ -154,7 +155,7 @@ pub trait MarinadeRequestBuilder<'a, C> {
         transfer_sol_to: Pubkey,
         transfer_msol_to: Pubkey,
         tokens: u64,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn config_lp(
         &'a self,
@@ -163,13 +164,13 @@ pub trait MarinadeRequestBuilder<'a, C> {
         max_fee: Option<Fee>,
         liquidity_target: Option<u64>,
         treasury_bp_cut: Option<Fee>,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn config_marinade(
         &'a self,
         admin_authority: &'a PubkeyOrKeypair,
         params: ConfigMarinadeParams,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn stake_reserve(
         &'a self,
@@ -177,21 +178,21 @@ pub trait MarinadeRequestBuilder<'a, C> {
         validator_vote: Pubkey,
         stake_account: &'a PubkeyOrKeypair,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn update_active(
         &'a self,
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn update_deactivated(
         &'a self,
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn order_unstake(
         &'a self,
@@ -199,23 +200,23 @@ pub trait MarinadeRequestBuilder<'a, C> {
         burn_msol_from_authority: &'a PubkeyOrKeypair,
         msol_amount: u64,
         ticket_account: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn claim(
         &'a self,
         ticket_account: Pubkey,
         beneficiary: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn emergency_pause(
         &'a self,
         pause_authority: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn emergency_resume(
         &'a self,
         pause_authority: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn withdraw_stake_account(
         &'a self,
@@ -228,12 +229,12 @@ pub trait MarinadeRequestBuilder<'a, C> {
         stake_index: u32,
         msol_amount: u64,
         beneficiary: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 
     fn finalize_delinquent_upgrade(
         &'a self,
         max_validators: u32,
-    ) -> anyhow::Result<RequestBuilder<C>>;
+    ) -> Result<RequestBuilder<C>, MarinadeClientError>;
 }
 
 impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> for RpcMarinade<C> {
@@ -243,7 +244,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         validator_vote: Pubkey,
         score: u32,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = add_validator(
             &self.program,
@@ -268,7 +269,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         validator_vote: Pubkey,
         validator_index: u32,
         score: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = set_validator_score(
             &self.program,
@@ -288,7 +289,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         &'a self,
         validator_manager_authority: &'a PubkeyOrKeypair,
         extra_runs: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = config_validator_system(
             &self.program,
@@ -308,7 +309,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = emergency_unstake(
             &self.program,
@@ -329,7 +330,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         validator_manager_authority: &'a PubkeyOrKeypair,
         validator_vote: Pubkey,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = remove_validator(
             &self.program,
@@ -349,7 +350,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         transfer_from: &'a PubkeyOrKeypair,
         mint_to: Pubkey,
         lamports: u64,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = add_liquidity(
             &self.program,
             &self.instance_pubkey,
@@ -368,7 +369,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         &'a self,
         admin_authority: &'a PubkeyOrKeypair,
         params: ChangeAuthorityData,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_admin_authority(&self.state, &admin_authority.pubkey())?;
         let mut builder =
             change_authority(&self.program, &self.instance_pubkey, &self.state, params)?;
@@ -385,7 +386,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         split_stake_rent_payer: &'a PubkeyOrKeypair,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = deactivate_stake(
             &self.program,
             &self.instance_pubkey,
@@ -410,7 +411,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         transfer_from: &'a PubkeyOrKeypair,
         mint_to: Pubkey,
         lamports: u64,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = deposit(
             &self.program,
             &self.instance_pubkey,
@@ -433,7 +434,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         validator_index: u32,
         validator_vote: Pubkey,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = deposit_stake_account(
             &self.program,
             &self.instance_pubkey,
@@ -463,7 +464,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         split_stake_account: &'a PubkeyOrKeypair,
         split_stake_rent_payer: &'a PubkeyOrKeypair,
         desired_amount: u64,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_manager_authority(&self.state, &validator_manager_authority.pubkey())?;
         let mut builder = partial_unstake(
             &self.program,
@@ -499,7 +500,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         lp_mint: Pubkey,
         liq_pool_msol_leg: Pubkey,
         data: marinade_finance::instructions::InitializeData,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         Ok(initialize(
             &self.program,
             &state.pubkey(),
@@ -521,7 +522,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         get_msol_from_authority: &'a PubkeyOrKeypair,
         transfer_sol_to: Pubkey,
         msol_amount: u64,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = liquid_unstake(
             &self.program,
             &self.instance_pubkey,
@@ -544,7 +545,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         source_stake: Pubkey,
         source_stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let builder = merge_stakes(
             &self.program,
             &self.instance_pubkey,
@@ -564,7 +565,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         source_stake_index: u32,
         validator_index: u32,
         validator_vote: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         create_canonical_stake(
             &self.program,
             &self.instance_pubkey,
@@ -583,7 +584,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         transfer_sol_to: Pubkey,
         transfer_msol_to: Pubkey,
         tokens: u64,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = remove_liquidity(
             &self.program,
             &self.instance_pubkey,
@@ -607,7 +608,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         max_fee: Option<Fee>,
         liquidity_target: Option<u64>,
         treasury_bp_cut: Option<Fee>,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_admin_authority(&self.state, &admin_authority.pubkey())?;
         let mut builder = config_lp(
             &self.program,
@@ -628,7 +629,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         &'a self,
         admin_authority: &'a PubkeyOrKeypair,
         params: ConfigMarinadeParams,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_admin_authority(&self.state, &admin_authority.pubkey())?;
         let mut builder =
             config_marinade(&self.program, &self.instance_pubkey, &self.state, params)?;
@@ -644,7 +645,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         validator_vote: Pubkey,
         stake_account: &'a PubkeyOrKeypair,
         rent_payer: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = stake_reserve(
             &self.program,
             &self.instance_pubkey,
@@ -668,7 +669,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         update_active(
             &self.program,
             &self.instance_pubkey,
@@ -684,7 +685,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         stake_account: Pubkey,
         stake_index: u32,
         validator_index: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         update_deactivated(
             &self.program,
             &self.instance_pubkey,
@@ -701,7 +702,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         burn_msol_from_authority: &'a PubkeyOrKeypair,
         msol_amount: u64,
         ticket_account: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = order_unstake(
             &self.program,
             &self.instance_pubkey,
@@ -721,7 +722,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         &'a self,
         ticket_account: Pubkey,
         beneficiary: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         claim(
             &self.program,
             &self.instance_pubkey,
@@ -733,7 +734,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
     fn emergency_pause(
         &'a self,
         pause_authority: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_pause_authority(&self.state, &pause_authority.pubkey())?;
         let mut builder = emergency_pause(&self.program, &self.instance_pubkey, &self.state)?;
         if let Some(signer) = pause_authority.use_keypair() {
@@ -745,7 +746,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
     fn emergency_resume(
         &'a self,
         pause_authority: &'a PubkeyOrKeypair,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         verify_pause_authority(&self.state, &pause_authority.pubkey())?;
         let mut builder = emergency_resume(&self.program, &self.instance_pubkey, &self.state)?;
         if let Some(signer) = pause_authority.use_keypair() {
@@ -765,7 +766,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
         stake_index: u32,
         msol_amount: u64,
         beneficiary: Pubkey,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         let mut builder = withdraw_stake_account(
             &self.program,
             &self.instance_pubkey,
@@ -795,7 +796,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> MarinadeRequestBuilder<'a, C> f
     fn finalize_delinquent_upgrade(
         &'a self,
         max_validators: u32,
-    ) -> anyhow::Result<RequestBuilder<C>> {
+    ) -> Result<RequestBuilder<C>, MarinadeClientError> {
         finalize_delinquent_upgrade(
             &self.program,
             &self.instance_pubkey,
@@ -0,0 +1,645 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::transaction_diagnostic::marinade_instruction_name;
+use crate::transactions::transaction_instruction::TransactionInstruction;
+use anchor_lang::{AnchorDeserialize, ToAccountMetas};
+use marinade_finance::{
+    accounts as marinade_finance_accounts, instruction as marinade_finance_instruction,
+};
+use serde::Serialize;
+use solana_sdk::hash::hashv;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedAccount {
+    /// Name of the account in the `marinade_finance::accounts` struct of the instruction.
+    pub role: Option<String>,
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedArg {
+    pub name: String,
+    pub value: String,
+}
+
+/// Instruction with the Marinade instruction name, args and account roles decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedInstruction {
+    pub program_id: String,
+    pub name: Option<String>,
+    pub args: Vec<DecodedArg>,
+    pub accounts: Vec<DecodedAccount>,
+    /// Base64 instruction data when the instruction is not a known Marinade instruction.
+    pub data: Option<String>,
+}
+
+impl DecodedInstruction {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Display for DecodedInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Instruction: {}",
+            self.name.as_deref().unwrap_or("<unknown>")
+        )?;
+        writeln!(f, "  program: {}", self.program_id)?;
+        if !self.args.is_empty() {
+            writeln!(f, "  args:")?;
+            for arg in &self.args {
+                writeln!(f, "    {}: {}", arg.name, arg.value)?;
+            }
+        }
+        if let Some(data) = &self.data {
+            writeln!(f, "  data: {}", data)?;
+        }
+        writeln!(f, "  accounts:")?;
+        for (index, account) in self.accounts.iter().enumerate() {
+            writeln!(
+                f,
+                "    #{} {}: {}{}{}",
+                index,
+                account.role.as_deref().unwrap_or("<unknown>"),
+                account.pubkey,
+                if account.is_writable {
+                    " [writable]"
+                } else {
+                    ""
+                },
+                if account.is_signer { " [signer]" } else { "" },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the base64 instruction printed by [crate::transactions::transaction_instruction::print_base64].
+pub fn decode_base64_instruction(
+    base64: &str,
+    marinade_program_id: &Pubkey,
+) -> Result<DecodedInstruction, MarinadeClientError> {
+    let instruction = Instruction::from(&TransactionInstruction::from_base64(base64)?);
+    decode_instruction(&instruction, marinade_program_id)
+}
+
+/// Decodes the instruction of the Marinade program, the instruction of any other program
+/// is returned with the accounts and raw data only.
+pub fn decode_instruction(
+    instruction: &Instruction,
+    marinade_program_id: &Pubkey,
+) -> Result<DecodedInstruction, MarinadeClientError> {
+    let name = if instruction.program_id == *marinade_program_id {
+        marinade_instruction_name(&instruction.data)
+    } else {
+        None
+    };
+    let (args, roles) = match name {
+        Some(name) => (
+            decode_args(name, &instruction.data[8..])?,
+            account_roles(name),
+        ),
+        None => (vec![], vec![]),
+    };
+    Ok(DecodedInstruction {
+        program_id: instruction.program_id.to_string(),
+        name: name.map(|name| name.to_string()),
+        args,
+        accounts: instruction
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(index, account)| DecodedAccount {
+                role: roles.get(index).cloned().flatten(),
+                pubkey: account.pubkey.to_string(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: name
+            .is_none()
+            .then(|| anchor_lang::__private::base64::encode(&instruction.data)),
+    })
+}
+
+macro_rules! decode_args {
+    ($data:expr, $instruction:ident {}) => {{
+        marinade_finance_instruction::$instruction::deserialize(&mut &$data[..]).map_err(|e| {
+            MarinadeClientError::deserialization(stringify!($instruction), e)
+        })?;
+        vec![]
+    }};
+    ($data:expr, $instruction:ident { $($field:ident),+ }) => {{
+        let args = marinade_finance_instruction::$instruction::deserialize(&mut &$data[..])
+            .map_err(|e| MarinadeClientError::deserialization(stringify!($instruction), e))?;
+        vec![$(DecodedArg {
+            name: stringify!($field).to_string(),
+            value: format!("{:?}", args.$field),
+        }),+]
+    }};
+}
+
+fn decode_args(name: &str, data: &[u8]) -> Result<Vec<DecodedArg>, MarinadeClientError> {
+    Ok(match name {
+        "Initialize" => decode_args!(data, Initialize { data }),
+        "ChangeAuthority" => decode_args!(data, ChangeAuthority { data }),
+        "AddValidator" => decode_args!(data, AddValidator { score }),
+        "RemoveValidator" => decode_args!(
+            data,
+            RemoveValidator {
+                index,
+                validator_vote
+            }
+        ),
+        "SetValidatorScore" => {
+            decode_args!(
+                data,
+                SetValidatorScore {
+                    index,
+                    validator_vote,
+                    score
+                }
+            )
+        }
+        "ConfigValidatorSystem" => decode_args!(data, ConfigValidatorSystem { extra_runs }),
+        "Deposit" => decode_args!(data, Deposit { lamports }),
+        "DepositStakeAccount" => decode_args!(data, DepositStakeAccount { validator_index }),
+        "LiquidUnstake" => decode_args!(data, LiquidUnstake { msol_amount }),
+        "AddLiquidity" => decode_args!(data, AddLiquidity { lamports }),
+        "RemoveLiquidity" => decode_args!(data, RemoveLiquidity { tokens }),
+        "ConfigLp" => decode_args!(data, ConfigLp { params }),
+        "ConfigMarinade" => decode_args!(data, ConfigMarinade { params }),
+        "OrderUnstake" => decode_args!(data, OrderUnstake { msol_amount }),
+        "Claim" => decode_args!(data, Claim {}),
+        "StakeReserve" => decode_args!(data, StakeReserve { validator_index }),
+        "UpdateActive" => decode_args!(
+            data,
+            UpdateActive {
+                stake_index,
+                validator_index
+            }
+        ),
+        "UpdateDeactivated" => decode_args!(
+            data,
+            UpdateDeactivated {
+                stake_index,
+                validator_index
+            }
+        ),
+        "DeactivateStake" => {
+            decode_args!(
+                data,
+                DeactivateStake {
+                    stake_index,
+                    validator_index
+                }
+            )
+        }
+        "EmergencyUnstake" => {
+            decode_args!(
+                data,
+                EmergencyUnstake {
+                    stake_index,
+                    validator_index
+                }
+            )
+        }
+        "PartialUnstake" => decode_args!(
+            data,
+            PartialUnstake {
+                stake_index,
+                validator_index,
+                desired_unstake_amount
+            }
+        ),
+        "MergeStakes" => decode_args!(
+            data,
+            MergeStakes {
+                destination_stake_index,
+                source_stake_index,
+                validator_index
+            }
+        ),
+        "CreateCanonicalStake" => decode_args!(
+            data,
+            CreateCanonicalStake {
+                source_stake_index,
+                validator_index
+            }
+        ),
+        "Pause" => decode_args!(data, Pause {}),
+        "Resume" => decode_args!(data, Resume {}),
+        "WithdrawStakeAccount" => decode_args!(
+            data,
+            WithdrawStakeAccount {
+                stake_index,
+                validator_index,
+                msol_amount,
+                beneficiary
+            }
+        ),
+        "FinalizeDelinquentUpgrade" => {
+            decode_args!(data, FinalizeDelinquentUpgrade { max_validators })
+        }
+        _ => vec![],
+    })
+}
+
+/// Pubkey standing for the account role, the order of the roles is taken
+/// from the account metas of the accounts struct filled with these pubkeys.
+fn role_marker(role: &str) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[role.as_bytes()]).to_bytes())
+}
+
+macro_rules! marked_accounts {
+    ($prefix:literal, $accounts:ident { $($field:ident),+ $(,)? } $(, $nested:ident: $value:expr)*) => {(
+        marinade_finance_accounts::$accounts {
+            $($field: role_marker(concat!($prefix, stringify!($field))),)+
+            $($nested: $value,)*
+        },
+        vec![$(concat!($prefix, stringify!($field))),+],
+    )};
+}
+
+fn role_names(accounts: &impl ToAccountMetas, roles: &[&str]) -> Vec<Option<String>> {
+    accounts
+        .to_account_metas(None)
+        .iter()
+        .map(|account_meta| {
+            roles
+                .iter()
+                .find(|role| role_marker(role) == account_meta.pubkey)
+                .map(|role| role.to_string())
+        })
+        .collect()
+}
+
+macro_rules! flat_roles {
+    ($accounts:ident { $($field:ident),+ $(,)? }) => {{
+        let (accounts, roles) = marked_accounts!("", $accounts { $($field),+ });
+        role_names(&accounts, &roles)
+    }};
+}
+
+/// Account role names of the Marinade instruction in the order of the instruction accounts.
+fn account_roles(name: &str) -> Vec<Option<String>> {
+    match name {
+        "Initialize" => {
+            let (liq_pool, mut roles) = marked_accounts!(
+                "liq_pool.",
+                LiqPoolInitialize {
+                    lp_mint,
+                    sol_leg_pda,
+                    msol_leg
+                }
+            );
+            let (accounts, initialize_roles) = marked_accounts!(
+                "",
+                Initialize {
+                    state,
+                    reserve_pda,
+                    stake_list,
+                    validator_list,
+                    msol_mint,
+                    operational_sol_account,
+                    treasury_msol_account,
+                    clock,
+                    rent,
+                },
+                liq_pool: liq_pool
+            );
+            roles.extend(initialize_roles);
+            role_names(&accounts, &roles)
+        }
+        "UpdateActive" | "UpdateDeactivated" => {
+            let (common, mut roles) = marked_accounts!(
+                "common.",
+                UpdateCommon {
+                    state,
+                    stake_list,
+                    stake_account,
+                    stake_withdraw_authority,
+                    reserve_pda,
+                    msol_mint,
+                    clock,
+                    stake_history,
+                    msol_mint_authority,
+                    treasury_msol_account,
+                    token_program,
+                    stake_program,
+                    validator_list,
+                }
+            );
+            if name == "UpdateActive" {
+                return role_names(&marinade_finance_accounts::UpdateActive { common }, &roles);
+            }
+            let (accounts, deactivated_roles) = marked_accounts!(
+                "",
+                UpdateDeactivated {
+                    operational_sol_account,
+                    system_program
+                },
+                common: common
+            );
+            roles.extend(deactivated_roles);
+            role_names(&accounts, &roles)
+        }
+        "ChangeAuthority" => flat_roles!(ChangeAuthority {
+            state,
+            admin_authority
+        }),
+        "AddValidator" => flat_roles!(AddValidator {
+            state,
+            manager_authority,
+            validator_list,
+            validator_vote,
+            duplication_flag,
+            rent_payer,
+            clock,
+            rent,
+            system_program,
+        }),
+        "RemoveValidator" => flat_roles!(RemoveValidator {
+            state,
+            manager_authority,
+            validator_list,
+            duplication_flag,
+            operational_sol_account,
+        }),
+        "SetValidatorScore" => flat_roles!(SetValidatorScore {
+            state,
+            manager_authority,
+            validator_list,
+        }),
+        "ConfigValidatorSystem" => flat_roles!(ConfigValidatorSystem {
+            state,
+            manager_authority
+        }),
+        "Deposit" => flat_roles!(Deposit {
+            state,
+            msol_mint,
+            liq_pool_sol_leg_pda,
+            liq_pool_msol_leg,
+            liq_pool_msol_leg_authority,
+            reserve_pda,
+            transfer_from,
+            mint_to,
+            msol_mint_authority,
+            system_program,
+            token_program,
+        }),
+        "DepositStakeAccount" => flat_roles!(DepositStakeAccount {
+            state,
+            validator_list,
+            stake_list,
+            stake_account,
+            stake_authority,
+            duplication_flag,
+            rent_payer,
+            msol_mint,
+            mint_to,
+            msol_mint_authority,
+            clock,
+            rent,
+            system_program,
+            token_program,
+            stake_program,
+        }),
+        "LiquidUnstake" => flat_roles!(LiquidUnstake {
+            state,
+            msol_mint,
+            liq_pool_sol_leg_pda,
+            liq_pool_msol_leg,
+            get_msol_from,
+            get_msol_from_authority,
+            transfer_sol_to,
+            treasury_msol_account,
+            system_program,
+            token_program,
+        }),
+        "AddLiquidity" => flat_roles!(AddLiquidity {
+            state,
+            lp_mint,
+            lp_mint_authority,
+            liq_pool_msol_leg,
+            liq_pool_sol_leg_pda,
+            transfer_from,
+            mint_to,
+            system_program,
+            token_program,
+        }),
+        "RemoveLiquidity" => flat_roles!(RemoveLiquidity {
+            state,
+            lp_mint,
+            burn_from,
+            burn_from_authority,
+            transfer_sol_to,
+            transfer_msol_to,
+            liq_pool_sol_leg_pda,
+            liq_pool_msol_leg,
+            liq_pool_msol_leg_authority,
+            system_program,
+            token_program,
+        }),
+        "ConfigLp" => flat_roles!(ConfigLp {
+            state,
+            admin_authority
+        }),
+        "ConfigMarinade" => flat_roles!(ConfigMarinade {
+            state,
+            admin_authority
+        }),
+        "OrderUnstake" => flat_roles!(OrderUnstake {
+            state,
+            msol_mint,
+            burn_msol_from,
+            burn_msol_authority,
+            new_ticket_account,
+            token_program,
+            clock,
+            rent,
+        }),
+        "Claim" => flat_roles!(Claim {
+            state,
+            reserve_pda,
+            ticket_account,
+            transfer_sol_to,
+            system_program,
+            clock,
+        }),
+        "StakeReserve" => flat_roles!(StakeReserve {
+            state,
+            validator_list,
+            stake_list,
+            validator_vote,
+            reserve_pda,
+            stake_account,
+            stake_deposit_authority,
+            rent_payer,
+            clock,
+            epoch_schedule,
+            rent,
+            stake_history,
+            stake_config,
+            system_program,
+            stake_program,
+        }),
+        "DeactivateStake" => flat_roles!(DeactivateStake {
+            state,
+            reserve_pda,
+            validator_list,
+            stake_list,
+            stake_account,
+            stake_deposit_authority,
+            split_stake_account,
+            split_stake_rent_payer,
+            clock,
+            rent,
+            epoch_schedule,
+            stake_history,
+            system_program,
+            stake_program,
+        }),
+        "EmergencyUnstake" => flat_roles!(EmergencyUnstake {
+            state,
+            validator_manager_authority,
+            validator_list,
+            stake_list,
+            stake_account,
+            stake_deposit_authority,
+            clock,
+            stake_program,
+        }),
+        "PartialUnstake" => flat_roles!(PartialUnstake {
+            state,
+            validator_manager_authority,
+            validator_list,
+            stake_list,
+            stake_account,
+            stake_deposit_authority,
+            reserve_pda,
+            split_stake_account,
+            split_stake_rent_payer,
+            clock,
+            rent,
+            stake_history,
+            system_program,
+            stake_program,
+        }),
+        "MergeStakes" => flat_roles!(MergeStakes {
+            state,
+            stake_list,
+            validator_list,
+            destination_stake,
+            source_stake,
+            stake_deposit_authority,
+            stake_withdraw_authority,
+            operational_sol_account,
+            clock,
+            stake_history,
+            stake_program,
+        }),
+        "CreateCanonicalStake" => flat_roles!(CreateCanonicalStake {
+            state,
+            stake_list,
+            validator_list,
+            canonical_stake,
+            source_stake,
+            stake_deposit_authority,
+            stake_withdraw_authority,
+            operational_sol_account,
+            clock,
+            stake_history,
+            stake_program,
+            system_program,
+        }),
+        "Pause" | "Resume" => flat_roles!(EmergencyPause {
+            state,
+            pause_authority
+        }),
+        "WithdrawStakeAccount" => flat_roles!(WithdrawStakeAccount {
+            state,
+            msol_mint,
+            burn_msol_from,
+            burn_msol_authority,
+            treasury_msol_account,
+            validator_list,
+            stake_list,
+            stake_withdraw_authority,
+            stake_deposit_authority,
+            stake_account,
+            split_stake_account,
+            split_stake_rent_payer,
+            clock,
+            system_program,
+            token_program,
+            stake_program,
+        }),
+        "FinalizeDelinquentUpgrade" => flat_roles!(FinalizeDelinquentUpgrade {
+            state,
+            validator_list
+        }),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::InstructionData;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn test_decode_deposit() {
+        let accounts = marinade_finance_accounts::Deposit {
+            state: Pubkey::new_unique(),
+            msol_mint: Pubkey::new_unique(),
+            liq_pool_sol_leg_pda: Pubkey::new_unique(),
+            liq_pool_msol_leg: Pubkey::new_unique(),
+            liq_pool_msol_leg_authority: Pubkey::new_unique(),
+            reserve_pda: Pubkey::new_unique(),
+            transfer_from: Pubkey::new_unique(),
+            mint_to: Pubkey::new_unique(),
+            msol_mint_authority: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        };
+        let instruction = Instruction {
+            program_id: marinade_finance::ID,
+            accounts: accounts.to_account_metas(None),
+            data: marinade_finance_instruction::Deposit { lamports: 42 }.data(),
+        };
+        let decoded = decode_instruction(&instruction, &marinade_finance::ID).unwrap();
+        assert_eq!(decoded.name.as_deref(), Some("Deposit"));
+        assert_eq!(
+            decoded.args,
+            vec![DecodedArg {
+                name: "lamports".to_string(),
+                value: "42".to_string(),
+            }]
+        );
+        let transfer_from = decoded
+            .accounts
+            .iter()
+            .find(|account| account.role.as_deref() == Some("transfer_from"))
+            .unwrap();
+        assert_eq!(transfer_from.pubkey, accounts.transfer_from.to_string());
+        assert!(decoded.data.is_none());
+
+        let other_instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(Pubkey::new_unique(), true)],
+        );
+        let decoded = decode_instruction(&other_instruction, &marinade_finance::ID).unwrap();
+        assert_eq!(decoded.name, None);
+        assert_eq!(decoded.accounts[0].role, None);
+        assert_eq!(decoded.data.as_deref(), Some("AQID"));
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(clippy::too_many_arguments)]
+use crate::error::MarinadeClientError;
 use anchor_client::{Program, RequestBuilder};
 use marinade_finance::state::liq_pool::LiqPool;
 use marinade_finance::state::stake_system::StakeSystem;
@@ -19,7 +20,7 @@ pub fn add_validator<'a, C: Deref<Target = impl Signer> + Clone>(
     validator_vote: &Pubkey,
     score: u32,
     rent_payer: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::AddValidator {
@@ -45,7 +46,7 @@ pub fn config_validator_system<'a, C: Deref<Target = impl Signer> + Clone>(
     state_pubkey: &Pubkey,
     state: &'a State,
     extra_runs: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::ConfigValidatorSystem {
@@ -62,7 +63,7 @@ pub fn set_validator_score<'a, C: Deref<Target = impl Signer> + Clone>(
     validator_vote: &Pubkey,
     validator_index: u32,
     score: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::SetValidatorScore {
@@ -83,7 +84,7 @@ pub fn remove_validator<'a, C: Deref<Target = impl Signer> + Clone>(
     state: &State,
     validator_vote: &Pubkey,
     index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::RemoveValidator {
@@ -110,7 +111,7 @@ pub fn emergency_unstake<'a, C: Deref<Target = impl Signer> + Clone>(
     stake_account: &Pubkey,
     stake_index: u32,
     validator_index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::EmergencyUnstake {
@@ -136,7 +137,7 @@ pub fn add_liquidity<'a, C: Deref<Target = impl Signer> + Clone>(
     transfer_from: &Pubkey,
     mint_to: &Pubkey,
     lamports: u64,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::AddLiquidity {
@@ -158,7 +159,7 @@ pub fn change_authority<'a, C: Deref<Target = impl Signer> + Clone>(
     state_pubkey: &Pubkey,
     state: &State,
     data: marinade_finance::instructions::ChangeAuthorityData,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::ChangeAuthority {
@@ -177,7 +178,7 @@ pub fn deactivate_stake<'a, C: Deref<Target = impl Signer> + Clone>(
     split_stake_rent_payer: &Pubkey,
     stake_index: u32,
     validator_index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::DeactivateStake {
@@ -209,7 +210,7 @@ pub fn deposit<'a, C: Deref<Target = impl Signer> + Clone>(
     transfer_from: &Pubkey,
     mint_to: &Pubkey,
     lamports: u64,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::Deposit {
@@ -238,7 +239,7 @@ pub fn deposit_stake_account<'a, C: Deref<Target = impl Signer> + Clone>(
     validator_index: u32,
     validator_vote: &Pubkey,
     rent_payer: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::DepositStakeAccount {
@@ -278,7 +279,7 @@ pub fn withdraw_stake_account<'a, C: Deref<Target = impl Signer> + Clone>(
     stake_index: u32,
     msol_amount: u64,
     beneficiary: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::WithdrawStakeAccount {
@@ -317,7 +318,7 @@ pub fn partial_unstake<'a, C: Deref<Target = impl Signer> + Clone>(
     split_stake_account: &Pubkey,
     split_stake_rent_payer: &Pubkey,
     desired_unstake_amount: u64,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::PartialUnstake {
@@ -354,7 +355,7 @@ pub fn initialize<'a, C: Deref<Target = impl Signer> + Clone>(
     lp_mint: &Pubkey,
     liq_pool_msol_leg: &Pubkey,
     data: marinade_finance::instructions::InitializeData,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::Initialize {
@@ -384,7 +385,7 @@ pub fn liquid_unstake<'a, C: Deref<Target = impl Signer> + Clone>(
     get_msol_from_authority: &Pubkey,
     transfer_sol_to: &Pubkey,
     msol_amount: u64,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::LiquidUnstake {
@@ -411,7 +412,7 @@ pub fn merge_stakes<'a, C: Deref<Target = impl Signer> + Clone>(
     source_stake: &Pubkey,
     source_stake_index: u32,
     validator_index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::MergeStakes {
@@ -442,7 +443,7 @@ pub fn create_canonical_stake<'a, C: Deref<Target = impl Signer> + Clone>(
     source_stake_index: u32,
     validator_index: u32,
     validator_vote: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::CreateCanonicalStake {
@@ -474,7 +475,7 @@ pub fn remove_liquidity<'a, C: Deref<Target = impl Signer> + Clone>(
     transfer_sol_to: &Pubkey,
     transfer_msol_to: &Pubkey,
     tokens: u64,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::RemoveLiquidity {
@@ -501,7 +502,7 @@ pub fn config_lp<'a, C: Deref<Target = impl Signer> + Clone>(
     max_fee: Option<Fee>,
     liquidity_target: Option<u64>,
     treasury_bp_cut: Option<Fee>,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::ConfigLp {
@@ -523,7 +524,7 @@ pub fn config_marinade<'a, C: Deref<Target = impl Signer> + Clone>(
     state_pubkey: &Pubkey,
     state: &State,
     params: marinade_finance::instructions::ConfigMarinadeParams,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::ConfigMarinade {
@@ -541,7 +542,7 @@ pub fn stake_reserve<'a, C: Deref<Target = impl Signer> + Clone>(
     validator_vote: &Pubkey,
     stake_account: &Pubkey,
     rent_payer: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::StakeReserve {
@@ -571,7 +572,7 @@ pub fn update_active<'a, C: Deref<Target = impl Signer> + Clone>(
     stake_account: &Pubkey,
     stake_index: u32,
     validator_index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::UpdateActive {
@@ -605,7 +606,7 @@ pub fn update_deactivated<'a, C: Deref<Target = impl Signer> + Clone>(
     stake_account: &Pubkey,
     stake_index: u32,
     validator_index: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::UpdateDeactivated {
@@ -639,7 +640,7 @@ pub fn claim<'a, C: Deref<Target = impl Signer> + Clone>(
     state_pubkey: &Pubkey,
     ticket_account: &Pubkey,
     transfer_sol_to: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::Claim {
@@ -661,7 +662,7 @@ pub fn order_unstake<'a, C: Deref<Target = impl Signer> + Clone>(
     burn_msol_from_authority: &Pubkey, // delegated or owner
     msol_amount: u64,
     new_ticket_account: &Pubkey,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::OrderUnstake {
@@ -681,7 +682,7 @@ pub fn emergency_pause<'a, C: Deref<Target = impl Signer> + Clone>(
     program: &'a Program<C>,
     state_pubkey: &Pubkey,
     state: &State,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::EmergencyPause {
@@ -695,7 +696,7 @@ pub fn emergency_resume<'a, C: Deref<Target = impl Signer> + Clone>(
     program: &'a Program<C>,
     state_pubkey: &Pubkey,
     state: &State,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::EmergencyPause {
@@ -710,7 +711,7 @@ pub fn finalize_delinquent_upgrade<'a, C: Deref<Target = impl Signer> + Clone>(
     state_pubkey: &Pubkey,
     state: &State,
     max_validators: u32,
-) -> anyhow::Result<RequestBuilder<'a, C>> {
+) -> Result<RequestBuilder<'a, C>, MarinadeClientError> {
     Ok(program
         .request()
         .accounts(marinade_finance_accounts::FinalizeDelinquentUpgrade {
@@ -1,5 +1,8 @@
+pub mod accounts_diff;
 pub mod builder;
+pub mod instruction_decoder;
 pub mod instructions;
+pub mod nonblocking;
 pub mod rpc_marinade;
 pub mod state;
 pub mod verifiers;
@@ -0,0 +1,83 @@
+use crate::error::MarinadeClientError;
+use crate::marinade::state::{
+    parse_stake_list, parse_stakes_info_batch, parse_validator_list, StakeInfo,
+    STAKE_ACCOUNTS_BATCH_SIZE,
+};
+use marinade_finance::state::stake_system::StakeRecord;
+use marinade_finance::state::validator_system::ValidatorRecord;
+use marinade_finance::state::State;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::clock::Clock;
+
+pub async fn validator_list(
+    rpc_client: &RpcClient,
+    state: &State,
+) -> Result<(Vec<ValidatorRecord>, u32), MarinadeClientError> {
+    let validator_list_account_data = rpc_client
+        .get_account_data(state.validator_system.validator_list_address())
+        .await?;
+    parse_validator_list(state, &validator_list_account_data)
+}
+
+pub async fn stake_list(
+    rpc_client: &RpcClient,
+    state: &State,
+) -> Result<(Vec<StakeRecord>, u32), MarinadeClientError> {
+    let stake_list_account_data = rpc_client
+        .get_account_data(state.stake_system.stake_list_address())
+        .await?;
+    parse_stake_list(state, &stake_list_account_data)
+}
+
+/// composes a Vec<StakeInfo> from each account in stake_list
+/// StakeInfo includes {index, account data, stake & current balance }
+pub async fn stakes_info(
+    rpc_client: &RpcClient,
+    state: &State,
+) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
+    let (stake_list, stakes_max_capacity) = stake_list(rpc_client, state).await?;
+
+    let mut result_vec: Vec<StakeInfo> = Vec::new();
+
+    let to_process = stake_list.len();
+    let mut processed = 0;
+    while processed < to_process {
+        let accounts = rpc_client
+            .get_multiple_accounts(
+                &stake_list
+                    .iter()
+                    .map(|record| record.stake_account)
+                    .skip(processed)
+                    .take(STAKE_ACCOUNTS_BATCH_SIZE)
+                    .collect::<Vec<_>>(),
+            )
+            .await?;
+        result_vec.append(&mut parse_stakes_info_batch(
+            &stake_list,
+            processed,
+            accounts,
+        )?);
+        processed += STAKE_ACCOUNTS_BATCH_SIZE;
+    }
+    Ok((result_vec, stakes_max_capacity))
+}
+
+/// The vec is returned **reversed**, see [crate::marinade::state::stakes_info_reversed].
+pub async fn stakes_info_reversed(
+    rpc_client: &RpcClient,
+    state: &State,
+) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
+    let (mut vec, stakes_capacity) = stakes_info(rpc_client, state).await?;
+    // reverse vector (last indexes should be processed first)
+    vec.reverse();
+    Ok((vec, stakes_capacity))
+}
+
+pub async fn get_clock(rpc_client: &RpcClient) -> Result<Clock, MarinadeClientError> {
+    bincode::deserialize(
+        &rpc_client
+            .get_account_data(&solana_sdk::sysvar::clock::ID)
+            .await?,
+    )
+    .map_err(|e| MarinadeClientError::deserialization("clock sysvar", e))
+}
@@ -1,3 +1,4 @@
+use crate::error::MarinadeClientError;
 use crate::marinade::state::{
     stake_list, stakes_info, stakes_info_reversed, validator_list, StakeInfo,
 };
@@ -23,9 +24,9 @@ impl<C: Deref<Target = impl Signer> + Clone> RpcMarinade<C> {
         anchor_client: &Client<C>,
         program_pubkey: Pubkey,
         instance_pubkey: Pubkey,
-    ) -> anyhow::Result<Self> {
+    ) -> Result<Self, MarinadeClientError> {
         let program = anchor_client.program(program_pubkey);
-        let state: State = program.account(instance_pubkey)?;
+        let state = load_state(&program, &instance_pubkey)?;
         Ok(Self {
             client: program.rpc(),
             program,
@@ -35,24 +36,36 @@ impl<C: Deref<Target = impl Signer> + Clone> RpcMarinade<C> {
         })
     }
 
-    pub fn update(&mut self) -> anyhow::Result<()> {
-        self.state = self.program.account(self.instance_pubkey)?;
+    pub fn update(&mut self) -> Result<(), MarinadeClientError> {
+        self.state = load_state(&self.program, &self.instance_pubkey)?;
         Ok(())
     }
 
-    pub fn validator_list(&self) -> anyhow::Result<(Vec<ValidatorRecord>, u32)> {
+    pub fn validator_list(&self) -> Result<(Vec<ValidatorRecord>, u32), MarinadeClientError> {
         validator_list(&self.client, &self.state)
     }
 
-    pub fn stake_list(&self) -> anyhow::Result<(Vec<StakeRecord>, u32)> {
+    pub fn stake_list(&self) -> Result<(Vec<StakeRecord>, u32), MarinadeClientError> {
         stake_list(&self.client, &self.state)
     }
 
-    pub fn stakes_info(&self) -> anyhow::Result<(Vec<StakeInfo>, u32)> {
+    pub fn stakes_info(&self) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
         stakes_info(&self.client, &self.state)
     }
 
-    pub fn stakes_info_reversed(&self) -> anyhow::Result<(Vec<StakeInfo>, u32)> {
+    pub fn stakes_info_reversed(&self) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
         stakes_info_reversed(&self.client, &self.state)
     }
 }
+
+fn load_state<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    instance_pubkey: &Pubkey,
+) -> Result<State, MarinadeClientError> {
+    program.account(*instance_pubkey).map_err(|err| match err {
+        anchor_client::ClientError::AccountNotFound => {
+            MarinadeClientError::AccountNotFound(*instance_pubkey)
+        }
+        err => err.into(),
+    })
+}
@@ -1,27 +1,43 @@
+use crate::error::MarinadeClientError;
 use anchor_lang::AnchorDeserialize;
-use anyhow::bail;
 use marinade_finance::state::stake_system::StakeRecord;
 use marinade_finance::state::validator_system::ValidatorRecord;
 use marinade_finance::state::State;
 use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
 use solana_sdk::clock::Clock;
 use solana_sdk::stake::state::StakeState;
 
 pub fn validator_list(
     rpc_client: &RpcClient,
     state: &State,
-) -> anyhow::Result<(Vec<ValidatorRecord>, u32)> {
+) -> Result<(Vec<ValidatorRecord>, u32), MarinadeClientError> {
     let validator_list_account_data =
         rpc_client.get_account_data(state.validator_system.validator_list_address())?;
+    parse_validator_list(state, &validator_list_account_data)
+}
+
+pub(crate) fn parse_validator_list(
+    state: &State,
+    validator_list_account_data: &[u8],
+) -> Result<(Vec<ValidatorRecord>, u32), MarinadeClientError> {
     let validator_record_size = state.validator_system.validator_record_size() as usize;
 
     Ok((
         (0..state.validator_system.validator_count())
             .map(|index| {
-                let start = 8 + index as usize * validator_record_size;
-                ValidatorRecord::deserialize(
-                    &mut &validator_list_account_data[start..(start + validator_record_size)],
-                )
+                let data = list_record_data(
+                    "validator list",
+                    validator_list_account_data,
+                    index,
+                    validator_record_size,
+                )?;
+                ValidatorRecord::deserialize(&mut &data[..]).map_err(|e| {
+                    MarinadeClientError::deserialization(
+                        format!("validator list record {}", index),
+                        e,
+                    )
+                })
             })
             .collect::<Result<Vec<_>, _>>()?,
         state
@@ -30,20 +46,49 @@ pub fn validator_list(
     ))
 }
 
+/// Data of the list record at the index, the list account starts with 8 bytes of the discriminator.
+fn list_record_data<'a>(
+    list: &'static str,
+    list_account_data: &'a [u8],
+    index: u32,
+    record_size: usize,
+) -> Result<&'a [u8], MarinadeClientError> {
+    let start = 8 + index as usize * record_size;
+    list_account_data
+        .get(start..(start + record_size))
+        .ok_or(MarinadeClientError::ListIndex {
+            list,
+            index,
+            len: list_account_data.len().saturating_sub(8) / record_size,
+        })
+}
+
 pub fn stake_list(
     rpc_client: &RpcClient,
     state: &State,
-) -> anyhow::Result<(Vec<StakeRecord>, u32)> {
+) -> Result<(Vec<StakeRecord>, u32), MarinadeClientError> {
     let stake_list_account_data =
         rpc_client.get_account_data(state.stake_system.stake_list_address())?;
+    parse_stake_list(state, &stake_list_account_data)
+}
+
+pub(crate) fn parse_stake_list(
+    state: &State,
+    stake_list_account_data: &[u8],
+) -> Result<(Vec<StakeRecord>, u32), MarinadeClientError> {
     let stake_record_size = state.stake_system.stake_record_size() as usize;
     Ok((
         (0..state.stake_system.stake_count())
             .map(|index| {
-                let start = 8 + index as usize * stake_record_size;
-                StakeRecord::deserialize(
-                    &mut &stake_list_account_data[start..(start + stake_record_size)],
-                )
+                let data = list_record_data(
+                    "stake list",
+                    stake_list_account_data,
+                    index,
+                    stake_record_size,
+                )?;
+                StakeRecord::deserialize(&mut &data[..]).map_err(|e| {
+                    MarinadeClientError::deserialization(format!("stake list record {}", index), e)
+                })
             })
             .collect::<Result<Vec<_>, _>>()?,
         state
@@ -54,51 +99,73 @@ pub fn stake_list(
 
 /// composes a Vec<StakeInfo> from each account in stake_list
 /// StakeInfo includes {index, account data, stake & current balance }
-pub fn stakes_info(rpc_client: &RpcClient, state: &State) -> anyhow::Result<(Vec<StakeInfo>, u32)> {
+pub fn stakes_info(
+    rpc_client: &RpcClient,
+    state: &State,
+) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
     let (stake_list, stakes_max_capacity) = stake_list(rpc_client, state)?;
 
     let mut result_vec: Vec<StakeInfo> = Vec::new();
 
     let to_process = stake_list.len();
     let mut processed = 0;
-    // rpc.get_multiple_accounts() has a max of 100 accounts
-    const BATCH_SIZE: usize = 100;
     while processed < to_process {
-        result_vec.append(
-            &mut rpc_client
-                .get_multiple_accounts(
-                    &stake_list
-                        .iter()
-                        .map(|record| record.stake_account)
-                        .skip(processed)
-                        .take(BATCH_SIZE)
-                        .collect::<Vec<_>>(),
-                )?
-                .into_iter()
-                .enumerate()
-                .map(|(index, maybe_account)| {
-                    if let Some(account) = maybe_account {
-                        let stake = bincode::deserialize(&account.data)?;
-                        Ok(StakeInfo {
-                            index: processed as u32 + index as u32,
-                            record: stake_list[processed + index],
-                            stake,
-                            balance: account.lamports,
-                        })
-                    } else {
-                        bail!(
-                            "Can not find account {} from stake list",
-                            stake_list[processed + index].stake_account
-                        );
-                    }
-                })
-                .collect::<Result<Vec<_>, _>>()?,
-        );
-        processed += BATCH_SIZE;
+        let accounts = rpc_client.get_multiple_accounts(
+            &stake_list
+                .iter()
+                .map(|record| record.stake_account)
+                .skip(processed)
+                .take(STAKE_ACCOUNTS_BATCH_SIZE)
+                .collect::<Vec<_>>(),
+        )?;
+        result_vec.append(&mut parse_stakes_info_batch(
+            &stake_list,
+            processed,
+            accounts,
+        )?);
+        processed += STAKE_ACCOUNTS_BATCH_SIZE;
     }
     Ok((result_vec, stakes_max_capacity))
 }
 
+// rpc.get_multiple_accounts() has a max of 100 accounts
+pub(crate) const STAKE_ACCOUNTS_BATCH_SIZE: usize = 100;
+
+/// Maps the stake accounts loaded for the stake list records starting at index `processed`.
+pub(crate) fn parse_stakes_info_batch(
+    stake_list: &[StakeRecord],
+    processed: usize,
+    accounts: Vec<Option<Account>>,
+) -> Result<Vec<StakeInfo>, MarinadeClientError> {
+    accounts
+        .into_iter()
+        .enumerate()
+        .map(|(index, maybe_account)| {
+            if let Some(account) = maybe_account {
+                let stake = bincode::deserialize(&account.data).map_err(|e| {
+                    MarinadeClientError::deserialization(
+                        format!(
+                            "stake account {}",
+                            stake_list[processed + index].stake_account
+                        ),
+                        e,
+                    )
+                })?;
+                Ok(StakeInfo {
+                    index: processed as u32 + index as u32,
+                    record: stake_list[processed + index],
+                    stake,
+                    balance: account.lamports,
+                })
+            } else {
+                Err(MarinadeClientError::AccountNotFound(
+                    stake_list[processed + index].stake_account,
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+}
+
 /// The vec is returned **reversed** meaning the last index is the first item.
 /// This is because when merging or deleting an account, the account record
 /// on the list on-chain is "removed". Removal is made by a "replace with last & list.count-=1"
@@ -107,17 +174,16 @@ pub fn stakes_info(rpc_client: &RpcClient, state: &State) -> anyhow::Result<(Vec
 pub fn stakes_info_reversed(
     rpc_client: &RpcClient,
     state: &State,
-) -> anyhow::Result<(Vec<StakeInfo>, u32)> {
+) -> Result<(Vec<StakeInfo>, u32), MarinadeClientError> {
     let (mut vec, stakes_capacity) = stakes_info(rpc_client, state)?;
     // reverse vector (last indexes should be processed first)
     vec.reverse();
     Ok((vec, stakes_capacity))
 }
 
-pub fn get_clock(rpc_client: &RpcClient) -> anyhow::Result<Clock> {
-    Ok(bincode::deserialize(
-        &rpc_client.get_account_data(&solana_sdk::sysvar::clock::ID)?,
-    )?)
+pub fn get_clock(rpc_client: &RpcClient) -> Result<Clock, MarinadeClientError> {
+    bincode::deserialize(&rpc_client.get_account_data(&solana_sdk::sysvar::clock::ID)?)
+        .map_err(|e| MarinadeClientError::deserialization("clock sysvar", e))
 }
 
 pub struct StakeInfo {
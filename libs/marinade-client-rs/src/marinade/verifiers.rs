@@ -1,49 +1,86 @@
-use anyhow::bail;
+use crate::error::MarinadeClientError;
 use marinade_finance::State;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::system_program;
 
+fn verify_authority(
+    authority: &'static str,
+    expected: &Pubkey,
+    provided: &Pubkey,
+) -> Result<(), MarinadeClientError> {
+    if expected != provided {
+        return Err(MarinadeClientError::AuthorityMismatch {
+            authority,
+            expected: *expected,
+            provided: *provided,
+        });
+    }
+    Ok(())
+}
+
 pub fn verify_manager_authority(
     state: &State,
     validator_manager_authority: &Pubkey,
-) -> anyhow::Result<()> {
-    if state.validator_system.manager_authority != *validator_manager_authority {
-        bail!("verify_manager_authority: validator-manager-authority {} to sign the transaction mismatches Marinade state system manager authority {}",
-                validator_manager_authority,
-                state.validator_system.manager_authority
-            );
-    }
-    Ok(())
+) -> Result<(), MarinadeClientError> {
+    verify_authority(
+        "validator manager authority",
+        &state.validator_system.manager_authority,
+        validator_manager_authority,
+    )
 }
 
-pub fn verify_admin_authority(state: &State, admin_authority: &Pubkey) -> anyhow::Result<()> {
-    if state.admin_authority != *admin_authority {
-        bail!("verify_admin_authority: admin-authority {} signing the transaction mismatches Marinade state admin authority: {}",
-                admin_authority,
-                state.admin_authority
-            );
-    }
-    Ok(())
+pub fn verify_admin_authority(
+    state: &State,
+    admin_authority: &Pubkey,
+) -> Result<(), MarinadeClientError> {
+    verify_authority("admin authority", &state.admin_authority, admin_authority)
 }
 
-pub fn verify_rent_payer(rpc_client: &RpcClient, rent_payer: &Pubkey) -> anyhow::Result<()> {
-    let rent_account = rpc_client.get_account(rent_payer)?;
+pub fn verify_rent_payer(
+    rpc_client: &RpcClient,
+    rent_payer: &Pubkey,
+) -> Result<(), MarinadeClientError> {
+    let rent_account = rpc_client
+        .get_account_with_commitment(rent_payer, rpc_client.commitment())?
+        .value
+        .ok_or(MarinadeClientError::AccountNotFound(*rent_payer))?;
     if rent_account.owner != system_program::ID {
-        bail!(
-            "verify_rent_payer: provided rent payer {} address must be a system account",
-            rent_payer
-        )
+        return Err(MarinadeClientError::InvalidRentPayer {
+            rent_payer: *rent_payer,
+            owner: rent_account.owner,
+        });
     }
     Ok(())
 }
 
-pub fn verify_pause_authority(state: &State, pause_authority: &Pubkey) -> anyhow::Result<()> {
-    if &state.pause_authority != pause_authority {
-        bail!("verify_pause_authority: pause-authority {} to sign the transaction mismatches Marinade state pause authority {}",
-                pause_authority,
-                state.pause_authority
-            );
+pub fn verify_pause_authority(
+    state: &State,
+    pause_authority: &Pubkey,
+) -> Result<(), MarinadeClientError> {
+    verify_authority("pause authority", &state.pause_authority, pause_authority)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_authority_mismatch() {
+        let expected = Pubkey::new_unique();
+        let provided = Pubkey::new_unique();
+        assert!(verify_authority("admin authority", &expected, &expected).is_ok());
+        match verify_authority("admin authority", &expected, &provided) {
+            Err(MarinadeClientError::AuthorityMismatch {
+                authority,
+                expected: err_expected,
+                provided: err_provided,
+            }) => {
+                assert_eq!(authority, "admin authority");
+                assert_eq!(err_expected, expected);
+                assert_eq!(err_provided, provided);
+            }
+            other => panic!("Expected authority mismatch, got {:?}", other),
+        }
     }
-    Ok(())
 }
@@ -0,0 +1,305 @@
+use crate::transactions::chained_simulation::{
+    get_multiple_accounts, lookup_table_accounts, AccountsOverlay, ChainedSimulator,
+};
+use crate::transactions::durable_nonce::{durable_nonce_account, get_durable_nonce_blockhash};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_address_lookup_table_program::state::AddressLookupTable;
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcSimulateTransactionConfig};
+use solana_client::rpc_request::RpcError::ForUser;
+use solana_client::rpc_response::{
+    Response, RpcResponseContext, RpcResult, RpcSimulateTransactionResult,
+};
+use solana_runtime::bank::Bank;
+use solana_runtime::genesis_utils::activate_all_features;
+use solana_sdk::account::{from_account, Account, AccountSharedData};
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::clock::Clock;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::genesis_config::{ClusterType, GenesisConfig};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::native_loader;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::stake_history::StakeHistory;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{
+    TransactionError, TransactionVerificationMode, VersionedTransaction,
+};
+use solana_transaction_status::UiTransactionReturnData;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Simulates the transaction in a local bank loaded with the cluster accounts the transaction uses,
+/// the overlay accounts replace the cluster ones.
+/// The clock, epoch schedule and stake history are taken from the cluster, all features are active.
+/// The bank is created by the `solana-runtime` test utilities, the simulator is available with the `bank-simulator` feature.
+pub struct BankSimulator<'a> {
+    rpc_client: &'a RpcClient,
+}
+
+impl<'a> BankSimulator<'a> {
+    pub fn new(rpc_client: &'a RpcClient) -> Self {
+        Self { rpc_client }
+    }
+
+    fn get_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+        account_config: &RpcAccountInfoConfig,
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        Ok(get_multiple_accounts(self.rpc_client, pubkeys, account_config)?.0)
+    }
+
+    fn get_sysvar<S: sysvar::Sysvar>(
+        account: Option<Account>,
+        name: &str,
+    ) -> Result<S, ClientError> {
+        account
+            .and_then(|account| from_account::<S, _>(&account))
+            .ok_or_else(|| ForUser(format!("Cannot load sysvar {} from the cluster", name)).into())
+    }
+
+    /// The overlay accounts replace the cluster ones, the accounts not existing on the cluster are left out.
+    fn load_accounts(
+        &self,
+        pubkeys: impl IntoIterator<Item = Pubkey>,
+        overlay: &AccountsOverlay,
+        account_config: &RpcAccountInfoConfig,
+    ) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+        let mut unique = HashSet::new();
+        let pubkeys: Vec<Pubkey> = pubkeys
+            .into_iter()
+            .filter(|pubkey| unique.insert(*pubkey))
+            .collect();
+        let cluster_pubkeys: Vec<Pubkey> = pubkeys
+            .iter()
+            .filter(|pubkey| !overlay.contains(pubkey))
+            .cloned()
+            .collect();
+        let mut cluster_accounts: HashMap<Pubkey, Account> = cluster_pubkeys
+            .iter()
+            .cloned()
+            .zip(self.get_accounts(&cluster_pubkeys, account_config)?)
+            .filter_map(|(pubkey, account)| account.map(|account| (pubkey, account)))
+            .collect();
+        Ok(pubkeys
+            .into_iter()
+            .filter_map(|pubkey| {
+                overlay
+                    .get(&pubkey)
+                    .cloned()
+                    .or_else(|| cluster_accounts.remove(&pubkey))
+                    .map(|account| (pubkey, account))
+            })
+            .collect())
+    }
+
+    fn load_bank(
+        &self,
+        message: &VersionedMessage,
+        overlay: &AccountsOverlay,
+        account_config: &RpcAccountInfoConfig,
+    ) -> Result<Bank, ClientError> {
+        let mut sysvars = self
+            .get_accounts(
+                &[
+                    sysvar::clock::id(),
+                    sysvar::epoch_schedule::id(),
+                    sysvar::stake_history::id(),
+                ],
+                account_config,
+            )?
+            .into_iter();
+        let clock: Clock = Self::get_sysvar(sysvars.next().flatten(), "clock")?;
+        let epoch_schedule: EpochSchedule =
+            Self::get_sysvar(sysvars.next().flatten(), "epoch schedule")?;
+        let stake_history: StakeHistory =
+            Self::get_sysvar(sysvars.next().flatten(), "stake history")?;
+
+        let mut genesis_config = GenesisConfig {
+            cluster_type: ClusterType::Development,
+            epoch_schedule,
+            ..GenesisConfig::default()
+        };
+        activate_all_features(&mut genesis_config);
+        let genesis_bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        // the bank is past the cluster slot, the address lookup tables extended recently are usable
+        let bank = Bank::new_from_parent(&genesis_bank, &Pubkey::default(), clock.slot + 1);
+        bank.set_sysvar_for_tests(&clock);
+        bank.set_sysvar_for_tests(&stake_history);
+
+        let table_accounts = self.load_accounts(
+            message
+                .address_table_lookups()
+                .unwrap_or_default()
+                .iter()
+                .map(|lookup| lookup.account_key),
+            overlay,
+            account_config,
+        )?;
+        let mut address_lookup_tables = Vec::with_capacity(table_accounts.len());
+        for (key, account) in table_accounts.iter() {
+            let table = AddressLookupTable::deserialize(&account.data).map_err(|e| {
+                ClientError::from(ForUser(format!(
+                    "Cannot deserialize address lookup table {}: {}",
+                    key, e
+                )))
+            })?;
+            address_lookup_tables.push(AddressLookupTableAccount {
+                key: *key,
+                addresses: table.addresses.to_vec(),
+            });
+        }
+        let mut accounts = self.load_accounts(
+            message
+                .static_account_keys()
+                .iter()
+                .cloned()
+                .chain(lookup_table_accounts(
+                    message,
+                    &address_lookup_tables,
+                    |lookup| {
+                        lookup
+                            .writable_indexes
+                            .iter()
+                            .chain(&lookup.readonly_indexes)
+                    },
+                )),
+            overlay,
+            account_config,
+        )?;
+        let programdata_addresses: Vec<Pubkey> = accounts
+            .iter()
+            .filter(|(_, account)| {
+                account.executable && account.owner == bpf_loader_upgradeable::id()
+            })
+            .filter_map(|(_, account)| match bincode::deserialize(&account.data) {
+                Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) => Some(programdata_address),
+                _ => None,
+            })
+            .collect();
+        accounts.extend(self.load_accounts(programdata_addresses, overlay, account_config)?);
+
+        for (pubkey, account) in table_accounts.into_iter().chain(accounts) {
+            // builtin programs and sysvars are provided by the bank
+            if account.owner == native_loader::id() || account.owner == sysvar::id() {
+                continue;
+            }
+            bank.store_account(&pubkey, &AccountSharedData::from(account));
+        }
+        bank.freeze();
+        Ok(bank)
+    }
+
+    /// The recent blockhash of the transaction is valid on the cluster,
+    /// the one of the durable nonce transaction has to be the nonce stored in the nonce account.
+    fn is_blockhash_valid(
+        &self,
+        transaction: &VersionedTransaction,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<bool, ClientError> {
+        let commitment = commitment.unwrap_or_else(|| self.rpc_client.commitment());
+        let blockhash = transaction.message.recent_blockhash();
+        match durable_nonce_account(transaction) {
+            Some(nonce_account) => Ok(get_durable_nonce_blockhash(
+                &nonce_account,
+                self.rpc_client,
+                commitment,
+            )? == *blockhash),
+            None => self.rpc_client.is_blockhash_valid(blockhash, commitment),
+        }
+    }
+}
+
+impl<'a> ChainedSimulator for BankSimulator<'a> {
+    fn simulate_chained(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+        overlay: &AccountsOverlay,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        let failed = |slot, err| Response {
+            context: RpcResponseContext::new(slot),
+            value: RpcSimulateTransactionResult {
+                err: Some(err),
+                logs: None,
+                accounts: None,
+                units_consumed: None,
+                return_data: None,
+            },
+        };
+        // signatures and the blockhash are verified before the blockhash is replaced by the one of the local bank
+        if config.sig_verify && !transaction.verify_with_results().iter().all(|ok| *ok) {
+            return Ok(failed(
+                self.rpc_client.get_slot()?,
+                TransactionError::SignatureFailure,
+            ));
+        }
+        if !config.replace_recent_blockhash
+            && !self.is_blockhash_valid(transaction, config.commitment)?
+        {
+            return Ok(failed(
+                self.rpc_client.get_slot()?,
+                TransactionError::BlockhashNotFound,
+            ));
+        }
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+            ..RpcAccountInfoConfig::default()
+        };
+        let bank = self.load_bank(&transaction.message, overlay, &account_config)?;
+        let mut transaction = transaction.clone();
+        match &mut transaction.message {
+            VersionedMessage::Legacy(message) => message.recent_blockhash = bank.last_blockhash(),
+            VersionedMessage::V0(message) => message.recent_blockhash = bank.last_blockhash(),
+        }
+        let sanitized_transaction =
+            match bank.verify_transaction(transaction, TransactionVerificationMode::HashOnly) {
+                Ok(sanitized_transaction) => sanitized_transaction,
+                Err(err) => return Ok(failed(bank.slot(), err)),
+            };
+        let simulation = bank.simulate_transaction(sanitized_transaction);
+
+        let accounts = config.accounts.map(|accounts_config| {
+            let encoding = accounts_config
+                .encoding
+                .unwrap_or(UiAccountEncoding::Base64);
+            accounts_config
+                .addresses
+                .iter()
+                .map(|address| {
+                    let pubkey = Pubkey::from_str(address).ok()?;
+                    simulation
+                        .post_simulation_accounts
+                        .iter()
+                        .find(|(key, _)| *key == pubkey)
+                        .map(|(_, account)| account.clone())
+                        .or_else(|| bank.get_account(&pubkey))
+                        .map(|account| UiAccount::encode(&pubkey, &account, encoding, None, None))
+                })
+                .collect()
+        });
+        Ok(Response {
+            context: RpcResponseContext::new(bank.slot()),
+            value: RpcSimulateTransactionResult {
+                err: simulation.result.err(),
+                logs: Some(simulation.logs),
+                accounts,
+                units_consumed: Some(simulation.units_consumed),
+                return_data: simulation.return_data.map(UiTransactionReturnData::from),
+            },
+        })
+    }
+
+    fn applies_overlay(&self) -> bool {
+        true
+    }
+}
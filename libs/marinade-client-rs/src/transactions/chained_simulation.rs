@@ -0,0 +1,495 @@
+use crate::error::MarinadeClientError;
+use crate::marinade::accounts_diff::AccountsDiff;
+use crate::transactions::execution_report::{ExecutionReport, TransactionReport};
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::transactions::transaction_builder::TransactionBuilder;
+use crate::transactions::transaction_executors::{
+    get_transaction_blockhash, log_simulation, simulation_config, warn_text_simulate_print,
+};
+use crate::transactions::transaction_instruction::print_base64;
+use log::{error, warn};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcSendTransactionConfig, RpcSimulateTransactionAccountsConfig,
+    RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_request::RpcError::ForUser;
+use solana_client::rpc_response::{Response, RpcResult, RpcSimulateTransactionResult};
+use solana_sdk::account::Account;
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::clock::Slot;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::message::v0::MessageAddressTableLookup;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use std::collections::HashMap;
+
+/// Max number of accounts the RPC `getMultipleAccounts` call accepts.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Accounts state changed by the transactions simulated so far.
+#[derive(Debug, Clone, Default)]
+pub struct AccountsOverlay {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl AccountsOverlay {
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.accounts.contains_key(pubkey)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn insert(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    /// Stores the accounts returned by the simulation, a missing account means it was closed.
+    pub fn update_from_simulation(
+        &mut self,
+        addresses: &[Pubkey],
+        simulated_accounts: &[Option<UiAccount>],
+    ) {
+        for (pubkey, simulated_account) in addresses.iter().zip(simulated_accounts) {
+            let account = simulated_account
+                .as_ref()
+                .and_then(|ui_account| ui_account.decode::<Account>())
+                .unwrap_or_default();
+            self.accounts.insert(*pubkey, account);
+        }
+    }
+
+    /// Accounts of the transaction that were changed by the previous transactions,
+    /// the accounts loaded from the address lookup tables included.
+    /// The fee payer is not reported, its balance is changed by every transaction.
+    pub fn touched_by(
+        &self,
+        transaction: &VersionedTransaction,
+        address_lookup_tables: &[AddressLookupTableAccount],
+    ) -> Vec<Pubkey> {
+        let message = &transaction.message;
+        message
+            .static_account_keys()
+            .iter()
+            .skip(1)
+            .cloned()
+            .chain(lookup_table_accounts(
+                message,
+                address_lookup_tables,
+                |lookup| {
+                    lookup
+                        .writable_indexes
+                        .iter()
+                        .chain(&lookup.readonly_indexes)
+                },
+            ))
+            .filter(|pubkey| self.contains(pubkey))
+            .collect()
+    }
+}
+
+/// Loads the accounts in chunks the RPC accepts, every chunk is loaded at the slot of the previous one or later.
+/// Returns the accounts with the slot of the last chunk.
+pub(crate) fn get_multiple_accounts(
+    rpc_client: &RpcClient,
+    pubkeys: &[Pubkey],
+    account_config: &RpcAccountInfoConfig,
+) -> Result<(Vec<Option<Account>>, Option<Slot>), ClientError> {
+    let mut accounts = Vec::with_capacity(pubkeys.len());
+    let mut context_slot = None;
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let response = rpc_client.get_multiple_accounts_with_config(
+            chunk,
+            RpcAccountInfoConfig {
+                min_context_slot: context_slot.or(account_config.min_context_slot),
+                ..account_config.clone()
+            },
+        )?;
+        context_slot = Some(response.context.slot);
+        accounts.extend(response.value);
+    }
+    Ok((accounts, context_slot))
+}
+
+/// Simulates transactions on top of the accounts changed by the previously simulated transactions.
+pub trait ChainedSimulator {
+    fn simulate_chained(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+        overlay: &AccountsOverlay,
+    ) -> RpcResult<RpcSimulateTransactionResult>;
+
+    /// When false, the simulation runs against the cluster state and the overlay is only used
+    /// to report the accounts whose changes the simulation did not see.
+    fn applies_overlay(&self) -> bool;
+}
+
+/// RPC node cannot simulate over a custom account state, every transaction is simulated
+/// against the cluster state. See `BankSimulator` of the `bank-simulator` feature for the simulator
+/// applying the overlay.
+impl ChainedSimulator for RpcClient {
+    fn simulate_chained(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+        _overlay: &AccountsOverlay,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(transaction, config)
+    }
+
+    fn applies_overlay(&self) -> bool {
+        false
+    }
+}
+
+impl<T: ChainedSimulator + ?Sized> ChainedSimulator for &T {
+    fn simulate_chained(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+        overlay: &AccountsOverlay,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        (*self).simulate_chained(transaction, config, overlay)
+    }
+
+    fn applies_overlay(&self) -> bool {
+        (*self).applies_overlay()
+    }
+}
+
+/// Accounts the message loads from the address lookup tables at the indexes picked by `indexes`,
+/// indexes of tables not provided are skipped.
+pub(crate) fn lookup_table_accounts<'m, I: Iterator<Item = &'m u8>>(
+    message: &'m VersionedMessage,
+    address_lookup_tables: &'m [AddressLookupTableAccount],
+    indexes: impl Fn(&'m MessageAddressTableLookup) -> I + 'm,
+) -> impl Iterator<Item = Pubkey> + 'm {
+    message
+        .address_table_lookups()
+        .unwrap_or_default()
+        .iter()
+        .flat_map(move |lookup| {
+            let table = address_lookup_tables
+                .iter()
+                .find(|table| table.key == lookup.account_key);
+            indexes(lookup).filter_map(move |index| {
+                table.and_then(|table| table.addresses.get(*index as usize).cloned())
+            })
+        })
+}
+
+/// Accounts the transaction may write to, the ones loaded from the address lookup tables included.
+fn writable_accounts(
+    transaction: &VersionedTransaction,
+    address_lookup_tables: &[AddressLookupTableAccount],
+) -> Vec<Pubkey> {
+    let message = &transaction.message;
+    message
+        .static_account_keys()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| message.is_maybe_writable(*index))
+        .map(|(_, pubkey)| *pubkey)
+        .chain(lookup_table_accounts(
+            message,
+            address_lookup_tables,
+            |lookup| lookup.writable_indexes.iter(),
+        ))
+        .collect()
+}
+
+/// Result of the chained simulation of a transaction.
+pub struct ChainedSimulation {
+    pub result: RpcResult<RpcSimulateTransactionResult>,
+    /// Accounts changed by the previous transactions the simulation did not see.
+    pub stale_accounts: Vec<Pubkey>,
+    /// Changes of the writable accounts, when requested.
+    pub accounts_diff: Option<AccountsDiff>,
+}
+
+impl ChainedSimulation {
+    fn failed(err: ClientError) -> Self {
+        Self {
+            result: Err(err),
+            stale_accounts: vec![],
+            accounts_diff: None,
+        }
+    }
+}
+
+/// Writable accounts state the simulation starts from, loaded from the cluster and replaced
+/// by the overlay when the simulator applies it. Returns the slot of the cluster state.
+fn accounts_before(
+    rpc_client: &RpcClient,
+    simulator: &dyn ChainedSimulator,
+    addresses: &[Pubkey],
+    simulate_config: &RpcSimulateTransactionConfig,
+    overlay: &AccountsOverlay,
+) -> Result<(Vec<Option<Account>>, Option<Slot>), ClientError> {
+    let (accounts, context_slot) = get_multiple_accounts(
+        rpc_client,
+        addresses,
+        &RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: simulate_config.commitment,
+            min_context_slot: simulate_config.min_context_slot,
+            ..RpcAccountInfoConfig::default()
+        },
+    )?;
+    if !simulator.applies_overlay() {
+        return Ok((accounts, context_slot));
+    }
+    let accounts = addresses
+        .iter()
+        .zip(accounts)
+        .map(|(pubkey, account)| match overlay.get(pubkey) {
+            // the account closed by a previous transaction is stored drained of lamports
+            Some(overlay_account) => Some(overlay_account.clone()).filter(|a| a.lamports > 0),
+            None => account,
+        })
+        .collect();
+    Ok((accounts, context_slot))
+}
+
+/// Simulates the prepared transaction and stores the accounts it writes to the overlay.
+/// With `accounts_diff` the writable accounts are loaded before the simulation and the simulation
+/// is required to run at the same slot or later, the changes made by the simulation are reported.
+pub fn simulate_prepared_transaction_chained(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    simulator: &dyn ChainedSimulator,
+    simulate_config: RpcSimulateTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    overlay: &mut AccountsOverlay,
+    accounts_diff: bool,
+) -> ChainedSimulation {
+    let rpc_client_blockhash = RpcClient::new_with_commitment(
+        rpc_client.url(),
+        CommitmentConfig {
+            commitment: blockhash_commitment,
+        },
+    );
+    let latest_blockhash =
+        match get_transaction_blockhash(prepared_transaction, &rpc_client_blockhash) {
+            Ok(latest_blockhash) => latest_blockhash,
+            Err(err) => return ChainedSimulation::failed(err),
+        };
+    let address_lookup_tables = prepared_transaction
+        .source
+        .as_ref()
+        .map(|source| source.address_lookup_tables.clone())
+        .unwrap_or_default();
+    let transaction = if simulate_config.sig_verify {
+        match prepared_transaction.sign(latest_blockhash) {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                error!(
+                    "simulate_prepared_transaction_chained: error signing transaction with blockhash: {}: {:?}",
+                    latest_blockhash, e
+                );
+                return ChainedSimulation::failed(
+                    ForUser(format!("Signing transaction error: {}", e)).into(),
+                );
+            }
+        }
+    } else {
+        prepared_transaction.partial_sign(latest_blockhash)
+    };
+
+    let stale_accounts = if simulator.applies_overlay() {
+        vec![]
+    } else {
+        overlay.touched_by(transaction, &address_lookup_tables)
+    };
+    let writable_accounts = writable_accounts(transaction, &address_lookup_tables);
+    let mut simulate_config = RpcSimulateTransactionConfig {
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: writable_accounts.iter().map(|a| a.to_string()).collect(),
+        }),
+        ..simulate_config
+    };
+    let accounts_before = if accounts_diff {
+        match accounts_before(
+            rpc_client,
+            simulator,
+            &writable_accounts,
+            &simulate_config,
+            overlay,
+        ) {
+            Ok((accounts_before, context_slot)) => {
+                simulate_config.min_context_slot =
+                    context_slot.or(simulate_config.min_context_slot);
+                Some(accounts_before)
+            }
+            Err(err) => return ChainedSimulation::failed(err),
+        }
+    } else {
+        None
+    };
+    let simulation_result = simulator.simulate_chained(transaction, simulate_config, overlay);
+    let accounts_diff = accounts_before.map(|accounts_before| {
+        let accounts_after = match &simulation_result {
+            Ok(Response {
+                value:
+                    RpcSimulateTransactionResult {
+                        err: None,
+                        accounts: Some(accounts),
+                        ..
+                    },
+                ..
+            }) => accounts
+                .iter()
+                .map(|ui_account| {
+                    ui_account
+                        .as_ref()
+                        .and_then(|ui_account| ui_account.decode::<Account>())
+                        // the account drained of lamports is closed
+                        .filter(|account| account.lamports > 0)
+                })
+                .collect(),
+            // no accounts are changed when the simulation fails
+            _ => accounts_before.clone(),
+        };
+        AccountsDiff::new(&writable_accounts, &accounts_before, &accounts_after)
+    });
+    if let Ok(response) = &simulation_result {
+        if let Some(accounts) = &response.value.accounts {
+            overlay.update_from_simulation(&writable_accounts, accounts);
+        }
+    }
+    ChainedSimulation {
+        result: simulation_result,
+        stale_accounts,
+        accounts_diff,
+    }
+}
+
+/// Simulates all combined transactions of the builder in order, every one of them is simulated
+/// over the account changes of the previous ones when the simulator is able to apply them.
+/// Otherwise the accounts changed by previous transactions are reported as stale in the simulation report.
+pub fn simulate_transaction_builder_chained(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    simulator: &dyn ChainedSimulator,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    print: bool,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    warn_text_simulate_print(true, print);
+
+    if print {
+        print_base64(&transaction_builder.instructions())?;
+    }
+
+    let is_checked_signers = transaction_builder.is_check_signers();
+    let mut overlay = AccountsOverlay::default();
+    let mut report = ExecutionReport::default();
+    for mut prepared_transaction in transaction_builder.sequence_combined() {
+        let simulation = simulate_prepared_transaction_chained(
+            &mut prepared_transaction,
+            rpc_client,
+            simulator,
+            simulation_config(&preflight_config, !print && is_checked_signers),
+            blockhash_commitment,
+            &mut overlay,
+            false,
+        );
+        let _ = log_simulation(&simulation.result);
+        report.push(
+            chained_simulation_report(prepared_transaction.instruction_packs, &simulation)
+                .with_message(&prepared_transaction.transaction.message),
+        );
+    }
+
+    Ok(report)
+}
+
+/// Simulation report with the accounts whose changes by previous transactions the simulation did not see.
+pub(crate) fn chained_simulation_report(
+    instruction_packs: Vec<usize>,
+    simulation: &ChainedSimulation,
+) -> TransactionReport {
+    if !simulation.stale_accounts.is_empty() {
+        warn!(
+            "Simulation of instruction packs {:?} does not see changes of accounts {:?} made by previous transactions",
+            instruction_packs, simulation.stale_accounts
+        );
+    }
+    let mut transaction_report =
+        TransactionReport::from_simulation(instruction_packs, &simulation.result);
+    if let Some(simulation_report) = transaction_report.simulation.as_mut() {
+        simulation_report.stale_accounts = simulation
+            .stale_accounts
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+    }
+    transaction_report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::{v0, Message};
+
+    #[test]
+    fn test_overlay_reports_accounts_changed_by_previous_transactions() {
+        let fee_payer = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        let stake_account = Pubkey::new_unique();
+        let instruction = |accounts: Vec<AccountMeta>| {
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts)
+        };
+        let transaction = |message: VersionedMessage| VersionedTransaction {
+            signatures: vec![Default::default()],
+            message,
+        };
+
+        let first = transaction(VersionedMessage::Legacy(Message::new_with_blockhash(
+            &[instruction(vec![AccountMeta::new(state, false)])],
+            Some(&fee_payer),
+            &Hash::default(),
+        )));
+        let writable = writable_accounts(&first, &[]);
+        assert_eq!(writable, vec![fee_payer, state]);
+
+        let mut overlay = AccountsOverlay::default();
+        overlay.update_from_simulation(&writable, &[None, None]);
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![state, stake_account],
+        };
+        let second = transaction(VersionedMessage::V0(
+            v0::Message::try_compile(
+                &fee_payer,
+                &[instruction(vec![
+                    AccountMeta::new_readonly(state, false),
+                    AccountMeta::new(stake_account, false),
+                ])],
+                &[lookup_table.clone()],
+                Hash::default(),
+            )
+            .unwrap(),
+        ));
+        let lookup_tables = [lookup_table];
+        assert_eq!(
+            writable_accounts(&second, &lookup_tables),
+            vec![fee_payer, stake_account]
+        );
+        assert_eq!(overlay.touched_by(&second, &lookup_tables), vec![state]);
+        assert!(!overlay.contains(&stake_account));
+    }
+}
@@ -0,0 +1,173 @@
+use anchor_client::RequestBuilder;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signer::Signer;
+use std::ops::Deref;
+
+/// Maximal compute unit limit that can be requested by a transaction.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute budget settings added to every transaction built or sent by the crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    /// Price of a compute unit in micro-lamports, fixed or derived from the recent prioritization fees.
+    pub compute_unit_price: Option<ComputeUnitPrice>,
+    pub compute_unit_limit: Option<u32>,
+    /// When set, the compute unit limit is estimated by simulation before the transaction is sent.
+    pub compute_unit_limit_estimation: Option<ComputeUnitLimitEstimation>,
+}
+
+/// Strategy to define the price of a compute unit in micro-lamports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeUnitPrice {
+    /// Fixed price, zero means no priority fee is set.
+    Fixed(u64),
+    /// Percentile of the recent prioritization fees paid for the writable accounts of the transaction.
+    Percentile { percentile: u8 },
+    /// Percentile of the recent prioritization fees clamped to the min/max price.
+    CappedPercentile { percentile: u8, min: u64, max: u64 },
+}
+
+impl ComputeUnitPrice {
+    pub fn is_fixed(&self) -> bool {
+        matches!(self, ComputeUnitPrice::Fixed(_))
+    }
+
+    /// Price used when the transaction is built, the fee based strategies replace it before sending.
+    fn initial_price(&self) -> u64 {
+        match self {
+            ComputeUnitPrice::Fixed(price) => *price,
+            ComputeUnitPrice::Percentile { .. } => 0,
+            ComputeUnitPrice::CappedPercentile { min, .. } => *min,
+        }
+    }
+
+    /// Price derived from the prioritization fees of the recent slots.
+    pub fn from_recent_fees(&self, recent_fees: &[u64]) -> u64 {
+        match self {
+            ComputeUnitPrice::Fixed(price) => *price,
+            ComputeUnitPrice::Percentile { percentile } => fee_percentile(recent_fees, *percentile),
+            ComputeUnitPrice::CappedPercentile {
+                percentile,
+                min,
+                max,
+            } => fee_percentile(recent_fees, *percentile).clamp(*min, (*max).max(*min)),
+        }
+    }
+}
+
+/// Nearest-rank percentile of the fees, zero when no fee is known.
+pub fn fee_percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    let percentile = percentile.min(100) as usize;
+    let rank = (percentile * fees.len() + 99) / 100;
+    fees[rank.saturating_sub(1)]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeUnitLimitEstimation {
+    /// Safety margin in percents added on top of the simulated consumed units.
+    pub margin_percent: u32,
+}
+
+impl ComputeUnitLimitEstimation {
+    pub fn compute_unit_limit(&self, units_consumed: u64) -> u32 {
+        let compute_unit_limit =
+            units_consumed.saturating_mul(100 + self.margin_percent as u64) / 100;
+        compute_unit_limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+    }
+}
+
+impl ComputeBudgetConfig {
+    pub fn new(compute_unit_price: Option<u64>, compute_unit_limit: Option<u32>) -> Self {
+        Self {
+            compute_unit_price: compute_unit_price.map(ComputeUnitPrice::Fixed),
+            compute_unit_limit,
+            compute_unit_limit_estimation: None,
+        }
+    }
+
+    pub fn with_compute_unit_price(mut self, compute_unit_price: Option<ComputeUnitPrice>) -> Self {
+        self.compute_unit_price = compute_unit_price;
+        self
+    }
+
+    pub fn with_compute_unit_limit_estimation(mut self, margin_percent: Option<u32>) -> Self {
+        self.compute_unit_limit_estimation =
+            margin_percent.map(|margin_percent| ComputeUnitLimitEstimation { margin_percent });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions().is_empty()
+    }
+
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        // the transaction is simulated with the maximal limit, the estimated limit replaces it before sending
+        let compute_unit_limit = if self.compute_unit_limit_estimation.is_some() {
+            Some(MAX_COMPUTE_UNIT_LIMIT)
+        } else {
+            self.compute_unit_limit
+        };
+        if let Some(compute_unit_limit) = compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                compute_unit_limit,
+            ));
+        }
+        // for the fee based strategies the instruction is a placeholder to account its size
+        match self.compute_unit_price {
+            Some(ComputeUnitPrice::Fixed(0)) | None => {}
+            Some(compute_unit_price) => {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                    compute_unit_price.initial_price(),
+                ));
+            }
+        }
+        instructions
+    }
+
+    /// Anchor places the instructions added through `instruction` before the one built from accounts and args.
+    pub fn apply_to_anchor_builder<'a, C: Deref<Target = impl Signer> + Clone>(
+        &self,
+        anchor_builder: RequestBuilder<'a, C>,
+    ) -> RequestBuilder<'a, C> {
+        self.instructions()
+            .into_iter()
+            .fold(anchor_builder, |builder, instruction| {
+                builder.instruction(instruction)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_unit_price_from_recent_fees() {
+        let recent_fees = [0, 10, 50, 20, 5000, 30, 40, 0, 100, 60];
+        assert_eq!(fee_percentile(&[], 75), 0);
+        assert_eq!(fee_percentile(&recent_fees, 50), 30);
+        assert_eq!(fee_percentile(&recent_fees, 90), 100);
+        assert_eq!(fee_percentile(&recent_fees, 100), 5000);
+        assert_eq!(fee_percentile(&recent_fees, 0), 0);
+
+        assert_eq!(ComputeUnitPrice::Fixed(7).from_recent_fees(&recent_fees), 7);
+        assert_eq!(
+            ComputeUnitPrice::Percentile { percentile: 100 }.from_recent_fees(&recent_fees),
+            5000
+        );
+        let capped = ComputeUnitPrice::CappedPercentile {
+            percentile: 100,
+            min: 1_000,
+            max: 2_000,
+        };
+        assert_eq!(capped.from_recent_fees(&recent_fees), 2_000);
+        assert_eq!(capped.from_recent_fees(&[]), 1_000);
+    }
+}
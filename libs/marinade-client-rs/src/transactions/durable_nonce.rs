@@ -0,0 +1,99 @@
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction::{self, SystemInstruction};
+use solana_sdk::system_program;
+use solana_sdk::transaction::VersionedTransaction;
+
+/// Nonce account used instead of a recent blockhash. The transaction does not expire
+/// until the nonce is advanced, which permits to collect signatures for a long time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub nonce_account: Pubkey,
+    pub nonce_authority: Pubkey,
+}
+
+impl DurableNonce {
+    pub fn new(nonce_account: Pubkey, nonce_authority: Pubkey) -> Self {
+        Self {
+            nonce_account,
+            nonce_authority,
+        }
+    }
+
+    /// Instruction that has to be the first one in the transaction using the nonce.
+    pub fn advance_nonce_instruction(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.nonce_account, &self.nonce_authority)
+    }
+
+    /// Loads the nonce stored in the nonce account, it's used as the transaction blockhash.
+    pub fn get_blockhash(&self, rpc_client: &RpcClient) -> Result<Hash, ClientError> {
+        get_durable_nonce_blockhash(&self.nonce_account, rpc_client, rpc_client.commitment())
+    }
+}
+
+pub fn get_durable_nonce_blockhash(
+    nonce_account: &Pubkey,
+    rpc_client: &RpcClient,
+    commitment: CommitmentConfig,
+) -> Result<Hash, ClientError> {
+    nonce_utils::get_account_with_commitment(rpc_client, nonce_account, commitment)
+        .and_then(|account| nonce_utils::data_from_account(&account))
+        .map(|data| data.blockhash())
+        .map_err(|e| {
+            ClientError::from(ClientErrorKind::Custom(format!(
+                "Cannot load durable nonce from account {}: {}",
+                nonce_account, e
+            )))
+        })
+}
+
+/// Nonce account of the durable nonce transaction, i.e., the account advanced by its first instruction.
+pub fn durable_nonce_account(transaction: &VersionedTransaction) -> Option<Pubkey> {
+    let account_keys = transaction.message.static_account_keys();
+    let instruction = transaction.message.instructions().first()?;
+    if account_keys.get(instruction.program_id_index as usize)? != &system_program::id() {
+        return None;
+    }
+    match bincode::deserialize(&instruction.data) {
+        Ok(SystemInstruction::AdvanceNonceAccount) => account_keys
+            .get(*instruction.accounts.first()? as usize)
+            .cloned(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    #[test]
+    fn test_durable_nonce_account() {
+        let fee_payer = Pubkey::new_unique();
+        let durable_nonce = DurableNonce::new(Pubkey::new_unique(), fee_payer);
+        let transfer = system_instruction::transfer(&fee_payer, &Pubkey::new_unique(), 1);
+        let transaction = |instructions: &[Instruction]| VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::Legacy(Message::new_with_blockhash(
+                instructions,
+                Some(&fee_payer),
+                &Hash::default(),
+            )),
+        };
+
+        assert_eq!(
+            durable_nonce_account(&transaction(&[
+                durable_nonce.advance_nonce_instruction(),
+                transfer.clone()
+            ])),
+            Some(durable_nonce.nonce_account)
+        );
+        assert_eq!(durable_nonce_account(&transaction(&[transfer])), None);
+    }
+}
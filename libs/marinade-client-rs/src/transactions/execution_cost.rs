@@ -0,0 +1,329 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::compute_budget::{ComputeBudgetConfig, MAX_COMPUTE_UNIT_LIMIT};
+use crate::transactions::prepared_transaction::writable_accounts;
+use crate::transactions::sender::TipConfig;
+use crate::transactions::transaction_diagnostic::marinade_instruction_name;
+use crate::transactions::transaction_executors::get_compute_unit_price;
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::stake::state::StakeState;
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+
+/// Fee of one signature of the transaction.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Compute unit limit of an instruction when the transaction does not set the limit.
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+// Marinade validator duplication flag account holds the discriminator only
+const DUPLICATION_FLAG_ACCOUNT_SIZE: usize = 8;
+
+/// Lamports the execution is expected to cost, the priority fees are an upper bound
+/// as the compute unit limit is used instead of the consumed units.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionCostEstimate {
+    pub transactions: usize,
+    pub fee_payer: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub signature_fees: u64,
+    pub priority_fees: u64,
+    pub tips: u64,
+    /// Rent of the accounts created by the transactions (e.g., split stake accounts, ticket accounts).
+    pub rent: u64,
+    /// Part of the rent paid by the fee payer, the rest is paid by the other rent payers.
+    pub fee_payer_rent: u64,
+    pub fee_payer_balance: u64,
+}
+
+impl ExecutionCostEstimate {
+    pub fn total(&self) -> u64 {
+        self.signature_fees
+            .saturating_add(self.priority_fees)
+            .saturating_add(self.tips)
+            .saturating_add(self.rent)
+    }
+
+    /// Lamports debited from the fee payer.
+    pub fn fee_payer_cost(&self) -> u64 {
+        self.signature_fees
+            .saturating_add(self.priority_fees)
+            .saturating_add(self.tips)
+            .saturating_add(self.fee_payer_rent)
+    }
+
+    pub fn check_fee_payer_balance(&self) -> Result<(), MarinadeClientError> {
+        if self.fee_payer_balance < self.fee_payer_cost() {
+            return Err(MarinadeClientError::InsufficientBalance {
+                account: self.fee_payer,
+                balance: self.fee_payer_balance,
+                required: self.fee_payer_cost(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for ExecutionCostEstimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Transactions: {}", self.transactions)?;
+        writeln!(f, "Signers required:")?;
+        for signer in &self.signers {
+            writeln!(f, "  {}", signer)?;
+        }
+        writeln!(f, "Estimated cost:")?;
+        for (name, lamports) in [
+            ("signature fees", self.signature_fees),
+            ("priority fees", self.priority_fees),
+            ("tips", self.tips),
+            ("rent of created accounts", self.rent),
+            ("total", self.total()),
+        ] {
+            writeln!(
+                f,
+                "  {:<26}{} lamports (◎{})",
+                name,
+                lamports,
+                lamports_to_sol(lamports)
+            )?;
+        }
+        write!(
+            f,
+            "Fee payer {} balance: {} lamports (◎{}), required: {} lamports (◎{})",
+            self.fee_payer,
+            self.fee_payer_balance,
+            lamports_to_sol(self.fee_payer_balance),
+            self.fee_payer_cost(),
+            lamports_to_sol(self.fee_payer_cost())
+        )
+    }
+}
+
+/// Estimates the cost of the transactions built from the instructions, the compute unit price
+/// derived from the recent prioritization fees is loaded from the RPC node.
+pub fn estimate_execution_cost(
+    transactions: &[Vec<Instruction>],
+    fee_payer: &Pubkey,
+    compute_budget: &ComputeBudgetConfig,
+    tip: Option<&TipConfig>,
+    rpc_client: &RpcClient,
+) -> Result<ExecutionCostEstimate, MarinadeClientError> {
+    let mut rent_exemptions: HashMap<usize, u64> = HashMap::new();
+    let mut estimate = ExecutionCostEstimate {
+        fee_payer: *fee_payer,
+        fee_payer_balance: rpc_client.get_balance(fee_payer)?,
+        ..ExecutionCostEstimate::default()
+    };
+    for instructions in transactions {
+        let compute_unit_price = match &compute_budget.compute_unit_price {
+            Some(compute_unit_price) => get_compute_unit_price(
+                compute_unit_price,
+                &writable_accounts(instructions),
+                rpc_client,
+            )?,
+            None => 0,
+        };
+        add_transaction_cost(
+            &mut estimate,
+            instructions,
+            compute_unit_price,
+            compute_budget.compute_unit_limit,
+            tip,
+            &mut |size| match rent_exemptions.get(&size) {
+                Some(lamports) => Ok(*lamports),
+                None => {
+                    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(size)?;
+                    rent_exemptions.insert(size, lamports);
+                    Ok(lamports)
+                }
+            },
+        )?;
+    }
+    Ok(estimate)
+}
+
+fn add_transaction_cost(
+    estimate: &mut ExecutionCostEstimate,
+    instructions: &[Instruction],
+    compute_unit_price: u64,
+    compute_unit_limit: Option<u32>,
+    tip: Option<&TipConfig>,
+    rent_exemption: &mut dyn FnMut(usize) -> Result<u64, SolanaClientError>,
+) -> Result<(), SolanaClientError> {
+    estimate.transactions += 1;
+
+    let mut signers = vec![estimate.fee_payer];
+    for account in instructions
+        .iter()
+        .flat_map(|instruction| instruction.accounts.iter())
+        .filter(|account| account.is_signer)
+    {
+        if !signers.contains(&account.pubkey) {
+            signers.push(account.pubkey);
+        }
+    }
+    estimate.signature_fees += signers.len() as u64 * LAMPORTS_PER_SIGNATURE;
+    for signer in signers {
+        if !estimate.signers.contains(&signer) {
+            estimate.signers.push(signer);
+        }
+    }
+
+    let compute_unit_limit = compute_unit_limit.unwrap_or_else(|| {
+        (instructions.len() as u32)
+            .saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+            .min(MAX_COMPUTE_UNIT_LIMIT)
+    });
+    let priority_fee =
+        (compute_unit_price as u128 * compute_unit_limit as u128 + MICRO_LAMPORTS_PER_LAMPORT - 1)
+            / MICRO_LAMPORTS_PER_LAMPORT;
+    estimate.priority_fees += priority_fee as u64;
+    estimate.tips += tip.map(|tip| tip.lamports).unwrap_or(0);
+
+    for instruction in instructions {
+        if let Some((rent_payer, rent)) = created_account_rent(instruction, rent_exemption)? {
+            estimate.rent += rent;
+            if rent_payer == estimate.fee_payer {
+                estimate.fee_payer_rent += rent;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rent payer and the rent of the account created by the instruction. The system program creates
+/// the account directly, the Marinade instructions create the stake accounts and validator flags by CPI
+/// with the rent paid by the first writable signer of the instruction.
+fn created_account_rent(
+    instruction: &Instruction,
+    rent_exemption: &mut dyn FnMut(usize) -> Result<u64, SolanaClientError>,
+) -> Result<Option<(Pubkey, u64)>, SolanaClientError> {
+    if instruction.program_id == system_program::ID {
+        let lamports = match bincode::deserialize::<SystemInstruction>(&instruction.data) {
+            Ok(SystemInstruction::CreateAccount { lamports, .. })
+            | Ok(SystemInstruction::CreateAccountWithSeed { lamports, .. }) => lamports,
+            _ => return Ok(None),
+        };
+        return Ok(instruction
+            .accounts
+            .first()
+            .map(|from| (from.pubkey, lamports)));
+    }
+
+    let created_account_size = match marinade_instruction_name(&instruction.data) {
+        Some("DeactivateStake" | "PartialUnstake" | "WithdrawStakeAccount" | "StakeReserve") => {
+            StakeState::size_of()
+        }
+        Some("AddValidator") => DUPLICATION_FLAG_ACCOUNT_SIZE,
+        _ => return Ok(None),
+    };
+    let rent_payer = match instruction
+        .accounts
+        .iter()
+        .find(|account| account.is_signer && account.is_writable)
+    {
+        Some(account) => account.pubkey,
+        None => return Ok(None),
+    };
+    Ok(Some((rent_payer, rent_exemption(created_account_size)?)))
+}
+
+/// Prints the estimate and asks for the confirmation of the execution. The fee payer balance
+/// is checked first. With `assume_yes` no question is asked, in the non-interactive mode
+/// (stdin is not a terminal) the execution has to be confirmed by `assume_yes`.
+pub fn confirm_execution(
+    estimate: &ExecutionCostEstimate,
+    assume_yes: bool,
+) -> Result<(), MarinadeClientError> {
+    eprintln!("{}", estimate);
+    estimate.check_fee_payer_balance()?;
+    if assume_yes {
+        return Ok(());
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        return Err(MarinadeClientError::ConfirmationRequired);
+    }
+    eprint!("Proceed with the execution? [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    if is_confirmed(&answer) {
+        Ok(())
+    } else {
+        Err(MarinadeClientError::ExecutionDeclined)
+    }
+}
+
+fn is_confirmed(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+    use solana_sdk::rent::Rent;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn test_transaction_cost() {
+        let fee_payer = Pubkey::new_unique();
+        let new_account = Pubkey::new_unique();
+        let other_signer = Pubkey::new_unique();
+        let instructions = vec![
+            system_instruction::create_account(
+                &fee_payer,
+                &new_account,
+                1_000_000,
+                100,
+                &Pubkey::new_unique(),
+            ),
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![AccountMeta::new_readonly(other_signer, true)],
+            ),
+        ];
+        let mut estimate = ExecutionCostEstimate {
+            fee_payer,
+            fee_payer_balance: 1_000_000,
+            ..ExecutionCostEstimate::default()
+        };
+        add_transaction_cost(
+            &mut estimate,
+            &instructions,
+            1_000,
+            None,
+            Some(&TipConfig::new(Pubkey::new_unique(), 10_000)),
+            &mut |size| Ok(Rent::default().minimum_balance(size)),
+        )
+        .unwrap();
+        assert_eq!(estimate.transactions, 1);
+        // fee payer, created account and the other signer
+        assert_eq!(estimate.signers.len(), 3);
+        assert_eq!(estimate.signature_fees, 3 * LAMPORTS_PER_SIGNATURE);
+        // 2 instructions of the default limit 200k units at 1000 micro-lamports
+        assert_eq!(estimate.priority_fees, 400);
+        assert_eq!(estimate.tips, 10_000);
+        assert_eq!(estimate.rent, 1_000_000);
+        assert_eq!(estimate.fee_payer_rent, 1_000_000);
+        assert_eq!(estimate.total(), 15_000 + 400 + 10_000 + 1_000_000);
+        assert!(matches!(
+            estimate.check_fee_payer_balance(),
+            Err(MarinadeClientError::InsufficientBalance { .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_confirmed() {
+        assert!(is_confirmed("y\n"));
+        assert!(is_confirmed(" YES "));
+        assert!(!is_confirmed("\n"));
+        assert!(!is_confirmed("no"));
+    }
+}
@@ -0,0 +1,319 @@
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::transactions::rebroadcast::CONFIRMATION_POLL_INTERVAL;
+use log::info;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::{hashv, Hash};
+use solana_sdk::signature::Signature;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExecutionJournalError {
+    #[error("Journal {} I/O error: {}", .path.display(), .error)]
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("Journal {} line {} cannot be parsed: {}", .path.display(), .line, .error)]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStatus {
+    /// Transaction was signed and is about to be sent, it may land even when the process dies.
+    Sent,
+    Confirmed,
+    Failed,
+}
+
+/// One line of the journal, the transaction is identified by its instruction pack indexes
+/// and the hash of its instructions, the same instructions may be executed by more transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub instruction_packs: Vec<usize>,
+    pub instructions_hash: String,
+    pub signature: Option<String>,
+    /// Blockhash the [JournalStatus::Sent] transaction was signed with, the nonce for the durable nonce transaction.
+    #[serde(default)]
+    pub blockhash: Option<String>,
+    /// The [JournalStatus::Sent] transaction may land until the block height passes this one,
+    /// `None` for the durable nonce transaction.
+    #[serde(default)]
+    pub last_valid_block_height: Option<u64>,
+    pub status: JournalStatus,
+    pub error: Option<String>,
+}
+
+/// Hash of the transaction instructions. The compute budget, durable nonce and tip instructions
+/// are not part of it as they may change when the transaction is sent again.
+pub fn instructions_hash(prepared_transaction: &PreparedTransaction) -> Hash {
+    let data = match &prepared_transaction.source {
+        Some(source) => bincode::serialize(&source.instructions),
+        None => bincode::serialize(&(
+            prepared_transaction
+                .transaction
+                .message
+                .static_account_keys(),
+            prepared_transaction.transaction.message.instructions(),
+        )),
+    }
+    .expect("instructions are serializable");
+    hashv(&[&data])
+}
+
+/// Append-only JSONL journal of the executed transactions, it permits to resume the operation
+/// with no double execution of the transactions that already landed.
+#[derive(Debug)]
+pub struct ExecutionJournal {
+    path: PathBuf,
+    file: File,
+    entries: Vec<JournalEntry>,
+}
+
+impl ExecutionJournal {
+    /// Opens the journal loading the entries recorded so far, the file is created when it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ExecutionJournalError> {
+        let path = path.as_ref().to_path_buf();
+        let io_error = |error| ExecutionJournalError::Io {
+            path: path.clone(),
+            error,
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(io_error)?;
+        let mut entries = Vec::new();
+        for (index, line) in BufReader::new(&file).lines().enumerate() {
+            let line = line.map_err(io_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| {
+                ExecutionJournalError::Parse {
+                    path: path.clone(),
+                    line: index + 1,
+                    error: e.to_string(),
+                }
+            })?);
+        }
+        Ok(Self {
+            path,
+            file,
+            entries,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Appends the entry and syncs it to the disk before returning.
+    pub fn record(&mut self, entry: JournalEntry) -> Result<(), ExecutionJournalError> {
+        let io_error = |error| ExecutionJournalError::Io {
+            path: self.path.clone(),
+            error,
+        };
+        let line = serde_json::to_string(&entry).expect("journal entry is serializable");
+        writeln!(self.file, "{}", line).map_err(io_error)?;
+        self.file.sync_data().map_err(io_error)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn transaction_entries<'a>(
+        &'a self,
+        instruction_packs: &'a [usize],
+        instructions_hash: &'a str,
+    ) -> impl Iterator<Item = &'a JournalEntry> {
+        self.entries.iter().filter(move |entry| {
+            entry.instruction_packs == instruction_packs
+                && entry.instructions_hash == instructions_hash
+        })
+    }
+
+    /// Signatures recorded for the transaction of the instruction packs with the instructions hash.
+    pub fn signatures(
+        &self,
+        instruction_packs: &[usize],
+        instructions_hash: &str,
+    ) -> Vec<Signature> {
+        let mut signatures: Vec<Signature> = Vec::new();
+        for signature in self
+            .transaction_entries(instruction_packs, instructions_hash)
+            .filter_map(|entry| entry.signature.as_deref())
+            .filter_map(|signature| Signature::from_str(signature).ok())
+        {
+            if !signatures.contains(&signature) {
+                signatures.push(signature);
+            }
+        }
+        signatures
+    }
+
+    /// Last valid block height of the sent transaction with the signature, the highest one recorded.
+    fn last_valid_block_height(&self, signature: &Signature) -> Option<u64> {
+        let signature = signature.to_string();
+        self.entries
+            .iter()
+            .filter(|entry| entry.signature.as_ref() == Some(&signature))
+            .filter_map(|entry| entry.last_valid_block_height)
+            .max()
+    }
+
+    /// Checks the recorded signatures of the transaction at the RPC node and returns the one
+    /// that landed with no error and reached the commitment of the `rpc_client`.
+    pub fn find_landed(
+        &self,
+        instruction_packs: &[usize],
+        instructions_hash: &str,
+        rpc_client: &RpcClient,
+    ) -> Result<Option<Signature>, SolanaClientError> {
+        let signatures = self.signatures(instruction_packs, instructions_hash);
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+        let statuses = rpc_client
+            .get_signature_statuses_with_history(&signatures)?
+            .value;
+        Ok(signatures
+            .into_iter()
+            .zip(statuses)
+            .find(|(_, status)| {
+                status
+                    .as_ref()
+                    .map(|status| {
+                        status.err.is_none() && status.satisfies_commitment(rpc_client.commitment())
+                    })
+                    .unwrap_or(false)
+            })
+            .map(|(signature, _)| signature))
+    }
+
+    /// Awaits the recorded transactions that may still land and returns the signature of the one that landed,
+    /// see [Self::find_landed]. A transaction may land when it was processed with no error and awaits the commitment,
+    /// or when it has no status and the block height has not passed its last valid block height yet.
+    /// The durable nonce transaction is not awaited, the transaction sent again uses the same nonce
+    /// and only one of them can land.
+    pub fn await_landed(
+        &self,
+        instruction_packs: &[usize],
+        instructions_hash: &str,
+        rpc_client: &RpcClient,
+    ) -> Result<Option<Signature>, SolanaClientError> {
+        let signatures = self.signatures(instruction_packs, instructions_hash);
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+        loop {
+            if let Some(signature) =
+                self.find_landed(instruction_packs, instructions_hash, rpc_client)?
+            {
+                return Ok(Some(signature));
+            }
+            let statuses = rpc_client
+                .get_signature_statuses_with_history(&signatures)?
+                .value;
+            let block_height = rpc_client.get_block_height()?;
+            let pending =
+                signatures
+                    .iter()
+                    .zip(statuses)
+                    .find(|(signature, status)| match status {
+                        Some(status) => status.err.is_none(),
+                        None => self
+                            .last_valid_block_height(signature)
+                            .map(|last_valid_block_height| block_height <= last_valid_block_height)
+                            .unwrap_or(false),
+                    });
+            match pending {
+                Some((signature, _)) => {
+                    info!(
+                        "Awaiting transaction {} recorded in journal {}, block height {}",
+                        signature,
+                        self.path.display(),
+                        block_height
+                    );
+                    std::thread::sleep(CONFIRMATION_POLL_INTERVAL);
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::transaction_builder::TransactionBuilder;
+    use solana_sdk::instruction::Instruction;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Keypair;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_journal_reopen_and_instructions_hash() {
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()));
+        tx_builder.add_instruction(instruction.clone()).unwrap();
+        let prepared_transaction = tx_builder.build_one();
+        let mut tx_builder =
+            TransactionBuilder::limited(Arc::new(Keypair::new())).with_compute_unit_price(Some(1));
+        tx_builder.add_instruction(instruction).unwrap();
+        // the hash does not depend on the compute budget
+        assert_eq!(
+            instructions_hash(&prepared_transaction),
+            instructions_hash(&tx_builder.build_one())
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "marinade-execution-journal-{}.jsonl",
+            Pubkey::new_unique()
+        ));
+        let hash = instructions_hash(&prepared_transaction).to_string();
+        let signature = Signature::new_unique();
+        {
+            let mut journal = ExecutionJournal::open(&path).unwrap();
+            assert!(journal.entries().is_empty());
+            for status in [JournalStatus::Sent, JournalStatus::Confirmed] {
+                journal
+                    .record(JournalEntry {
+                        instruction_packs: vec![0],
+                        instructions_hash: hash.clone(),
+                        signature: Some(signature.to_string()),
+                        blockhash: Some(Hash::default().to_string()),
+                        last_valid_block_height: Some(42),
+                        status,
+                        error: None,
+                    })
+                    .unwrap();
+            }
+        }
+        let journal = ExecutionJournal::open(&path).unwrap();
+        assert_eq!(journal.entries().len(), 2);
+        assert_eq!(journal.entries()[1].status, JournalStatus::Confirmed);
+        assert_eq!(journal.signatures(&[0], &hash), vec![signature]);
+        assert_eq!(journal.last_valid_block_height(&signature), Some(42));
+        // the same instructions of another instruction pack are a different transaction
+        assert!(journal.signatures(&[1], &hash).is_empty());
+        assert!(journal
+            .signatures(&[0], &Hash::default().to_string())
+            .is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
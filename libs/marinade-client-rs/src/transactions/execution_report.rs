@@ -0,0 +1,255 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::transaction_diagnostic::{
+    DiagnoseTransactionError, TransactionDiagnostic, TransactionFailure,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_response::{Response, RpcSimulateTransactionResult};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Transaction was sent and confirmed.
+    Executed,
+    /// Transaction was sent or attempted to be sent and failed.
+    Failed,
+    /// Transaction was simulated with no error.
+    Simulated,
+    /// Transaction simulation returned an error.
+    SimulationFailed,
+    /// Transaction was not sent as a previous transaction failed or only the first one was simulated.
+    Skipped,
+    /// Transaction was printed or exported only, it was not sent.
+    Printed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Accounts changed by the previously simulated transactions that this simulation could not see,
+    /// the result may differ from the real execution when not empty.
+    #[serde(default)]
+    pub stale_accounts: Vec<String>,
+}
+
+/// Result of one transaction, the instruction packs are indexes of the packs
+/// (or anchor builders) the transaction was built from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionReport {
+    pub instruction_packs: Vec<usize>,
+    pub status: TransactionStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub slot: Option<u64>,
+    pub compute_units_consumed: Option<u64>,
+    pub fee: Option<u64>,
+    pub simulation: Option<SimulationReport>,
+    /// Decoded failure of the transaction (failing instruction, program error and its logs).
+    #[serde(default)]
+    pub diagnostic: Option<TransactionDiagnostic>,
+}
+
+impl TransactionReport {
+    fn new(instruction_packs: Vec<usize>, status: TransactionStatus) -> Self {
+        Self {
+            instruction_packs,
+            status,
+            signature: None,
+            error: None,
+            slot: None,
+            compute_units_consumed: None,
+            fee: None,
+            simulation: None,
+            diagnostic: None,
+        }
+    }
+
+    pub fn skipped(instruction_packs: Vec<usize>) -> Self {
+        Self::new(instruction_packs, TransactionStatus::Skipped)
+    }
+
+    pub fn printed(instruction_packs: Vec<usize>) -> Self {
+        Self::new(instruction_packs, TransactionStatus::Printed)
+    }
+
+    pub fn from_execution<E: std::fmt::Debug + DiagnoseTransactionError>(
+        instruction_packs: Vec<usize>,
+        execution_result: &Result<Signature, E>,
+    ) -> Self {
+        match execution_result {
+            Ok(signature) => Self {
+                signature: Some(signature.to_string()),
+                ..Self::new(instruction_packs, TransactionStatus::Executed)
+            },
+            Err(err) => Self {
+                error: Some(format!("{:?}", err)),
+                diagnostic: err.diagnostic(),
+                ..Self::new(instruction_packs, TransactionStatus::Failed)
+            },
+        }
+    }
+
+    pub fn from_simulation<E: std::fmt::Display + DiagnoseTransactionError>(
+        instruction_packs: Vec<usize>,
+        simulation_result: &Result<Response<RpcSimulateTransactionResult>, E>,
+    ) -> Self {
+        match simulation_result {
+            Ok(response) => {
+                let simulation = &response.value;
+                let status = if simulation.err.is_some() {
+                    TransactionStatus::SimulationFailed
+                } else {
+                    TransactionStatus::Simulated
+                };
+                Self {
+                    error: simulation.err.as_ref().map(|err| err.to_string()),
+                    slot: Some(response.context.slot),
+                    compute_units_consumed: simulation.units_consumed,
+                    simulation: Some(SimulationReport {
+                        error: simulation.err.as_ref().map(|err| err.to_string()),
+                        logs: simulation.logs.clone().unwrap_or_default(),
+                        units_consumed: simulation.units_consumed,
+                        stale_accounts: vec![],
+                    }),
+                    diagnostic: TransactionDiagnostic::from_simulation(simulation),
+                    ..Self::new(instruction_packs, status)
+                }
+            }
+            Err(err) => Self {
+                error: Some(err.to_string()),
+                diagnostic: err.diagnostic(),
+                ..Self::new(instruction_packs, TransactionStatus::SimulationFailed)
+            },
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(
+            self.status,
+            TransactionStatus::Failed | TransactionStatus::SimulationFailed
+        )
+    }
+
+    /// Typed error of the failed transaction.
+    pub fn failure(&self) -> Option<TransactionFailure> {
+        match (&self.status, &self.diagnostic) {
+            (TransactionStatus::Failed, Some(diagnostic)) => {
+                Some(TransactionFailure::Execution(diagnostic.clone()))
+            }
+            (TransactionStatus::SimulationFailed, Some(diagnostic)) => {
+                Some(TransactionFailure::Simulation(diagnostic.clone()))
+            }
+            (TransactionStatus::Failed | TransactionStatus::SimulationFailed, None) => {
+                Some(TransactionFailure::Client(
+                    self.error.clone().unwrap_or_else(|| "unknown".to_string()),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Completes the diagnostic of the failed transaction from its message,
+    /// see [TransactionDiagnostic::with_message].
+    pub fn with_message(mut self, message: &VersionedMessage) -> Self {
+        self.diagnostic = self
+            .diagnostic
+            .map(|diagnostic| diagnostic.with_message(message));
+        self
+    }
+
+    /// Fills the slot, fee and consumed compute units of the confirmed transaction.
+    pub fn with_confirmed_transaction(
+        mut self,
+        confirmed_transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Self {
+        self.slot = Some(confirmed_transaction.slot);
+        if let Some(meta) = &confirmed_transaction.transaction.meta {
+            self.fee = Some(meta.fee);
+            self.compute_units_consumed = meta.compute_units_consumed.clone().into();
+        }
+        self
+    }
+
+    /// Loads the details of the executed transaction from the RPC node,
+    /// the report is returned unchanged when the transaction cannot be loaded.
+    pub fn load_details(self, rpc_client: &RpcClient) -> Self {
+        let signature = match self.executed_signature() {
+            Some(signature) => signature,
+            None => return self,
+        };
+        match rpc_client.get_transaction_with_config(&signature, confirmed_transaction_config()) {
+            Ok(confirmed_transaction) => self.with_confirmed_transaction(&confirmed_transaction),
+            Err(err) => {
+                warn!(
+                    "Cannot load details of the transaction {}: {:?}",
+                    signature, err
+                );
+                self
+            }
+        }
+    }
+
+    pub(crate) fn executed_signature(&self) -> Option<Signature> {
+        if self.status != TransactionStatus::Executed {
+            return None;
+        }
+        self.signature
+            .as_ref()
+            .and_then(|signature| signature.parse().ok())
+    }
+}
+
+pub(crate) fn confirmed_transaction_config() -> RpcTransactionConfig {
+    RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    }
+}
+
+/// Report of the executor run with one entry per transaction in the order of execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub transactions: Vec<TransactionReport>,
+}
+
+impl ExecutionReport {
+    pub fn push(&mut self, transaction_report: TransactionReport) {
+        self.transactions.push(transaction_report);
+    }
+
+    pub fn is_success(&self) -> bool {
+        !self.transactions.iter().any(TransactionReport::is_failed)
+    }
+
+    pub fn signatures(&self) -> Vec<&str> {
+        self.transactions
+            .iter()
+            .filter_map(|transaction| transaction.signature.as_deref())
+            .collect()
+    }
+
+    /// Fails with the [TransactionFailure] of the first failed transaction.
+    pub fn into_result(self) -> Result<Self, MarinadeClientError> {
+        if let Some(failure) = self
+            .transactions
+            .iter()
+            .find_map(TransactionReport::failure)
+        {
+            return Err(failure.into());
+        }
+        Ok(self)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
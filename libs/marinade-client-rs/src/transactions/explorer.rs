@@ -0,0 +1,123 @@
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use solana_sdk::message::VersionedMessage;
+
+const EXPLORER_URL: &str = "https://explorer.solana.com";
+
+/// Cluster of the block explorer, derived from the RPC URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplorerCluster {
+    MainnetBeta,
+    Testnet,
+    Devnet,
+    /// Any other RPC node, e.g., localhost, the explorer connects to the URL directly.
+    Custom(String),
+}
+
+impl ExplorerCluster {
+    /// The URL is expected as normalized from the moniker (e.g., `https://api.devnet.solana.com`).
+    pub fn from_rpc_url(rpc_url: &str) -> Self {
+        let host = rpc_url
+            .split("://")
+            .last()
+            .unwrap_or(rpc_url)
+            .split(&['/', ':'][..])
+            .next()
+            .unwrap_or_default();
+        match host {
+            "api.mainnet-beta.solana.com" => Self::MainnetBeta,
+            "api.testnet.solana.com" => Self::Testnet,
+            "api.devnet.solana.com" => Self::Devnet,
+            _ => Self::Custom(rpc_url.to_string()),
+        }
+    }
+
+    fn query(&self) -> String {
+        match self {
+            Self::MainnetBeta => String::new(),
+            Self::Testnet => "&cluster=testnet".to_string(),
+            Self::Devnet => "&cluster=devnet".to_string(),
+            Self::Custom(rpc_url) => format!("&cluster=custom&customUrl={}", url_encode(rpc_url)),
+        }
+    }
+}
+
+/// Base64 of the serialized message.
+pub fn message_base64(message: &VersionedMessage) -> String {
+    anchor_lang::__private::base64::encode(message.serialize())
+}
+
+/// URL of the explorer transaction inspector showing the message.
+pub fn inspector_url(message: &VersionedMessage, cluster: &ExplorerCluster) -> String {
+    format!(
+        "{}/tx/inspector?message={}{}",
+        EXPLORER_URL,
+        url_encode(&message_base64(message)),
+        cluster.query()
+    )
+}
+
+pub fn print_prepared_message(
+    prepared_transaction: &PreparedTransaction,
+    cluster: &ExplorerCluster,
+) {
+    let message = &prepared_transaction.transaction.message;
+    println!(
+        "message: {}\n  inspector: {}",
+        message_base64(message),
+        inspector_url(message, cluster)
+    );
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_inspector_url() {
+        assert_eq!(
+            ExplorerCluster::from_rpc_url("https://api.mainnet-beta.solana.com"),
+            ExplorerCluster::MainnetBeta
+        );
+        assert_eq!(
+            ExplorerCluster::from_rpc_url("https://api.devnet.solana.com/"),
+            ExplorerCluster::Devnet
+        );
+        let localhost = ExplorerCluster::from_rpc_url("http://localhost:8899");
+        assert_eq!(
+            localhost.query(),
+            "&cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899"
+        );
+
+        let fee_payer = Pubkey::new_unique();
+        let message = VersionedMessage::Legacy(Message::new(
+            &[Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[0xfb, 0xff],
+                vec![AccountMeta::new(fee_payer, true)],
+            )],
+            Some(&fee_payer),
+        ));
+        let url = inspector_url(&message, &ExplorerCluster::Testnet);
+        assert!(url.starts_with("https://explorer.solana.com/tx/inspector?message="));
+        assert!(url.ends_with("&cluster=testnet"));
+        let encoded = url
+            .trim_start_matches("https://explorer.solana.com/tx/inspector?message=")
+            .trim_end_matches("&cluster=testnet");
+        assert!(!encoded.contains(&['+', '/', '='][..]));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::transaction_builder::TransactionBuilder;
+use crate::transactions::transaction_instruction::TransactionInstruction;
+use serde::Deserialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Path reading the instructions from stdin.
+pub const STDIN_PATH: &str = "-";
+
+#[derive(Debug, Deserialize)]
+struct JsonAccount {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonInstruction {
+    program_id: String,
+    accounts: Vec<JsonAccount>,
+    /// Base64 of the instruction data.
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonInstructionEntry {
+    Base64(String),
+    Instruction(JsonInstruction),
+}
+
+/// Loads the instructions from the file, or from stdin when the path is [STDIN_PATH].
+pub fn load_instructions(path: &str) -> Result<Vec<Instruction>, MarinadeClientError> {
+    let content = if path == STDIN_PATH {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    parse_instructions(&content)
+}
+
+/// Parses the output of [crate::transactions::transaction_instruction::print_base64]
+/// (one base64 per line, the `program:` lines are skipped) or a JSON array where an item is
+/// either the base64 instruction or an object with `program_id`, `accounts` and base64 `data`.
+pub fn parse_instructions(content: &str) -> Result<Vec<Instruction>, MarinadeClientError> {
+    if content.trim_start().starts_with('[') {
+        let entries: Vec<JsonInstructionEntry> = serde_json::from_str(content)
+            .map_err(|e| MarinadeClientError::deserialization("JSON instructions", e))?;
+        return entries.into_iter().map(json_instruction).collect();
+    }
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("program:"))
+        .map(base64_instruction)
+        .collect()
+}
+
+fn base64_instruction(base64: &str) -> Result<Instruction, MarinadeClientError> {
+    Ok(Instruction::from(&TransactionInstruction::from_base64(
+        base64,
+    )?))
+}
+
+fn json_instruction(entry: JsonInstructionEntry) -> Result<Instruction, MarinadeClientError> {
+    let instruction = match entry {
+        JsonInstructionEntry::Base64(base64) => return base64_instruction(&base64),
+        JsonInstructionEntry::Instruction(instruction) => instruction,
+    };
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|account| {
+            let pubkey = parse_pubkey(&account.pubkey)?;
+            Ok(match account.is_writable {
+                false => AccountMeta::new_readonly(pubkey, account.is_signer),
+                true => AccountMeta::new(pubkey, account.is_signer),
+            })
+        })
+        .collect::<Result<Vec<_>, MarinadeClientError>>()?;
+    Ok(Instruction {
+        program_id: parse_pubkey(&instruction.program_id)?,
+        accounts,
+        data: anchor_lang::__private::base64::decode(&instruction.data)
+            .map_err(|e| MarinadeClientError::deserialization("instruction data", e))?,
+    })
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, MarinadeClientError> {
+    Pubkey::from_str(pubkey).map_err(|e| MarinadeClientError::deserialization("pubkey", e))
+}
+
+/// Signers required by the instructions, in the order of appearance.
+pub fn required_signers(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut signers: Vec<Pubkey> = vec![];
+    for account in instructions
+        .iter()
+        .flat_map(|instruction| instruction.accounts.iter())
+        .filter(|account| account.is_signer)
+    {
+        if !signers.contains(&account.pubkey) {
+            signers.push(account.pubkey);
+        }
+    }
+    signers
+}
+
+/// Adds the signers and the loaded instructions to the builder, every instruction is one instruction pack
+/// as the grouping of the printed instructions is not known. The builder fails with the unknown signer
+/// when a signer required by the instructions is not provided.
+pub fn add_loaded_instructions(
+    transaction_builder: &mut TransactionBuilder,
+    instructions: Vec<Instruction>,
+    signers: &[Arc<dyn Signer + Send + Sync>],
+) -> Result<(), MarinadeClientError> {
+    for signer in signers {
+        transaction_builder.add_signer(signer.clone());
+    }
+    for instruction in instructions {
+        transaction_builder.add_instruction(instruction)?;
+        transaction_builder.finish_instruction_pack();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::transaction_builder::TransactionBuildError;
+    use anchor_lang::prelude::AnchorSerialize;
+    use solana_sdk::signature::Keypair;
+
+    fn to_base64(instruction: &Instruction) -> String {
+        anchor_lang::__private::base64::encode(
+            TransactionInstruction::from(instruction)
+                .try_to_vec()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_instructions() {
+        let signer = Keypair::new();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![
+                AccountMeta::new(signer.pubkey(), true),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            ],
+        );
+        // the format of print_base64
+        let printed = format!(
+            "program: {}\n  {}\n\n",
+            instruction.program_id,
+            to_base64(&instruction)
+        );
+        assert_eq!(
+            parse_instructions(&printed).unwrap(),
+            vec![instruction.clone()]
+        );
+
+        let json = format!(
+            r#"["{}", {{"program_id": "{}", "accounts": [{{"pubkey": "{}", "is_signer": true, "is_writable": true}},
+                {{"pubkey": "{}", "is_signer": false, "is_writable": false}}], "data": "AQID"}}]"#,
+            to_base64(&instruction),
+            instruction.program_id,
+            instruction.accounts[0].pubkey,
+            instruction.accounts[1].pubkey,
+        );
+        let instructions = parse_instructions(&json).unwrap();
+        assert_eq!(instructions, vec![instruction.clone(), instruction.clone()]);
+        assert_eq!(required_signers(&instructions), vec![signer.pubkey()]);
+
+        let mut transaction_builder = TransactionBuilder::unlimited(Arc::new(Keypair::new()));
+        assert!(matches!(
+            add_loaded_instructions(&mut transaction_builder, instructions.clone(), &[]),
+            Err(MarinadeClientError::TransactionBuild(
+                TransactionBuildError::UnknownSigner(_)
+            ))
+        ));
+        let mut transaction_builder = TransactionBuilder::unlimited(Arc::new(Keypair::new()));
+        let signer: Arc<dyn Signer + Send + Sync> = Arc::new(signer);
+        add_loaded_instructions(&mut transaction_builder, instructions, &[signer]).unwrap();
+        assert_eq!(transaction_builder.instruction_packs().len(), 2);
+    }
+}
@@ -1,5 +1,23 @@
+#[cfg(feature = "bank-simulator")]
+pub mod bank_simulator;
+pub mod chained_simulation;
+pub mod compute_budget;
+pub mod durable_nonce;
+pub mod execution_cost;
+pub mod execution_journal;
+pub mod execution_report;
+pub mod explorer;
+pub mod instruction_loader;
+pub mod nonblocking;
+pub mod offline_transaction;
 pub mod prepared_transaction;
+pub mod rebroadcast;
+pub mod retry_policy;
+pub mod sender;
 pub mod signature_builder;
+pub mod spl_governance;
 pub mod transaction_builder;
+pub mod transaction_diagnostic;
+pub mod transaction_executor;
 pub mod transaction_executors;
 pub mod transaction_instruction;
@@ -0,0 +1,2 @@
+pub mod rebroadcast;
+pub mod transaction_executors;
@@ -0,0 +1,192 @@
+use crate::transactions::durable_nonce::durable_nonce_account;
+use crate::transactions::rebroadcast::{
+    expired_transaction_error, rebroadcast_config, CONFIRMATION_POLL_INTERVAL,
+};
+use log::{debug, info, warn};
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::nonblocking::nonce_utils;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::TransactionStatus;
+use std::time::{Duration, Instant};
+
+/// Async counterpart of [crate::transactions::rebroadcast::send_and_confirm_transaction_with_rebroadcast].
+pub async fn send_and_confirm_transaction_with_rebroadcast(
+    rpc_client: &RpcClient,
+    send_rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    preflight_config: RpcSendTransactionConfig,
+    last_valid_block_height: Option<u64>,
+    rebroadcast_interval: Option<Duration>,
+) -> Result<Signature, SolanaClientError> {
+    let signature = send_rpc_client
+        .send_transaction_with_config(transaction, preflight_config)
+        .await?;
+    info!(
+        "Transaction {} sent to {}",
+        signature,
+        send_rpc_client.url()
+    );
+    confirm_transaction_with_rebroadcast(
+        rpc_client,
+        send_rpc_client,
+        transaction,
+        preflight_config,
+        last_valid_block_height,
+        rebroadcast_interval,
+    )
+    .await?;
+    Ok(signature)
+}
+
+/// Async counterpart of [crate::transactions::rebroadcast::confirm_transaction_with_rebroadcast].
+pub async fn confirm_transaction_with_rebroadcast(
+    rpc_client: &RpcClient,
+    send_rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    preflight_config: RpcSendTransactionConfig,
+    last_valid_block_height: Option<u64>,
+    rebroadcast_interval: Option<Duration>,
+) -> Result<(), SolanaClientError> {
+    let signature = transaction.signatures[0];
+    let nonce_account = durable_nonce_account(transaction);
+    let started = Instant::now();
+    let mut last_broadcast = Instant::now();
+    let mut rebroadcasts = 0_u32;
+    loop {
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        let status = match get_signature_status(rpc_client, &signature).await {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        if let Some(status) = status {
+            if let Some(err) = status.err {
+                return Err(err.into());
+            }
+            if status.satisfies_commitment(rpc_client.commitment()) {
+                info!(
+                    "Transaction {} confirmed at slot {} after {:?}",
+                    signature,
+                    status.slot,
+                    started.elapsed()
+                );
+                return Ok(());
+            }
+            debug!(
+                "Transaction {} processed at slot {}, awaiting {:?} commitment",
+                signature,
+                status.slot,
+                rpc_client.commitment().commitment
+            );
+            continue;
+        }
+
+        let is_expired = match (last_valid_block_height, nonce_account) {
+            (Some(last_valid_block_height), _) => match rpc_client.get_block_height().await {
+                Ok(block_height) => {
+                    debug!(
+                        "Transaction {} not processed yet, block height {}, last valid block height {}",
+                        signature, block_height, last_valid_block_height
+                    );
+                    block_height > last_valid_block_height
+                }
+                Err(err) => {
+                    warn!(
+                        "Transaction {} block height cannot be loaded: {:?}",
+                        signature, err
+                    );
+                    false
+                }
+            },
+            (None, Some(nonce_account)) => {
+                is_nonce_advanced(rpc_client, transaction, &nonce_account).await
+            }
+            (None, None) => false,
+        };
+        // the transaction may land in between, the status is checked once more
+        if is_expired && matches!(get_signature_status(rpc_client, &signature).await, Ok(None)) {
+            warn!(
+                "Transaction {} expired after {} rebroadcast(s)",
+                signature, rebroadcasts
+            );
+            return Err(expired_transaction_error(&signature));
+        }
+
+        if let Some(rebroadcast_interval) = rebroadcast_interval {
+            if last_broadcast.elapsed() >= rebroadcast_interval {
+                rebroadcasts += 1;
+                last_broadcast = Instant::now();
+                match send_rpc_client
+                    .send_transaction_with_config(transaction, rebroadcast_config(preflight_config))
+                    .await
+                {
+                    Ok(_) => debug!(
+                        "Transaction {} rebroadcast #{} to {}",
+                        signature,
+                        rebroadcasts,
+                        send_rpc_client.url()
+                    ),
+                    Err(err) => warn!(
+                        "Transaction {} rebroadcast #{} failed: {:?}",
+                        signature, rebroadcasts, err
+                    ),
+                }
+            }
+        }
+    }
+}
+
+async fn get_signature_status(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<Option<TransactionStatus>, SolanaClientError> {
+    rpc_client
+        .get_signature_statuses(&[*signature])
+        .await
+        .map(|response| response.value[0].clone())
+        .map_err(|err| {
+            warn!(
+                "Transaction {} status cannot be loaded: {:?}",
+                signature, err
+            );
+            err
+        })
+}
+
+async fn is_nonce_advanced(
+    rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    nonce_account: &Pubkey,
+) -> bool {
+    let nonce = match nonce_utils::get_account_with_commitment(
+        rpc_client,
+        nonce_account,
+        rpc_client.commitment(),
+    )
+    .await
+    {
+        Ok(account) => nonce_utils::data_from_account(&account).map(|data| data.blockhash()),
+        Err(err) => Err(err),
+    };
+    match nonce {
+        Ok(nonce) => {
+            debug!(
+                "Transaction {} not processed yet, nonce {}, transaction nonce {}",
+                transaction.signatures[0],
+                nonce,
+                transaction.message.recent_blockhash()
+            );
+            nonce != *transaction.message.recent_blockhash()
+        }
+        Err(err) => {
+            warn!(
+                "Transaction {} nonce cannot be loaded: {:?}",
+                transaction.signatures[0], err
+            );
+            false
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::compute_budget::ComputeUnitPrice;
+use crate::transactions::execution_report::{
+    confirmed_transaction_config, ExecutionReport, TransactionReport,
+};
+use crate::transactions::nonblocking::rebroadcast::confirm_transaction_with_rebroadcast;
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::transactions::retry_policy::{
+    classify_confirm_error, classify_send_error, ErrorClass, RetryPolicy, RetryState,
+};
+use crate::transactions::transaction_builder::TransactionBuilder;
+use crate::transactions::transaction_executors::{
+    estimated_compute_unit_limit, estimation_simulation_config, log_execution, log_simulation,
+    simulation_config, warn_text_simulate_print,
+};
+use crate::transactions::transaction_instruction::print_base64;
+use log::{debug, error, info, warn};
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::client_error::ClientErrorKind;
+use solana_client::nonblocking::nonce_utils;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig};
+use solana_client::rpc_request::RpcError::ForUser;
+use solana_client::rpc_response::{RpcResult, RpcSimulateTransactionResult};
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::VersionedTransaction;
+
+/// Async counterpart of [crate::transactions::transaction_executors::execute_transaction_builder].
+pub async fn execute_transaction_builder(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_transaction_builder_report(
+        transaction_builder,
+        rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        simulate,
+        print,
+        retry_policy,
+    )
+    .await?
+    .into_result()
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::execute_transaction_builder_report].
+pub async fn execute_transaction_builder_report(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    warn_text_simulate_print(simulate, print);
+
+    if print {
+        print_base64(&transaction_builder.instructions())?;
+    }
+
+    let mut report = ExecutionReport::default();
+    if simulate {
+        // expecting the instructions are dependent one to each other
+        // the result of the first can be used in the next one, for that simulation is run only for the fist bunch
+        let is_checked_signers = transaction_builder.is_check_signers();
+        let mut prepared_transactions = transaction_builder.sequence_combined();
+        if let Some(mut prepared_transaction) = prepared_transactions.next() {
+            let simulation_result = simulate_prepared_transaction(
+                &mut prepared_transaction,
+                rpc_client,
+                simulation_config(&preflight_config, !print && is_checked_signers),
+                blockhash_commitment,
+            )
+            .await;
+            // failures are kept in the report
+            let _ = log_simulation(&simulation_result);
+            report.push(
+                TransactionReport::from_simulation(
+                    prepared_transaction.instruction_packs,
+                    &simulation_result,
+                )
+                .with_message(&prepared_transaction.transaction.message),
+            );
+        }
+        // need to drain whole sequence to find the number of transaction bunches
+        for prepared_transaction in prepared_transactions {
+            report.push(TransactionReport::skipped(
+                prepared_transaction.instruction_packs,
+            ));
+        }
+        if report.transactions.len() > 1 {
+            warn!("Simulation mode: only the first bunch of transactions was simulated, the rest was not simulated.");
+        }
+    } else {
+        for mut prepared_transaction in transaction_builder.sequence_combined() {
+            if !report.is_success() {
+                report.push(TransactionReport::skipped(
+                    prepared_transaction.instruction_packs,
+                ));
+                continue;
+            }
+            let execution_result = execute_prepared_transaction_with_retry_policy(
+                &mut prepared_transaction,
+                rpc_client,
+                preflight_config,
+                blockhash_commitment,
+                retry_policy,
+            )
+            .await;
+            let _ = log_execution(&execution_result);
+            let transaction_report = TransactionReport::from_execution(
+                prepared_transaction.instruction_packs,
+                &execution_result,
+            )
+            .with_message(&prepared_transaction.transaction.message);
+            report.push(load_transaction_details(transaction_report, rpc_client).await);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Async counterpart of [TransactionReport::load_details].
+pub async fn load_transaction_details(
+    transaction_report: TransactionReport,
+    rpc_client: &RpcClient,
+) -> TransactionReport {
+    let signature = match transaction_report.executed_signature() {
+        Some(signature) => signature,
+        None => return transaction_report,
+    };
+    match rpc_client
+        .get_transaction_with_config(&signature, confirmed_transaction_config())
+        .await
+    {
+        Ok(confirmed_transaction) => {
+            transaction_report.with_confirmed_transaction(&confirmed_transaction)
+        }
+        Err(err) => {
+            warn!(
+                "Cannot load details of the transaction {}: {:?}",
+                signature, err
+            );
+            transaction_report
+        }
+    }
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::get_transaction_blockhash].
+pub async fn get_transaction_blockhash(
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<Hash, SolanaClientError> {
+    match prepared_transaction.durable_nonce() {
+        Some(durable_nonce) => {
+            let nonce_account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                &durable_nonce.nonce_account,
+                rpc_client.commitment(),
+            )
+            .await
+            .map_err(|e| {
+                SolanaClientError::from(ClientErrorKind::Custom(format!(
+                    "Cannot load durable nonce account {}: {}",
+                    durable_nonce.nonce_account, e
+                )))
+            })?;
+            nonce_utils::data_from_account(&nonce_account)
+                .map(|data| data.blockhash())
+                .map_err(|e| {
+                    SolanaClientError::from(ClientErrorKind::Custom(format!(
+                        "Cannot load durable nonce from account {}: {}",
+                        durable_nonce.nonce_account, e
+                    )))
+                })
+        }
+        None => rpc_client.get_latest_blockhash().await,
+    }
+}
+
+/// Signs the transaction and sends it, returns the signed transaction with its last valid block height.
+/// On error the RPC did not accept the transaction and it may be sent again.
+async fn sign_and_send_prepared_transaction(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+) -> Result<(VersionedTransaction, Option<u64>), SolanaClientError> {
+    let (latest_hash, last_valid_block_height) = match prepared_transaction.durable_nonce() {
+        Some(_) => (
+            get_transaction_blockhash(prepared_transaction, rpc_client).await?,
+            None,
+        ),
+        None => {
+            let (latest_hash, last_valid_block_height) = rpc_client
+                .get_latest_blockhash_with_commitment(rpc_client.commitment())
+                .await?;
+            (latest_hash, Some(last_valid_block_height))
+        }
+    };
+    let tx = prepared_transaction
+        .sign(latest_hash)
+        .map_err(|e| {
+            error!(
+                "execute_prepared_transaction: error signing transaction with blockhash: {}: {:?}",
+                latest_hash, e
+            );
+            SolanaClientError::from(e)
+        })?
+        .clone();
+    let signature = rpc_client
+        .send_transaction_with_config(&tx, preflight_config)
+        .await?;
+    info!("Transaction {} sent to {}", signature, rpc_client.url());
+    Ok((tx, last_valid_block_height))
+}
+
+fn send_failed_error(
+    err: SolanaClientError,
+    error_class: ErrorClass,
+    retry_state: &RetryState,
+) -> anchor_client::ClientError {
+    if error_class == ErrorClass::Fatal {
+        error!("Transaction ERR send_transaction: {:?}", err);
+    } else {
+        error!(
+            "Transaction ERR send_transaction: retry exhausted after {} attempt(s); last error: {:?}",
+            retry_state.attempts(),
+            err
+        );
+    }
+    anchor_client::ClientError::SolanaClientError(err)
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::execute_prepared_transaction_blockhash_retry].
+pub async fn execute_prepared_transaction_blockhash_retry(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    blockhash_failure_retries: Option<u16>,
+) -> Result<Signature, anchor_client::ClientError> {
+    execute_prepared_transaction_with_retry_policy(
+        prepared_transaction,
+        rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        &RetryPolicy::blockhash_not_found_retries(blockhash_failure_retries),
+    )
+    .await
+}
+
+/// Sends the transaction, the transaction the RPC did not accept is sent again as the retry policy permits.
+/// The accepted one is re-signed with a fresh blockhash only when it expired with no status.
+pub async fn execute_prepared_transaction_with_retry_policy(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: &RetryPolicy,
+) -> Result<Signature, anchor_client::ClientError> {
+    estimate_compute_unit_price(prepared_transaction, rpc_client).await?;
+    estimate_compute_unit_limit(prepared_transaction, rpc_client).await?;
+    let rpc_client_blockhash = RpcClient::new_with_commitment(
+        rpc_client.url(),
+        CommitmentConfig {
+            commitment: blockhash_commitment,
+        },
+    );
+
+    let mut retry_state = retry_policy.start();
+    loop {
+        let send_result = sign_and_send_prepared_transaction(
+            prepared_transaction,
+            &rpc_client_blockhash,
+            preflight_config,
+        )
+        .await;
+        let (err, error_class) = match send_result {
+            Err(err) => {
+                let error_class = classify_send_error(&err, prepared_transaction);
+                (err, error_class)
+            }
+            Ok((tx, last_valid_block_height)) => loop {
+                let err = match confirm_transaction_with_rebroadcast(
+                    &rpc_client_blockhash,
+                    &rpc_client_blockhash,
+                    &tx,
+                    preflight_config,
+                    last_valid_block_height,
+                    retry_policy.rebroadcast_interval,
+                )
+                .await
+                {
+                    Ok(()) => return Ok(tx.signatures[0]),
+                    Err(err) => err,
+                };
+                let error_class = classify_confirm_error(&err, prepared_transaction);
+                if error_class != ErrorClass::Retryable {
+                    break (err, error_class);
+                }
+                // the transaction is not sent again, its status is awaited after the backoff
+                match retry_state.retry_after(error_class) {
+                    Some(delay) => {
+                        debug!(
+                            "Confirming transaction {} again in {:?} after error: {:?}",
+                            tx.signatures[0], delay, err
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => return Err(send_failed_error(err, error_class, &retry_state)),
+                }
+            },
+        };
+        match retry_state.retry_after(error_class) {
+            Some(delay) => {
+                debug!(
+                    "Retrying attempt #{}/{} to send transaction in {:?} after {:?} error: {:?}",
+                    retry_state.attempts(),
+                    retry_policy.max_attempts,
+                    delay,
+                    error_class,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+            }
+            None => return Err(send_failed_error(err, error_class, &retry_state)),
+        }
+    }
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::simulate_prepared_transaction].
+pub async fn simulate_prepared_transaction(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    simulate_config: RpcSimulateTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+) -> RpcResult<RpcSimulateTransactionResult> {
+    let rpc_client_blockhash = RpcClient::new_with_commitment(
+        rpc_client.url(),
+        CommitmentConfig {
+            commitment: blockhash_commitment,
+        },
+    );
+    let latest_blockhash =
+        get_transaction_blockhash(prepared_transaction, &rpc_client_blockhash).await?;
+    let tx = if simulate_config.sig_verify {
+        prepared_transaction.sign(latest_blockhash).map_err(|e| {
+            error!(
+                "simulate_prepared_transaction: error signing transaction with blockhash: {}: {:?}",
+                latest_blockhash, e
+            );
+            ForUser(format!("Signing transaction error: {}", e))
+        })?
+    } else {
+        prepared_transaction.partial_sign(latest_blockhash)
+    };
+
+    rpc_client
+        .simulate_transaction_with_config(tx, simulate_config)
+        .await
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::estimate_compute_unit_price].
+pub async fn estimate_compute_unit_price(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<(), SolanaClientError> {
+    let compute_unit_price = match prepared_transaction
+        .compute_budget()
+        .and_then(|compute_budget| compute_budget.compute_unit_price)
+    {
+        Some(compute_unit_price) if !compute_unit_price.is_fixed() => compute_unit_price,
+        _ => return Ok(()),
+    };
+    let price = get_compute_unit_price(
+        &compute_unit_price,
+        &prepared_transaction.writable_accounts(),
+        rpc_client,
+    )
+    .await?;
+    prepared_transaction
+        .set_compute_unit_price(price)
+        .map_err(|e| {
+            error!(
+                "estimate_compute_unit_price: cannot set compute unit price {}: {:?}",
+                price, e
+            );
+            SolanaClientError::from(ClientErrorKind::Custom(format!(
+                "Setting compute unit price error: {}",
+                e
+            )))
+        })
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::get_compute_unit_price].
+pub async fn get_compute_unit_price(
+    compute_unit_price: &ComputeUnitPrice,
+    writable_accounts: &[solana_sdk::pubkey::Pubkey],
+    rpc_client: &RpcClient,
+) -> Result<u64, SolanaClientError> {
+    if let ComputeUnitPrice::Fixed(price) = compute_unit_price {
+        return Ok(*price);
+    }
+    let recent_fees = rpc_client
+        .get_recent_prioritization_fees(writable_accounts)
+        .await?
+        .iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect::<Vec<u64>>();
+    let price = compute_unit_price.from_recent_fees(&recent_fees);
+    debug!(
+        "get_compute_unit_price: strategy {:?}, {} recent fees for accounts {:?}, compute unit price {}",
+        compute_unit_price,
+        recent_fees.len(),
+        writable_accounts,
+        price
+    );
+    Ok(price)
+}
+
+/// Async counterpart of [crate::transactions::transaction_executors::estimate_compute_unit_limit].
+pub async fn estimate_compute_unit_limit(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<(), SolanaClientError> {
+    let estimation = if let Some(estimation) = prepared_transaction
+        .compute_budget()
+        .and_then(|compute_budget| compute_budget.compute_unit_limit_estimation)
+    {
+        estimation
+    } else {
+        return Ok(());
+    };
+
+    let simulation_result = rpc_client
+        .simulate_transaction_with_config(
+            &prepared_transaction.transaction,
+            estimation_simulation_config(),
+        )
+        .await?
+        .value;
+    let compute_unit_limit = estimated_compute_unit_limit(estimation, simulation_result)?;
+    prepared_transaction
+        .set_compute_unit_limit(compute_unit_limit)
+        .map_err(|e| {
+            error!(
+                "estimate_compute_unit_limit: cannot set compute unit limit {}: {:?}",
+                compute_unit_limit, e
+            );
+            SolanaClientError::from(ClientErrorKind::Custom(format!(
+                "Setting compute unit limit error: {}",
+                e
+            )))
+        })
+}
+
+/// Signs and sends the prepared transaction, logs the signers when the sending fails.
+pub async fn execute_prepared_transaction(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+) -> Result<Signature, anchor_client::ClientError> {
+    execute_prepared_transaction_blockhash_retry(
+        prepared_transaction,
+        rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        None,
+    )
+    .await
+    .map_err(|e| {
+        error!(
+            "execute_prepared_transaction: error send_and_confirm transaction '{:?}', signers: '{:?}': {:?}",
+            prepared_transaction.transaction,
+            prepared_transaction.signers.iter().map(|s| s.pubkey()).collect::<Vec<_>>(),
+            e
+        );
+        e
+    })
+}
@@ -0,0 +1,246 @@
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use serde::{Deserialize, Serialize};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::VersionedTransaction;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum OfflineTransactionError {
+    #[error("Cannot decode exported transaction: {0}")]
+    Decode(String),
+    #[error("Exported transaction message differs from the transaction message")]
+    MessageMismatch,
+    #[error("Pubkey {0} is not a signer of the transaction")]
+    UnknownSigner(Pubkey),
+    #[error("Invalid signature of the signer {0}")]
+    InvalidSignature(Pubkey),
+    #[error("Transaction is not signed by: {}", .0.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "))]
+    MissingSignatures(Vec<Pubkey>),
+    #[error("Signing error: {0}")]
+    Signer(#[from] SignerError),
+}
+
+/// Signature of one of the required signers, `None` when the signer has not signed yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedSignature {
+    /// Base58 encoded pubkey of the signer.
+    pub pubkey: String,
+    /// Base58 encoded signature.
+    pub signature: Option<String>,
+}
+
+/// Transaction in a form that can be passed to other signers and merged back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTransaction {
+    /// Base64 encoded serialized message, the blockhash (or durable nonce) is part of it.
+    pub message: String,
+    /// Signatures in the order of the required signers of the message.
+    pub signatures: Vec<ExportedSignature>,
+}
+
+impl ExportedTransaction {
+    pub fn from_transaction(transaction: &VersionedTransaction) -> Self {
+        let signatures = required_signers(&transaction.message)
+            .iter()
+            .zip(transaction.signatures.iter())
+            .map(|(pubkey, signature)| ExportedSignature {
+                pubkey: pubkey.to_string(),
+                signature: if *signature == Signature::default() {
+                    None
+                } else {
+                    Some(signature.to_string())
+                },
+            })
+            .collect();
+        Self {
+            message: anchor_lang::__private::base64::encode(transaction.message.serialize()),
+            signatures,
+        }
+    }
+
+    /// Decodes the transaction, only valid signatures of the required signers are accepted.
+    pub fn to_transaction(&self) -> Result<VersionedTransaction, OfflineTransactionError> {
+        let message_data = anchor_lang::__private::base64::decode(&self.message)
+            .map_err(|e| OfflineTransactionError::Decode(format!("message base64: {}", e)))?;
+        let message: VersionedMessage = bincode::deserialize(&message_data)
+            .map_err(|e| OfflineTransactionError::Decode(format!("message: {}", e)))?;
+        let signers = required_signers(&message);
+        let mut transaction = VersionedTransaction {
+            signatures: vec![Signature::default(); signers.len()],
+            message,
+        };
+        for exported_signature in &self.signatures {
+            let pubkey = Pubkey::from_str(&exported_signature.pubkey).map_err(|e| {
+                OfflineTransactionError::Decode(format!(
+                    "pubkey {}: {}",
+                    exported_signature.pubkey, e
+                ))
+            })?;
+            if let Some(signature) = &exported_signature.signature {
+                let signature = Signature::from_str(signature).map_err(|e| {
+                    OfflineTransactionError::Decode(format!("signature of {}: {}", pubkey, e))
+                })?;
+                set_signature(&mut transaction, &message_data, pubkey, signature)?;
+            }
+        }
+        Ok(transaction)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, OfflineTransactionError> {
+        serde_json::from_str(json).map_err(|e| OfflineTransactionError::Decode(e.to_string()))
+    }
+}
+
+fn required_signers(message: &VersionedMessage) -> Vec<Pubkey> {
+    message.static_account_keys()[0..message.header().num_required_signatures as usize].to_vec()
+}
+
+fn set_signature(
+    transaction: &mut VersionedTransaction,
+    message_data: &[u8],
+    pubkey: Pubkey,
+    signature: Signature,
+) -> Result<(), OfflineTransactionError> {
+    let position = required_signers(&transaction.message)
+        .iter()
+        .position(|signer| *signer == pubkey)
+        .ok_or(OfflineTransactionError::UnknownSigner(pubkey))?;
+    if !signature.verify(pubkey.as_ref(), message_data) {
+        return Err(OfflineTransactionError::InvalidSignature(pubkey));
+    }
+    transaction.signatures[position] = signature;
+    Ok(())
+}
+
+impl PreparedTransaction {
+    /// Exports the transaction with the signatures collected so far.
+    /// The transaction has to be (partially) signed with the final blockhash before the export.
+    pub fn export(&self) -> ExportedTransaction {
+        ExportedTransaction::from_transaction(&self.transaction)
+    }
+
+    /// Creates the transaction from an export, it can be signed only by [PreparedTransaction::sign_offline]
+    /// as its blockhash must not change.
+    pub fn import(exported: &ExportedTransaction) -> Result<Self, OfflineTransactionError> {
+        Ok(PreparedTransaction::new_no_signers(
+            exported.to_transaction()?,
+        ))
+    }
+
+    /// Adds the signature of the signer without changing the transaction message.
+    pub fn sign_offline(&mut self, signer: &dyn Signer) -> Result<(), OfflineTransactionError> {
+        let message_data = self.transaction.message.serialize();
+        let signature = signer.try_sign_message(&message_data)?;
+        set_signature(
+            &mut self.transaction,
+            &message_data,
+            signer.pubkey(),
+            signature,
+        )
+    }
+
+    /// Merges the signatures from the export of the same transaction message.
+    pub fn merge_signatures(
+        &mut self,
+        exported: &ExportedTransaction,
+    ) -> Result<(), OfflineTransactionError> {
+        let other = exported.to_transaction()?;
+        if other.message != self.transaction.message {
+            return Err(OfflineTransactionError::MessageMismatch);
+        }
+        for (position, signature) in other.signatures.into_iter().enumerate() {
+            if signature != Signature::default() {
+                self.transaction.signatures[position] = signature;
+            }
+        }
+        Ok(())
+    }
+
+    /// Required signers whose signature is not part of the transaction yet.
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        required_signers(&self.transaction.message)
+            .into_iter()
+            .zip(self.transaction.signatures.iter())
+            .filter(|(_, signature)| **signature == Signature::default())
+            .map(|(pubkey, _)| pubkey)
+            .collect()
+    }
+
+    pub fn check_signatures_complete(&self) -> Result<(), OfflineTransactionError> {
+        let missing_signers = self.missing_signers();
+        if missing_signers.is_empty() {
+            Ok(())
+        } else {
+            Err(OfflineTransactionError::MissingSignatures(missing_signers))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::transaction_builder::TransactionBuilder;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::signature::Keypair;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_export_merge_signatures() {
+        let fee_payer: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let offline_signer = Keypair::new();
+        let ledger_signer = Keypair::new();
+        let mut tx_builder = TransactionBuilder::limited(fee_payer.clone()).with_no_signers_check();
+        tx_builder
+            .add_instruction(Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![
+                    AccountMeta::new_readonly(offline_signer.pubkey(), true),
+                    AccountMeta::new_readonly(ledger_signer.pubkey(), true),
+                ],
+                data: vec![1, 2, 3],
+            })
+            .unwrap();
+        let mut prepared_transaction = tx_builder.build_one();
+        prepared_transaction.signers = vec![fee_payer];
+        prepared_transaction.partial_sign(Hash::new_unique());
+
+        let exported = prepared_transaction.export();
+        assert_eq!(exported.signatures.len(), 3);
+        let json = exported.to_json().unwrap();
+
+        let mut offline_transaction =
+            PreparedTransaction::import(&ExportedTransaction::from_json(&json).unwrap()).unwrap();
+        offline_transaction.sign_offline(&offline_signer).unwrap();
+        assert!(matches!(
+            offline_transaction.sign_offline(&Keypair::new()),
+            Err(OfflineTransactionError::UnknownSigner(_))
+        ));
+
+        prepared_transaction
+            .merge_signatures(&offline_transaction.export())
+            .unwrap();
+        match prepared_transaction.check_signatures_complete() {
+            Err(OfflineTransactionError::MissingSignatures(missing)) => {
+                assert_eq!(missing, vec![ledger_signer.pubkey()])
+            }
+            other => panic!("Expected missing signatures, got {:?}", other),
+        }
+
+        prepared_transaction.sign_offline(&ledger_signer).unwrap();
+        prepared_transaction.check_signatures_complete().unwrap();
+        assert!(prepared_transaction
+            .transaction
+            .verify_with_results()
+            .iter()
+            .all(|ok| *ok));
+    }
+}
@@ -1,61 +1,311 @@
+use crate::transactions::compute_budget::{ComputeBudgetConfig, ComputeUnitPrice};
+use crate::transactions::durable_nonce::DurableNonce;
+use crate::transactions::sender::TipConfig;
 use crate::transactions::signature_builder::SignatureBuilder;
+use crate::transactions::transaction_builder::{
+    compile_transaction, transaction_size, TransactionBuildError,
+};
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
 use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::VersionedMessage;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Keypair;
-use solana_sdk::signer::SignerError;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::VersionedTransaction;
 use std::sync::Arc;
 
+/// Parts the transaction is compiled from. Kept to rebuild the transaction
+/// when its compute budget is changed before sending.
+#[derive(Debug, Clone)]
+pub struct TransactionSource {
+    pub fee_payer: Pubkey,
+    pub instructions: Vec<Instruction>,
+    pub compute_budget: ComputeBudgetConfig,
+    pub address_lookup_tables: Vec<AddressLookupTableAccount>,
+    pub max_transaction_size: usize,
+    pub durable_nonce: Option<DurableNonce>,
+    pub tip: Option<TipConfig>,
+}
+
+impl TransactionSource {
+    pub fn compile(
+        &self,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedTransaction, TransactionBuildError> {
+        let instructions = transaction_instructions(
+            &self.fee_payer,
+            &self.durable_nonce,
+            &self.compute_budget,
+            &self.tip,
+            &self.instructions,
+        );
+        let transaction = compile_transaction(
+            &self.fee_payer,
+            &instructions,
+            &self.address_lookup_tables,
+            recent_blockhash,
+        )?;
+        if self.max_transaction_size > 0
+            && transaction_size(&transaction) > self.max_transaction_size
+        {
+            return Err(TransactionBuildError::TooBigTransaction);
+        }
+        Ok(transaction)
+    }
+}
+
+/// Instructions of the transaction in the order they have to be placed in the message,
+/// the nonce advance is required to be the first one, the tip transfer is the last one.
+pub(crate) fn transaction_instructions(
+    fee_payer: &Pubkey,
+    durable_nonce: &Option<DurableNonce>,
+    compute_budget: &ComputeBudgetConfig,
+    tip: &Option<TipConfig>,
+    instructions: &[Instruction],
+) -> Vec<Instruction> {
+    durable_nonce
+        .iter()
+        .map(|durable_nonce| durable_nonce.advance_nonce_instruction())
+        .chain(compute_budget.instructions())
+        .chain(instructions.iter().cloned())
+        .chain(tip.iter().map(|tip| tip.instruction(fee_payer)))
+        .collect()
+}
+
+pub fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for account in instructions
+        .iter()
+        .flat_map(|instruction| instruction.accounts.iter())
+        .filter(|account| account.is_writable)
+    {
+        if !accounts.contains(&account.pubkey) {
+            accounts.push(account.pubkey);
+        }
+    }
+    accounts
+}
+
 pub struct PreparedTransaction {
-    pub transaction: Transaction,
-    pub signers: Vec<Arc<Keypair>>,
+    pub transaction: VersionedTransaction,
+    pub signers: Vec<Arc<dyn Signer + Send + Sync>>,
+    pub source: Option<TransactionSource>,
+    /// Indexes of the builder instruction packs the transaction contains.
+    pub instruction_packs: Vec<usize>,
 }
 
 impl PreparedTransaction {
     pub fn new(
-        transaction: Transaction,
+        transaction: VersionedTransaction,
         signature_builder: &SignatureBuilder,
     ) -> Result<Self, Pubkey> {
         let signers = signature_builder.signers_for_transaction(&transaction)?;
         Ok(Self {
             transaction,
             signers,
+            source: None,
+            instruction_packs: vec![],
         })
     }
 
-    pub fn new_no_signers(transaction: Transaction) -> Self {
+    pub fn new_no_signers(transaction: VersionedTransaction) -> Self {
         Self {
             transaction,
             signers: vec![],
+            source: None,
+            instruction_packs: vec![],
         }
     }
 
-    pub fn sign(&mut self, recent_blockhash: Hash) -> Result<&Transaction, SignerError> {
-        self.transaction.try_sign(
-            &self
-                .signers
-                .iter()
-                .map(|arc| arc.as_ref())
-                .collect::<Vec<_>>(),
-            recent_blockhash,
-        )?;
+    pub fn with_source(mut self, source: TransactionSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_instruction_packs(mut self, instruction_packs: Vec<usize>) -> Self {
+        self.instruction_packs = instruction_packs;
+        self
+    }
+
+    pub fn compute_budget(&self) -> Option<&ComputeBudgetConfig> {
+        self.source.as_ref().map(|source| &source.compute_budget)
+    }
+
+    pub fn durable_nonce(&self) -> Option<&DurableNonce> {
+        self.source
+            .as_ref()
+            .and_then(|source| source.durable_nonce.as_ref())
+    }
+
+    /// Rebuilds the transaction to use the durable nonce instead of a recent blockhash.
+    /// The nonce authority signer has to be provided when it's not one of the transaction signers.
+    pub fn set_durable_nonce(
+        &mut self,
+        durable_nonce: DurableNonce,
+        nonce_authority: Option<Arc<dyn Signer + Send + Sync>>,
+    ) -> Result<(), TransactionBuildError> {
+        let source = self
+            .source
+            .as_mut()
+            .ok_or(TransactionBuildError::NotRebuildable)?;
+        let previous_durable_nonce = source.durable_nonce.replace(durable_nonce);
+        match source.compile(*self.transaction.message.recent_blockhash()) {
+            Ok(transaction) => {
+                self.transaction = transaction;
+                if let Some(nonce_authority) = nonce_authority {
+                    if !self
+                        .signers
+                        .iter()
+                        .any(|signer| signer.pubkey() == nonce_authority.pubkey())
+                    {
+                        self.signers.push(nonce_authority);
+                    }
+                }
+                Ok(())
+            }
+            Err(err) => {
+                source.durable_nonce = previous_durable_nonce;
+                Err(err)
+            }
+        }
+    }
+
+    pub fn tip(&self) -> Option<&TipConfig> {
+        self.source.as_ref().and_then(|source| source.tip.as_ref())
+    }
+
+    /// Rebuilds the transaction with the tip transfer, `None` removes the tip.
+    /// The transaction has to be signed again afterwards.
+    pub fn set_tip(&mut self, tip: Option<TipConfig>) -> Result<(), TransactionBuildError> {
+        let source = self
+            .source
+            .as_mut()
+            .ok_or(TransactionBuildError::NotRebuildable)?;
+        let previous_tip = std::mem::replace(&mut source.tip, tip);
+        match source.compile(*self.transaction.message.recent_blockhash()) {
+            Ok(transaction) => {
+                self.transaction = transaction;
+                Ok(())
+            }
+            Err(err) => {
+                source.tip = previous_tip;
+                Err(err)
+            }
+        }
+    }
+
+    /// Rebuilds the transaction from its source with the updated compute budget.
+    /// The transaction has to be signed again afterwards.
+    pub fn update_compute_budget<F>(&mut self, update: F) -> Result<(), TransactionBuildError>
+    where
+        F: FnOnce(&mut ComputeBudgetConfig),
+    {
+        let source = self
+            .source
+            .as_mut()
+            .ok_or(TransactionBuildError::NotRebuildable)?;
+        let mut compute_budget = source.compute_budget;
+        update(&mut compute_budget);
+        let previous_compute_budget = std::mem::replace(&mut source.compute_budget, compute_budget);
+        match source.compile(*self.transaction.message.recent_blockhash()) {
+            Ok(transaction) => {
+                self.transaction = transaction;
+                Ok(())
+            }
+            Err(err) => {
+                source.compute_budget = previous_compute_budget;
+                Err(err)
+            }
+        }
+    }
+
+    pub fn set_compute_unit_limit(
+        &mut self,
+        compute_unit_limit: u32,
+    ) -> Result<(), TransactionBuildError> {
+        self.update_compute_budget(|compute_budget| {
+            compute_budget.compute_unit_limit = Some(compute_unit_limit);
+            compute_budget.compute_unit_limit_estimation = None;
+        })
+    }
+
+    pub fn set_compute_unit_price(
+        &mut self,
+        compute_unit_price: u64,
+    ) -> Result<(), TransactionBuildError> {
+        self.update_compute_budget(|compute_budget| {
+            compute_budget.compute_unit_price = Some(ComputeUnitPrice::Fixed(compute_unit_price));
+        })
+    }
+
+    /// Accounts locked for write by the transaction, deduplicated in order of appearance.
+    pub fn writable_accounts(&self) -> Vec<Pubkey> {
+        match &self.source {
+            Some(source) => writable_accounts(&source.instructions),
+            None => {
+                let message = &self.transaction.message;
+                message
+                    .static_account_keys()
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| message.is_maybe_writable(*index))
+                    .map(|(_, key)| *key)
+                    .collect()
+            }
+        }
+    }
+
+    pub fn sign(&mut self, recent_blockhash: Hash) -> Result<&VersionedTransaction, SignerError> {
+        self.sign_with_blockhash(recent_blockhash, false)?;
         Ok(&self.transaction)
     }
 
-    pub fn partial_sign(&mut self, recent_blockhash: Hash) -> &Transaction {
-        self.transaction.partial_sign(
-            &self
-                .signers
-                .iter()
-                .map(|arc| arc.as_ref())
-                .collect::<Vec<_>>(),
-            recent_blockhash,
-        );
+    pub fn partial_sign(&mut self, recent_blockhash: Hash) -> &VersionedTransaction {
+        self.sign_with_blockhash(recent_blockhash, true)
+            .expect("partial_sign: signing transaction failed");
         &self.transaction
     }
 
-    pub fn into_signed(mut self, recent_blockhash: Hash) -> Result<Transaction, SignerError> {
+    pub fn into_signed(
+        mut self,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedTransaction, SignerError> {
         self.sign(recent_blockhash)?;
         Ok(self.transaction)
     }
+
+    fn sign_with_blockhash(
+        &mut self,
+        recent_blockhash: Hash,
+        is_partial: bool,
+    ) -> Result<(), SignerError> {
+        match &mut self.transaction.message {
+            VersionedMessage::Legacy(message) => message.recent_blockhash = recent_blockhash,
+            VersionedMessage::V0(message) => message.recent_blockhash = recent_blockhash,
+        }
+        let message_data = self.transaction.message.serialize();
+        let num_required_signatures =
+            self.transaction.message.header().num_required_signatures as usize;
+        let signer_keys =
+            self.transaction.message.static_account_keys()[0..num_required_signatures].to_vec();
+        // signatures still valid for the message are kept, interactive signers are not asked again
+        let previous_signatures = std::mem::replace(
+            &mut self.transaction.signatures,
+            vec![Signature::default(); num_required_signatures],
+        );
+        for (pos, key) in signer_keys.iter().enumerate() {
+            if let Some(signature) = previous_signatures.get(pos).filter(|signature| {
+                **signature != Signature::default() && signature.verify(key.as_ref(), &message_data)
+            }) {
+                self.transaction.signatures[pos] = *signature;
+            } else if let Some(signer) = self.signers.iter().find(|signer| signer.pubkey() == *key)
+            {
+                self.transaction.signatures[pos] = signer.try_sign_message(&message_data)?;
+            } else if !is_partial {
+                return Err(SignerError::NotEnoughSigners);
+            }
+        }
+        Ok(())
+    }
 }
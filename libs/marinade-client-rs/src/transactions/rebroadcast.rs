@@ -0,0 +1,209 @@
+use crate::transactions::durable_nonce::{durable_nonce_account, get_durable_nonce_blockhash};
+use log::{debug, info, warn};
+use solana_client::client_error::{ClientError as SolanaClientError, ClientErrorKind};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_request::RpcError::ForUser;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::TransactionStatus;
+use std::time::{Duration, Instant};
+
+pub(crate) const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Preflight config of the rebroadcast, the simulation of the already processed transaction fails.
+pub(crate) fn rebroadcast_config(
+    preflight_config: RpcSendTransactionConfig,
+) -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: true,
+        max_retries: Some(0),
+        ..preflight_config
+    }
+}
+
+/// Error of the expired transaction, the message is matched as a not found blockhash to permit the re-sign.
+pub(crate) fn expired_transaction_error(signature: &Signature) -> SolanaClientError {
+    ForUser(format!(
+        "unable to confirm transaction {}, the transaction expired",
+        signature
+    ))
+    .into()
+}
+
+/// Whether the error is the one of the expired transaction, see [expired_transaction_error].
+pub(crate) fn is_expired_transaction_error(err: &SolanaClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::RpcError(ForUser(message)) if message.ends_with("the transaction expired")
+    )
+}
+
+/// Sends the signed transaction and confirms it, see [confirm_transaction_with_rebroadcast].
+pub fn send_and_confirm_transaction_with_rebroadcast(
+    rpc_client: &RpcClient,
+    send_rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    preflight_config: RpcSendTransactionConfig,
+    last_valid_block_height: Option<u64>,
+    rebroadcast_interval: Option<Duration>,
+) -> Result<Signature, SolanaClientError> {
+    let signature = send_rpc_client.send_transaction_with_config(transaction, preflight_config)?;
+    info!(
+        "Transaction {} sent to {}",
+        signature,
+        send_rpc_client.url()
+    );
+    confirm_transaction_with_rebroadcast(
+        rpc_client,
+        send_rpc_client,
+        transaction,
+        preflight_config,
+        last_valid_block_height,
+        rebroadcast_interval,
+    )?;
+    Ok(signature)
+}
+
+/// Polls the signature status until the transaction is confirmed with the commitment of the `rpc_client`,
+/// the same signed transaction is sent again through the `send_rpc_client` every `rebroadcast_interval`.
+/// The transaction expires when it has no status after the block height passes the last valid block height,
+/// with no block height (durable nonce) after the nonce is advanced.
+/// The errors of polling are logged and the polling continues until the transaction expires.
+pub fn confirm_transaction_with_rebroadcast(
+    rpc_client: &RpcClient,
+    send_rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    preflight_config: RpcSendTransactionConfig,
+    last_valid_block_height: Option<u64>,
+    rebroadcast_interval: Option<Duration>,
+) -> Result<(), SolanaClientError> {
+    let signature = transaction.signatures[0];
+    let nonce_account = durable_nonce_account(transaction);
+    let started = Instant::now();
+    let mut last_broadcast = Instant::now();
+    let mut rebroadcasts = 0_u32;
+    loop {
+        std::thread::sleep(CONFIRMATION_POLL_INTERVAL);
+        let status = match get_signature_status(rpc_client, &signature) {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        if let Some(status) = status {
+            if let Some(err) = status.err {
+                return Err(err.into());
+            }
+            if status.satisfies_commitment(rpc_client.commitment()) {
+                info!(
+                    "Transaction {} confirmed at slot {} after {:?}",
+                    signature,
+                    status.slot,
+                    started.elapsed()
+                );
+                return Ok(());
+            }
+            debug!(
+                "Transaction {} processed at slot {}, awaiting {:?} commitment",
+                signature,
+                status.slot,
+                rpc_client.commitment().commitment
+            );
+            continue;
+        }
+
+        let is_expired = match (last_valid_block_height, nonce_account) {
+            (Some(last_valid_block_height), _) => match rpc_client.get_block_height() {
+                Ok(block_height) => {
+                    debug!(
+                        "Transaction {} not processed yet, block height {}, last valid block height {}",
+                        signature, block_height, last_valid_block_height
+                    );
+                    block_height > last_valid_block_height
+                }
+                Err(err) => {
+                    warn!(
+                        "Transaction {} block height cannot be loaded: {:?}",
+                        signature, err
+                    );
+                    false
+                }
+            },
+            (None, Some(nonce_account)) => {
+                is_nonce_advanced(rpc_client, transaction, &nonce_account)
+            }
+            (None, None) => false,
+        };
+        // the transaction may land in between, the status is checked once more
+        if is_expired && matches!(get_signature_status(rpc_client, &signature), Ok(None)) {
+            warn!(
+                "Transaction {} expired after {} rebroadcast(s)",
+                signature, rebroadcasts
+            );
+            return Err(expired_transaction_error(&signature));
+        }
+
+        if let Some(rebroadcast_interval) = rebroadcast_interval {
+            if last_broadcast.elapsed() >= rebroadcast_interval {
+                rebroadcasts += 1;
+                last_broadcast = Instant::now();
+                match send_rpc_client
+                    .send_transaction_with_config(transaction, rebroadcast_config(preflight_config))
+                {
+                    Ok(_) => debug!(
+                        "Transaction {} rebroadcast #{} to {}",
+                        signature,
+                        rebroadcasts,
+                        send_rpc_client.url()
+                    ),
+                    Err(err) => warn!(
+                        "Transaction {} rebroadcast #{} failed: {:?}",
+                        signature, rebroadcasts, err
+                    ),
+                }
+            }
+        }
+    }
+}
+
+fn get_signature_status(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<Option<TransactionStatus>, SolanaClientError> {
+    rpc_client
+        .get_signature_statuses(&[*signature])
+        .map(|response| response.value[0].clone())
+        .map_err(|err| {
+            warn!(
+                "Transaction {} status cannot be loaded: {:?}",
+                signature, err
+            );
+            err
+        })
+}
+
+/// The durable nonce transaction cannot be processed after the nonce stored in the nonce account changed.
+pub(crate) fn is_nonce_advanced(
+    rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    nonce_account: &Pubkey,
+) -> bool {
+    match get_durable_nonce_blockhash(nonce_account, rpc_client, rpc_client.commitment()) {
+        Ok(nonce) => {
+            debug!(
+                "Transaction {} not processed yet, nonce {}, transaction nonce {}",
+                transaction.signatures[0],
+                nonce,
+                transaction.message.recent_blockhash()
+            );
+            nonce != *transaction.message.recent_blockhash()
+        }
+        Err(err) => {
+            warn!(
+                "Transaction {} nonce cannot be loaded: {:?}",
+                transaction.signatures[0], err
+            );
+            false
+        }
+    }
+}
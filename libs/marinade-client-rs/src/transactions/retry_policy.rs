@@ -0,0 +1,440 @@
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use rand::Rng;
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::client_error::ClientErrorKind;
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+};
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::transaction::TransactionError;
+use std::time::{Duration, Instant};
+
+const HTTP_TOO_MANY_REQUESTS: u16 = 429;
+
+/// How an error of sending the transaction is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transient error (rate limiting, node behind, connection reset, account in use),
+    /// the same transaction may be sent again after a backoff.
+    Retryable,
+    /// The transaction expired, it has to be re-signed with a fresh blockhash before sending again.
+    RefreshState,
+    /// Sending again cannot help.
+    Fatal,
+}
+
+pub fn classify_transaction_error(transaction_error: &TransactionError) -> ErrorClass {
+    match transaction_error {
+        TransactionError::BlockhashNotFound => ErrorClass::RefreshState,
+        TransactionError::AccountInUse
+        | TransactionError::WouldExceedMaxBlockCostLimit
+        | TransactionError::WouldExceedMaxAccountCostLimit
+        | TransactionError::ClusterMaintenance => ErrorClass::Retryable,
+        _ => ErrorClass::Fatal,
+    }
+}
+
+/// Classifies the error raised before the RPC accepted the transaction, see [classify_confirm_error]
+/// for the errors raised after that.
+pub fn classify_error(client_error: &SolanaClientError) -> ErrorClass {
+    match client_error.kind() {
+        ClientErrorKind::Io(_) => ErrorClass::Retryable,
+        ClientErrorKind::Reqwest(reqwest_error) => {
+            let is_retryable_status = reqwest_error
+                .status()
+                .map(|status| status.as_u16() == HTTP_TOO_MANY_REQUESTS || status.is_server_error())
+                .unwrap_or(false);
+            if is_retryable_status || reqwest_error.is_timeout() || reqwest_error.is_connect() {
+                ErrorClass::Retryable
+            } else {
+                classify_message(&reqwest_error.to_string())
+            }
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data:
+                RpcResponseErrorData::SendTransactionPreflightFailure(RpcSimulateTransactionResult {
+                    err: Some(transaction_error),
+                    ..
+                }),
+            ..
+        }) => classify_transaction_error(transaction_error),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                || *code == JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED =>
+        {
+            ErrorClass::Retryable
+        }
+        ClientErrorKind::TransactionError(transaction_error) => {
+            classify_transaction_error(transaction_error)
+        }
+        _ => classify_message(&client_error.to_string()),
+    }
+}
+
+/// Classifies the error of sending the prepared transaction. The durable nonce transaction cannot be refreshed,
+/// the not found blockhash means the nonce was advanced.
+pub fn classify_send_error(
+    client_error: &SolanaClientError,
+    prepared_transaction: &PreparedTransaction,
+) -> ErrorClass {
+    match classify_error(client_error) {
+        ErrorClass::RefreshState if prepared_transaction.durable_nonce().is_some() => {
+            ErrorClass::Fatal
+        }
+        error_class => error_class,
+    }
+}
+
+/// Classifies the error of confirming the transaction the RPC accepted, the transaction may still land.
+/// It is re-signed only when it expired, i.e., it has no status after its last valid block height passed.
+/// The transient errors of polling the status permit to confirm the same signature again.
+pub fn classify_confirm_error(
+    client_error: &SolanaClientError,
+    prepared_transaction: &PreparedTransaction,
+) -> ErrorClass {
+    match client_error.kind() {
+        // the transaction landed and failed
+        ClientErrorKind::TransactionError(_) => ErrorClass::Fatal,
+        // unable to confirm transaction, the transaction expired
+        ClientErrorKind::RpcError(RpcError::ForUser(message))
+            if message
+                .to_lowercase()
+                .contains("unable to confirm transaction") =>
+        {
+            if prepared_transaction.durable_nonce().is_some() {
+                ErrorClass::Fatal
+            } else {
+                ErrorClass::RefreshState
+            }
+        }
+        _ => classify_error(client_error),
+    }
+}
+
+fn classify_message(message: &str) -> ErrorClass {
+    let message = message.to_lowercase();
+    if message.contains("connection reset") || message.contains("too many requests") {
+        ErrorClass::Retryable
+    } else {
+        ErrorClass::Fatal
+    }
+}
+
+/// Configures when and how many times the transaction is sent again after an error.
+/// The default policy does not retry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximal number of attempts to send the transaction, the first attempt included.
+    pub max_attempts: u32,
+    /// Maximal number of retries after [ErrorClass::Retryable] errors.
+    pub max_transient_retries: u32,
+    /// Maximal number of retries after [ErrorClass::RefreshState] errors.
+    pub max_refresh_retries: u32,
+    /// Backoff before the first retry after a transient error, it is multiplied for every next one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub backoff_multiplier: u32,
+    /// Fraction of the backoff (0.0 - 1.0) randomly added to or subtracted from it.
+    pub jitter: f64,
+    /// No retry is started after this time elapsed from the first attempt.
+    pub deadline: Option<Duration>,
+    /// When set, the same signed transaction is rebroadcast in this interval until it is confirmed or expires.
+    pub rebroadcast_interval: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            max_transient_retries: 0,
+            max_refresh_retries: 0,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            backoff_multiplier: 2,
+            jitter: 0.2,
+            deadline: None,
+            rebroadcast_interval: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn no_retry() -> Self {
+        Self::default()
+    }
+
+    /// Retries only the expired transactions, the behaviour of the `--blockhash-not-found-retries` argument.
+    pub fn blockhash_not_found_retries(retries: Option<u16>) -> Self {
+        let retries = retries.unwrap_or(0) as u32;
+        Self {
+            max_attempts: retries + 1,
+            max_refresh_retries: retries,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_max_transient_retries(mut self, max_transient_retries: u32) -> Self {
+        self.max_transient_retries = max_transient_retries;
+        self
+    }
+
+    pub fn with_max_refresh_retries(mut self, max_refresh_retries: u32) -> Self {
+        self.max_refresh_retries = max_refresh_retries;
+        self
+    }
+
+    pub fn with_backoff(
+        mut self,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+        backoff_multiplier: u32,
+    ) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self.backoff_multiplier = backoff_multiplier.max(1);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_rebroadcast_interval(mut self, rebroadcast_interval: Option<Duration>) -> Self {
+        self.rebroadcast_interval = rebroadcast_interval;
+        self
+    }
+
+    /// Backoff before the retry with no jitter applied, `retry` is counted from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let multiplier = self
+            .backoff_multiplier
+            .saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(multiplier)
+            .min(self.max_backoff)
+    }
+
+    fn backoff_with_jitter(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if self.jitter <= 0.0 {
+            return backoff;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - self.jitter..=1.0 + self.jitter);
+        backoff.mul_f64(factor)
+    }
+
+    pub fn start(&self) -> RetryState {
+        RetryState::new(*self)
+    }
+}
+
+/// Retries done so far for one transaction under the [RetryPolicy].
+#[derive(Debug, Clone)]
+pub struct RetryState {
+    policy: RetryPolicy,
+    started: Instant,
+    attempts: u32,
+    transient_retries: u32,
+    refresh_retries: u32,
+    confirm_retries: u32,
+}
+
+impl RetryState {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            started: Instant::now(),
+            attempts: 1,
+            transient_retries: 0,
+            refresh_retries: 0,
+            confirm_retries: 0,
+        }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Records the retry after the failed attempt and returns the time to wait before it,
+    /// `None` when the policy does not permit another attempt.
+    pub fn retry_after(&mut self, error_class: ErrorClass) -> Option<Duration> {
+        if self.attempts >= self.policy.max_attempts {
+            return None;
+        }
+        let delay = match error_class {
+            ErrorClass::Fatal => return None,
+            ErrorClass::Retryable => {
+                if self.transient_retries >= self.policy.max_transient_retries {
+                    return None;
+                }
+                self.policy.backoff_with_jitter(self.transient_retries + 1)
+            }
+            ErrorClass::RefreshState => {
+                if self.refresh_retries >= self.policy.max_refresh_retries {
+                    return None;
+                }
+                // the transaction is re-signed with a fresh blockhash, no need to wait
+                Duration::ZERO
+            }
+        };
+        if self.is_past_deadline(delay) {
+            return None;
+        }
+        match error_class {
+            ErrorClass::Retryable => self.transient_retries += 1,
+            ErrorClass::RefreshState => self.refresh_retries += 1,
+            ErrorClass::Fatal => {}
+        }
+        self.attempts += 1;
+        Some(delay)
+    }
+
+    /// Records the retry of awaiting the status of the already sent transaction after the [ErrorClass::Retryable]
+    /// error and returns the time to wait before it. The confirmation retries have their own limit
+    /// of [RetryPolicy::max_transient_retries], they are not counted as attempts to send the transaction.
+    pub fn confirm_retry_after(&mut self, error_class: ErrorClass) -> Option<Duration> {
+        if error_class != ErrorClass::Retryable
+            || self.confirm_retries >= self.policy.max_transient_retries
+        {
+            return None;
+        }
+        let delay = self.policy.backoff_with_jitter(self.confirm_retries + 1);
+        if self.is_past_deadline(delay) {
+            return None;
+        }
+        self.confirm_retries += 1;
+        Some(delay)
+    }
+
+    fn is_past_deadline(&self, delay: Duration) -> bool {
+        self.policy
+            .deadline
+            .map_or(false, |deadline| self.started.elapsed() + delay > deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_request::RpcError::ForUser;
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(
+            classify_error(&TransactionError::BlockhashNotFound.into()),
+            ErrorClass::RefreshState
+        );
+        assert_eq!(
+            classify_error(&TransactionError::AccountInUse.into()),
+            ErrorClass::Retryable
+        );
+        assert_eq!(
+            classify_error(&TransactionError::InsufficientFundsForFee.into()),
+            ErrorClass::Fatal
+        );
+        let expired: SolanaClientError =
+            ForUser("unable to confirm transaction. This can happen...".to_string()).into();
+        assert_eq!(classify_error(&expired), ErrorClass::Fatal);
+        let prepared_transaction = PreparedTransaction::new_no_signers(Default::default());
+        assert_eq!(
+            classify_confirm_error(&expired, &prepared_transaction),
+            ErrorClass::RefreshState
+        );
+        assert_eq!(
+            classify_confirm_error(
+                &TransactionError::AccountInUse.into(),
+                &prepared_transaction
+            ),
+            ErrorClass::Fatal
+        );
+        assert_eq!(
+            classify_error(
+                &RpcError::RpcResponseError {
+                    code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                    message: "Node is behind by 42 slots".to_string(),
+                    data: RpcResponseErrorData::Empty,
+                }
+                .into()
+            ),
+            ErrorClass::Retryable
+        );
+        assert_eq!(
+            classify_error(
+                &std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset").into()
+            ),
+            ErrorClass::Retryable
+        );
+    }
+
+    #[test]
+    fn test_retry_limits() {
+        let policy = RetryPolicy::default()
+            .with_max_attempts(4)
+            .with_max_transient_retries(2)
+            .with_max_refresh_retries(1)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(150), 2)
+            .with_jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(150));
+
+        let mut state = policy.start();
+        assert_eq!(state.retry_after(ErrorClass::Fatal), None);
+        assert_eq!(
+            state.retry_after(ErrorClass::Retryable),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            state.retry_after(ErrorClass::RefreshState),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(state.retry_after(ErrorClass::RefreshState), None);
+        assert_eq!(
+            state.retry_after(ErrorClass::Retryable),
+            Some(Duration::from_millis(150))
+        );
+        assert_eq!(state.attempts(), 4);
+        assert_eq!(state.retry_after(ErrorClass::Retryable), None);
+
+        let mut state = policy.start();
+        assert_eq!(
+            state.confirm_retry_after(ErrorClass::Retryable),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(state.confirm_retry_after(ErrorClass::RefreshState), None);
+        assert_eq!(
+            state.confirm_retry_after(ErrorClass::Retryable),
+            Some(Duration::from_millis(150))
+        );
+        assert_eq!(state.confirm_retry_after(ErrorClass::Retryable), None);
+        // the send attempts are not used up by the confirmation retries
+        assert_eq!(state.attempts(), 1);
+        assert_eq!(
+            state.retry_after(ErrorClass::Retryable),
+            Some(Duration::from_millis(100))
+        );
+
+        let mut state = RetryPolicy::blockhash_not_found_retries(Some(1)).start();
+        assert_eq!(state.retry_after(ErrorClass::Retryable), None);
+        assert_eq!(
+            state.retry_after(ErrorClass::RefreshState),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(state.retry_after(ErrorClass::RefreshState), None);
+    }
+}
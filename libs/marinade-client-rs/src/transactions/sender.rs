@@ -0,0 +1,60 @@
+use log::{debug, warn};
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::VersionedTransaction;
+
+/// Transfer of lamports from the fee payer to the tip account (e.g., Jito tip account)
+/// appended as the last instruction of every transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TipConfig {
+    pub tip_account: Pubkey,
+    pub lamports: u64,
+}
+
+impl TipConfig {
+    pub fn new(tip_account: Pubkey, lamports: u64) -> Self {
+        Self {
+            tip_account,
+            lamports,
+        }
+    }
+
+    pub fn instruction(&self, fee_payer: &Pubkey) -> Instruction {
+        system_instruction::transfer(fee_payer, &self.tip_account, self.lamports)
+    }
+}
+
+/// Sends the signed transaction through the sender RPC, when the sender rejects the transaction
+/// it is sent through the `rpc_client` instead.
+/// Returns the RPC client that accepted the transaction, the one to rebroadcast it through.
+pub fn send_transaction_with_sender<'a>(
+    rpc_client: &'a RpcClient,
+    sender_rpc_client: &'a RpcClient,
+    tx: &VersionedTransaction,
+    preflight_config: RpcSendTransactionConfig,
+) -> Result<&'a RpcClient, SolanaClientError> {
+    match sender_rpc_client.send_transaction_with_config(tx, preflight_config) {
+        Ok(signature) => {
+            debug!(
+                "Transaction {} sent through sender {}",
+                signature,
+                sender_rpc_client.url()
+            );
+            Ok(sender_rpc_client)
+        }
+        Err(err) => {
+            warn!(
+                "Sender {} rejected the transaction, sending through {}: {:?}",
+                sender_rpc_client.url(),
+                rpc_client.url(),
+                err
+            );
+            rpc_client.send_transaction_with_config(tx, preflight_config)?;
+            Ok(rpc_client)
+        }
+    }
+}
@@ -3,13 +3,16 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer, SignerError},
     signers::Signers,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
+};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::Arc,
 };
-use std::{collections::HashMap, sync::Arc};
 
-#[derive(Debug)]
 pub struct SignatureBuilder {
-    pub signers: HashMap<Pubkey, Arc<Keypair>>,
+    pub signers: HashMap<Pubkey, Arc<dyn Signer + Send + Sync>>,
     pub is_check_signers: bool,
 }
 
@@ -34,7 +37,7 @@ impl SignatureBuilder {
         }
     }
 
-    pub fn add_signer(&mut self, signer: Arc<Keypair>) -> Pubkey {
+    pub fn add_signer(&mut self, signer: Arc<dyn Signer + Send + Sync>) -> Pubkey {
         let pubkey = signer.pubkey();
         self.signers.insert(pubkey, signer);
         pubkey
@@ -51,19 +54,22 @@ impl SignatureBuilder {
         self.signers.contains_key(key)
     }
 
-    pub fn get_signer(&self, key: &Pubkey) -> Option<Arc<Keypair>> {
+    pub fn get_signer(&self, key: &Pubkey) -> Option<Arc<dyn Signer + Send + Sync>> {
         self.signers.get(key).cloned()
     }
 
-    pub fn into_signers(self) -> Vec<Arc<Keypair>> {
+    pub fn into_signers(self) -> Vec<Arc<dyn Signer + Send + Sync>> {
         self.signers.into_values().collect()
     }
 
-    pub fn sign_transaction(&self, transaction: &mut Transaction) -> Result<(), SignerError> {
-        let keys = transaction.message().account_keys
-            [0..transaction.message().header.num_required_signatures as usize]
+    pub fn sign_transaction(
+        &self,
+        transaction: &mut VersionedTransaction,
+    ) -> Result<(), SignerError> {
+        let keys = transaction.message.static_account_keys()
+            [0..transaction.message.header().num_required_signatures as usize]
             .to_vec();
-        let message = transaction.message_data();
+        let message = transaction.message.serialize();
         for (pos, key) in keys.into_iter().enumerate() {
             if let Some(keypair) = self.signers.get(&key) {
                 transaction.signatures[pos] = keypair.try_sign_message(&message)?;
@@ -85,40 +91,130 @@ impl SignatureBuilder {
 
     pub fn signers_for_transaction(
         &self,
-        transaction: &Transaction,
-    ) -> Result<Vec<Arc<Keypair>>, Pubkey> {
-        transaction.message().account_keys
-            [0..transaction.message().header.num_required_signatures as usize]
+        transaction: &VersionedTransaction,
+    ) -> Result<Vec<Arc<dyn Signer + Send + Sync>>, Pubkey> {
+        transaction.message.static_account_keys()
+            [0..transaction.message.header().num_required_signatures as usize]
             .iter()
             .map(|key| self.signers.get(key).cloned().ok_or(*key))
             .collect()
     }
+
+    pub fn is_interactive(&self) -> bool {
+        self.signers.values().any(|signer| signer.is_interactive())
+    }
+
+    /// Signers listed in the order of their pubkeys, the same order for every call of [Signers] methods.
+    fn ordered_signers(&self) -> Vec<&Arc<dyn Signer + Send + Sync>> {
+        let mut signers: Vec<(&Pubkey, &Arc<dyn Signer + Send + Sync>)> =
+            self.signers.iter().collect();
+        signers.sort_by_key(|(pubkey, _)| **pubkey);
+        signers.into_iter().map(|(_, signer)| signer).collect()
+    }
+}
+
+impl Debug for SignatureBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut signers: Vec<&Pubkey> = self.signers.keys().collect();
+        signers.sort();
+        f.debug_struct("SignatureBuilder")
+            .field("signers", &signers)
+            .field("is_check_signers", &self.is_check_signers)
+            .finish()
+    }
 }
 
+/// The transaction places the signatures by the signer keys of the message (e.g., [VersionedTransaction::try_new]),
+/// the signers are listed ordered by their pubkeys.
 impl Signers for SignatureBuilder {
     fn pubkeys(&self) -> Vec<Pubkey> {
-        self.signers.keys().cloned().collect()
+        self.ordered_signers()
+            .iter()
+            .map(|signer| signer.pubkey())
+            .collect()
     }
 
     fn try_pubkeys(&self) -> Result<Vec<Pubkey>, SignerError> {
-        Ok(self.pubkeys())
+        self.ordered_signers()
+            .iter()
+            .map(|signer| signer.try_pubkey())
+            .collect()
     }
 
     fn sign_message(&self, message: &[u8]) -> Vec<Signature> {
-        self.signers
-            .values()
+        self.ordered_signers()
+            .iter()
             .map(|signer| signer.sign_message(message))
             .collect()
     }
 
     fn try_sign_message(&self, message: &[u8]) -> Result<Vec<Signature>, SignerError> {
-        self.signers
-            .values()
+        self.ordered_signers()
+            .iter()
             .map(|signer| signer.try_sign_message(message))
             .collect()
     }
 
     fn is_interactive(&self) -> bool {
-        false
+        SignatureBuilder::is_interactive(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    #[test]
+    fn test_signers_ordered_by_message() {
+        let mut signature_builder = SignatureBuilder::new();
+        let signers: Vec<Pubkey> = (0..5).map(|_| signature_builder.new_signer()).collect();
+        let message = VersionedMessage::Legacy(Message::new_with_blockhash(
+            &[Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                signers
+                    .iter()
+                    .rev()
+                    .map(|signer| AccountMeta::new_readonly(*signer, true))
+                    .collect(),
+            )],
+            Some(&signers[2]),
+            &Hash::new_unique(),
+        ));
+        let transaction = VersionedTransaction::try_new(message, &signature_builder).unwrap();
+        assert_eq!(transaction.verify_with_results(), vec![true; signers.len()]);
+        let transaction_signers = signature_builder
+            .signers_for_transaction(&transaction)
+            .unwrap();
+        assert_eq!(
+            transaction_signers
+                .iter()
+                .map(|signer| signer.pubkey())
+                .collect::<Vec<Pubkey>>(),
+            transaction.message.static_account_keys()[0..signers.len()].to_vec()
+        );
+
+        let unknown_signer = Pubkey::new_unique();
+        let message = VersionedMessage::Legacy(Message::new(
+            &[Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![AccountMeta::new_readonly(unknown_signer, true)],
+            )],
+            Some(&signers[0]),
+        ));
+        assert_eq!(
+            signature_builder
+                .signers_for_transaction(&VersionedTransaction {
+                    signatures: vec![Signature::default(); 2],
+                    message,
+                })
+                .err(),
+            Some(unknown_signer)
+        );
+        assert!(format!("{:?}", signature_builder).contains(&signers[0].to_string()));
     }
 }
@@ -0,0 +1,341 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::transaction_builder::{TransactionBuildError, TransactionBuilder};
+use crate::transactions::transaction_instruction::TransactionInstruction;
+use anchor_client::RequestBuilder;
+use anchor_lang::prelude::AnchorSerialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{system_program, sysvar};
+use std::ops::Deref;
+
+// SPL Governance (v3) instructions are encoded here as the program crate is not a dependency,
+// the first byte of the data is the index of the `GovernanceInstruction` variant.
+const CREATE_PROPOSAL_INSTRUCTION: u8 = 6;
+const INSERT_TRANSACTION_INSTRUCTION: u8 = 9;
+const SIGN_OFF_PROPOSAL_INSTRUCTION: u8 = 12;
+
+const PROGRAM_AUTHORITY_SEED: &[u8] = b"governance";
+const REALM_CONFIG_SEED: &[u8] = b"realm-config";
+const PROPOSAL_DEPOSIT_SEED: &[u8] = b"proposal-deposit";
+
+// `VoteType::SingleChoice`
+const SINGLE_CHOICE_VOTE_TYPE: u8 = 0;
+const APPROVE_OPTION: &str = "Approve";
+
+#[derive(AnchorSerialize)]
+struct CreateProposalArgs {
+    name: String,
+    description_link: String,
+    vote_type: u8,
+    options: Vec<String>,
+    use_deny_option: bool,
+    proposal_seed: Pubkey,
+}
+
+#[derive(AnchorSerialize)]
+struct InsertTransactionArgs {
+    option_index: u8,
+    index: u16,
+    hold_up_time: u32,
+    // `InstructionData` of SPL Governance has the same layout
+    instructions: Vec<TransactionInstruction>,
+}
+
+/// Proposal of SPL Governance with a single "Approve" option, every instruction pack
+/// is inserted as one proposal transaction.
+#[derive(Debug, Clone)]
+pub struct GovernanceProposal {
+    pub governance_program: Pubkey,
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub token_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+    pub payer: Pubkey,
+    pub name: String,
+    pub description_link: String,
+    pub proposal_seed: Pubkey,
+    pub hold_up_time: u32,
+}
+
+impl GovernanceProposal {
+    /// The governance authority (token owner or its delegate) pays for the proposal accounts by default.
+    pub fn new(
+        governance_program: Pubkey,
+        realm: Pubkey,
+        governance: Pubkey,
+        governing_token_mint: Pubkey,
+        token_owner_record: Pubkey,
+        governance_authority: Pubkey,
+        name: String,
+    ) -> Self {
+        Self {
+            governance_program,
+            realm,
+            governance,
+            governing_token_mint,
+            token_owner_record,
+            governance_authority,
+            payer: governance_authority,
+            name,
+            description_link: String::new(),
+            proposal_seed: Keypair::new().pubkey(),
+            hold_up_time: 0,
+        }
+    }
+
+    pub fn with_payer(mut self, payer: Pubkey) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    pub fn with_description_link(mut self, description_link: String) -> Self {
+        self.description_link = description_link;
+        self
+    }
+
+    pub fn with_proposal_seed(mut self, proposal_seed: Pubkey) -> Self {
+        self.proposal_seed = proposal_seed;
+        self
+    }
+
+    pub fn with_hold_up_time(mut self, hold_up_time: u32) -> Self {
+        self.hold_up_time = hold_up_time;
+        self
+    }
+
+    pub fn proposal_address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                PROGRAM_AUTHORITY_SEED,
+                self.governance.as_ref(),
+                self.governing_token_mint.as_ref(),
+                self.proposal_seed.as_ref(),
+            ],
+            &self.governance_program,
+        )
+        .0
+    }
+
+    pub fn proposal_transaction_address(&self, index: u16) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                PROGRAM_AUTHORITY_SEED,
+                self.proposal_address().as_ref(),
+                // option index
+                &[0],
+                &index.to_le_bytes(),
+            ],
+            &self.governance_program,
+        )
+        .0
+    }
+
+    fn realm_config_address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[REALM_CONFIG_SEED, self.realm.as_ref()],
+            &self.governance_program,
+        )
+        .0
+    }
+
+    fn proposal_deposit_address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                PROPOSAL_DEPOSIT_SEED,
+                self.proposal_address().as_ref(),
+                self.payer.as_ref(),
+            ],
+            &self.governance_program,
+        )
+        .0
+    }
+
+    pub fn create_proposal_instruction(&self) -> Result<Instruction, MarinadeClientError> {
+        let args = CreateProposalArgs {
+            name: self.name.clone(),
+            description_link: self.description_link.clone(),
+            vote_type: SINGLE_CHOICE_VOTE_TYPE,
+            options: vec![APPROVE_OPTION.to_string()],
+            use_deny_option: true,
+            proposal_seed: self.proposal_seed,
+        };
+        Ok(Instruction {
+            program_id: self.governance_program,
+            accounts: vec![
+                AccountMeta::new_readonly(self.realm, false),
+                AccountMeta::new(self.proposal_address(), false),
+                AccountMeta::new(self.governance, false),
+                AccountMeta::new(self.token_owner_record, false),
+                AccountMeta::new_readonly(self.governing_token_mint, false),
+                AccountMeta::new_readonly(self.governance_authority, true),
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(self.realm_config_address(), false),
+                AccountMeta::new(self.proposal_deposit_address(), false),
+            ],
+            data: instruction_data(CREATE_PROPOSAL_INSTRUCTION, &args)?,
+        })
+    }
+
+    pub fn insert_transaction_instruction(
+        &self,
+        index: u16,
+        instructions: &[Instruction],
+    ) -> Result<Instruction, MarinadeClientError> {
+        let args = InsertTransactionArgs {
+            option_index: 0,
+            index,
+            hold_up_time: self.hold_up_time,
+            instructions: instructions
+                .iter()
+                .map(TransactionInstruction::from)
+                .collect(),
+        };
+        Ok(Instruction {
+            program_id: self.governance_program,
+            accounts: vec![
+                AccountMeta::new_readonly(self.governance, false),
+                AccountMeta::new(self.proposal_address(), false),
+                AccountMeta::new_readonly(self.token_owner_record, false),
+                AccountMeta::new_readonly(self.governance_authority, true),
+                AccountMeta::new(self.proposal_transaction_address(index), false),
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::rent::ID, false),
+            ],
+            data: instruction_data(INSERT_TRANSACTION_INSTRUCTION, &args)?,
+        })
+    }
+
+    /// Sign off by the proposal owner, the proposal is moved to the voting state.
+    pub fn sign_off_proposal_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.governance_program,
+            accounts: vec![
+                AccountMeta::new(self.realm, false),
+                AccountMeta::new(self.governance, false),
+                AccountMeta::new(self.proposal_address(), false),
+                AccountMeta::new_readonly(self.governance_authority, true),
+                AccountMeta::new_readonly(self.token_owner_record, false),
+            ],
+            data: vec![SIGN_OFF_PROPOSAL_INSTRUCTION],
+        }
+    }
+
+    /// Adds the instructions creating the proposal, inserting the instruction packs
+    /// as the proposal transactions and signing off the proposal. The builder has to know
+    /// the governance authority and the payer signers, its transactions are then sent by the executors.
+    pub fn add_to_builder(
+        &self,
+        transaction_builder: &mut TransactionBuilder,
+        instruction_packs: Vec<Vec<Instruction>>,
+    ) -> Result<Pubkey, MarinadeClientError> {
+        transaction_builder.add_instruction(self.create_proposal_instruction()?)?;
+        transaction_builder.finish_instruction_pack();
+        for (index, instructions) in instruction_packs
+            .iter()
+            .filter(|instructions| !instructions.is_empty())
+            .enumerate()
+        {
+            let index = u16::try_from(index).map_err(|_| {
+                TransactionBuildError::MessageCompile(format!(
+                    "Proposal supports max {} transactions",
+                    u16::MAX
+                ))
+            })?;
+            transaction_builder
+                .add_instruction(self.insert_transaction_instruction(index, instructions)?)?;
+            transaction_builder.finish_instruction_pack();
+        }
+        transaction_builder.add_instruction(self.sign_off_proposal_instruction())?;
+        transaction_builder.finish_instruction_pack();
+        Ok(self.proposal_address())
+    }
+
+    /// Proposal of the instruction packs of the source builder.
+    pub fn add_from_transaction_builder(
+        &self,
+        transaction_builder: &mut TransactionBuilder,
+        source: &TransactionBuilder,
+    ) -> Result<Pubkey, MarinadeClientError> {
+        self.add_to_builder(transaction_builder, source.instruction_packs())
+    }
+
+    /// Proposal with one transaction per request builder.
+    pub fn add_from_request_builders<'a, C: Deref<Target = impl Signer> + Clone>(
+        &self,
+        transaction_builder: &mut TransactionBuilder,
+        request_builders: impl IntoIterator<Item = RequestBuilder<'a, C>>,
+    ) -> Result<Pubkey, MarinadeClientError> {
+        let instruction_packs = request_builders
+            .into_iter()
+            .map(|request_builder| request_builder.instructions())
+            .collect::<Result<Vec<_>, _>>()?;
+        self.add_to_builder(transaction_builder, instruction_packs)
+    }
+}
+
+fn instruction_data(
+    instruction: u8,
+    args: &impl AnchorSerialize,
+) -> Result<Vec<u8>, MarinadeClientError> {
+    let mut data = vec![instruction];
+    data.extend(args.try_to_vec()?);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_add_proposal_to_builder() {
+        let authority: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let proposal = GovernanceProposal::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            authority.pubkey(),
+            "Update Marinade config".to_string(),
+        );
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+
+        let mut transaction_builder = TransactionBuilder::unlimited(authority);
+        let proposal_address = proposal
+            .add_to_builder(
+                &mut transaction_builder,
+                vec![vec![instruction.clone()], vec![], vec![instruction.clone()]],
+            )
+            .unwrap();
+        assert_eq!(proposal_address, proposal.proposal_address());
+
+        // create, two inserted transactions, sign off
+        let packs = transaction_builder.instruction_packs();
+        assert_eq!(packs.len(), 4);
+        assert_eq!(packs[0][0].data[0], CREATE_PROPOSAL_INSTRUCTION);
+        assert_eq!(packs[3][0].data, vec![SIGN_OFF_PROPOSAL_INSTRUCTION]);
+
+        let insert = &packs[2][0];
+        assert_eq!(insert.data[0], INSERT_TRANSACTION_INSTRUCTION);
+        // option index, transaction index 1, hold up time
+        assert_eq!(&insert.data[1..8], &[0, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            insert.accounts[4].pubkey,
+            proposal.proposal_transaction_address(1)
+        );
+        let inserted = TransactionInstruction::from(&instruction)
+            .try_to_vec()
+            .unwrap();
+        assert_eq!(&insert.data[8..12], &1u32.to_le_bytes());
+        assert_eq!(&insert.data[12..], &inserted[..]);
+    }
+}
@@ -1,15 +1,23 @@
-use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::error::MarinadeClientError;
+use crate::transactions::compute_budget::{ComputeBudgetConfig, ComputeUnitPrice};
+use crate::transactions::durable_nonce::DurableNonce;
+use crate::transactions::prepared_transaction::{
+    transaction_instructions, PreparedTransaction, TransactionSource,
+};
+use crate::transactions::sender::TipConfig;
 use crate::transactions::signature_builder::SignatureBuilder;
 use anchor_client::RequestBuilder;
-use anyhow::anyhow;
 use log::error;
 use once_cell::sync::OnceCell;
 use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    hash::Hash,
     instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
     packet::PACKET_DATA_SIZE,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
-    transaction::Transaction,
+    signature::{Signature, Signer},
+    transaction::VersionedTransaction,
 };
 use std::ops::Deref;
 use std::sync::Arc;
@@ -21,6 +29,46 @@ pub enum TransactionBuildError {
     UnknownSigner(Pubkey),
     #[error("Too big transaction")]
     TooBigTransaction,
+    #[error("Cannot compile transaction message: {0}")]
+    MessageCompile(String),
+    #[error("Transaction cannot be rebuilt, its source instructions are not known")]
+    NotRebuildable,
+}
+
+/// Compiles the instructions into an unsigned transaction. When address lookup tables
+/// are provided the v0 message format is used, otherwise the legacy one.
+pub fn compile_transaction(
+    fee_payer: &Pubkey,
+    instructions: &[Instruction],
+    address_lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedTransaction, TransactionBuildError> {
+    let message = if address_lookup_tables.is_empty() {
+        VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(fee_payer),
+            &recent_blockhash,
+        ))
+    } else {
+        VersionedMessage::V0(
+            v0::Message::try_compile(
+                fee_payer,
+                instructions,
+                address_lookup_tables,
+                recent_blockhash,
+            )
+            .map_err(|e| TransactionBuildError::MessageCompile(e.to_string()))?,
+        )
+    };
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    })
+}
+
+/// Size of the transaction in the wire format, signatures included.
+pub fn transaction_size(transaction: &VersionedTransaction) -> usize {
+    bincode::serialize(transaction).unwrap().len()
 }
 
 #[derive(Debug)]
@@ -31,10 +79,15 @@ pub struct TransactionBuilder {
     current_instruction_pack: OnceCell<Vec<Instruction>>,
     max_transaction_size: usize,
     is_check_signers: bool,
+    address_lookup_tables: Vec<AddressLookupTableAccount>,
+    compute_budget: ComputeBudgetConfig,
+    durable_nonce: Option<DurableNonce>,
+    tip: Option<TipConfig>,
+    built_instruction_packs: usize,
 }
 
 impl TransactionBuilder {
-    pub fn new(fee_payer: Arc<Keypair>, max_transaction_size: usize) -> Self {
+    pub fn new(fee_payer: Arc<dyn Signer + Send + Sync>, max_transaction_size: usize) -> Self {
         let mut signature_builder = SignatureBuilder::new();
         let fee_payer = signature_builder.add_signer(fee_payer);
         let builder = Self {
@@ -44,6 +97,11 @@ impl TransactionBuilder {
             current_instruction_pack: OnceCell::new(),
             max_transaction_size,
             is_check_signers: true,
+            address_lookup_tables: Vec::new(),
+            compute_budget: ComputeBudgetConfig::default(),
+            durable_nonce: None,
+            tip: None,
+            built_instruction_packs: 0,
         };
         builder.current_instruction_pack.set(Vec::new()).unwrap();
         builder
@@ -55,29 +113,98 @@ impl TransactionBuilder {
         self
     }
 
+    /// Transactions are built as v0 transactions resolving accounts through the provided tables.
+    pub fn with_address_lookup_tables(
+        mut self,
+        address_lookup_tables: Vec<AddressLookupTableAccount>,
+    ) -> Self {
+        self.address_lookup_tables = address_lookup_tables;
+        self
+    }
+
+    pub fn address_lookup_tables(&self) -> &[AddressLookupTableAccount] {
+        &self.address_lookup_tables
+    }
+
+    /// Compute budget instructions are prepended to every built transaction
+    /// and they are accounted into the transaction size.
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudgetConfig) -> Self {
+        self.compute_budget = compute_budget;
+        self
+    }
+
+    pub fn with_compute_unit_price(mut self, compute_unit_price: Option<u64>) -> Self {
+        self.compute_budget.compute_unit_price = compute_unit_price.map(ComputeUnitPrice::Fixed);
+        self
+    }
+
+    pub fn with_compute_unit_price_strategy(
+        mut self,
+        compute_unit_price: Option<ComputeUnitPrice>,
+    ) -> Self {
+        self.compute_budget.compute_unit_price = compute_unit_price;
+        self
+    }
+
+    pub fn with_compute_unit_limit(mut self, compute_unit_limit: Option<u32>) -> Self {
+        self.compute_budget.compute_unit_limit = compute_unit_limit;
+        self
+    }
+
+    pub fn compute_budget(&self) -> &ComputeBudgetConfig {
+        &self.compute_budget
+    }
+
+    /// Transactions use the nonce stored in the nonce account instead of a recent blockhash.
+    /// The advance nonce instruction is placed first in every built transaction.
+    pub fn with_durable_nonce(
+        mut self,
+        nonce_account: Pubkey,
+        nonce_authority: Arc<dyn Signer + Send + Sync>,
+    ) -> Self {
+        let nonce_authority = self.signature_builder.add_signer(nonce_authority);
+        self.durable_nonce = Some(DurableNonce::new(nonce_account, nonce_authority));
+        self
+    }
+
+    pub fn durable_nonce(&self) -> Option<&DurableNonce> {
+        self.durable_nonce.as_ref()
+    }
+
+    /// Tip transfer from the fee payer is appended to every built transaction
+    /// and it is accounted into the transaction size.
+    pub fn with_tip(mut self, tip: Option<TipConfig>) -> Self {
+        self.tip = tip;
+        self
+    }
+
+    pub fn tip(&self) -> Option<&TipConfig> {
+        self.tip.as_ref()
+    }
+
     pub fn fee_payer(&self) -> Pubkey {
         self.fee_payer
     }
 
-    pub fn get_signer(&self, key: &Pubkey) -> Option<Arc<Keypair>> {
+    pub fn get_signer(&self, key: &Pubkey) -> Option<Arc<dyn Signer + Send + Sync>> {
         self.signature_builder.get_signer(key)
     }
 
-    pub fn fee_payer_signer(&self) -> Arc<Keypair> {
+    pub fn fee_payer_signer(&self) -> Arc<dyn Signer + Send + Sync> {
         self.get_signer(&self.fee_payer()).unwrap()
     }
 
     ///constructor, limit size to a single transaction
-    pub fn limited(fee_payer: Arc<Keypair>) -> Self {
+    pub fn limited(fee_payer: Arc<dyn Signer + Send + Sync>) -> Self {
         Self::new(fee_payer, PACKET_DATA_SIZE)
     }
 
     ///constructor, no size limit, can be split in many transactions
-    pub fn unlimited(fee_payer: Arc<Keypair>) -> Self {
+    pub fn unlimited(fee_payer: Arc<dyn Signer + Send + Sync>) -> Self {
         Self::new(fee_payer, 0)
     }
 
-    pub fn add_signer(&mut self, signer: Arc<Keypair>) -> Pubkey {
+    pub fn add_signer(&mut self, signer: Arc<dyn Signer + Send + Sync>) -> Pubkey {
         self.signature_builder.add_signer(signer)
     }
 
@@ -85,7 +212,7 @@ impl TransactionBuilder {
         self.signature_builder.new_signer()
     }
 
-    pub fn add_signer_checked(&mut self, signer: &Arc<Keypair>) {
+    pub fn add_signer_checked(&mut self, signer: &Arc<dyn Signer + Send + Sync>) {
         if !self.signature_builder.contains_key(&signer.pubkey()) {
             self.add_signer(signer.clone());
         }
@@ -151,20 +278,20 @@ impl TransactionBuilder {
     pub fn add_instructions_from_builder<C: Deref<Target = impl Signer> + Clone>(
         &mut self,
         request_builder: RequestBuilder<C>,
-    ) -> anyhow::Result<&mut Self> {
+    ) -> Result<&mut Self, MarinadeClientError> {
         let instructions = request_builder.instructions().map_err(|e| {
             error!(
                 "add_instructions_from_builder: error building instructions: {:?}",
                 e
             );
-            anyhow!(e)
+            MarinadeClientError::from(e)
         })?;
         self.add_instructions(instructions)?;
         self.finish_instruction_pack();
         Ok(self)
     }
 
-    pub fn add_instructions<I>(&mut self, instructions: I) -> anyhow::Result<&mut Self>
+    pub fn add_instructions<I>(&mut self, instructions: I) -> Result<&mut Self, MarinadeClientError>
     where
         I: IntoIterator<Item = Instruction>,
     {
@@ -174,28 +301,117 @@ impl TransactionBuilder {
         Ok(self)
     }
 
-    pub fn add_instruction(&mut self, instruction: Instruction) -> anyhow::Result<&mut Self> {
+    pub fn add_instruction(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<&mut Self, MarinadeClientError> {
         self.check_signers(&instruction)?;
-        let current = self.current_instruction_pack.get_mut().unwrap();
-
-        current.push(instruction);
-        let transaction_candidate =
-            Transaction::new_with_payer(&current.to_vec(), Some(&self.fee_payer));
-        let tx_size_candidate = bincode::serialize(&transaction_candidate).unwrap().len();
-        if self.max_transaction_size > 0 && tx_size_candidate > self.max_transaction_size {
-            // Transaction is too big to add new instruction, remove the last one
-            current.pop();
-            let transaction_current = bincode::serialize(&transaction_candidate).unwrap().len();
-            let tx_size_current = bincode::serialize(&transaction_current).unwrap().len();
-            error!(
-                "add_instruction: too big transaction, tx size with added transaction: {}, original tx size: {},  max size: {}",
-                tx_size_candidate,  tx_size_current, self.max_transaction_size);
-            return Err(anyhow!(TransactionBuildError::TooBigTransaction));
+
+        if self.max_transaction_size == 0 && !self.address_lookup_tables.is_empty() {
+            // No size limit but the v0 message may still not compile (e.g., too many account indexes)
+            let mut candidate = self.current_instruction_pack.get().unwrap().clone();
+            candidate.push(instruction.clone());
+            if let Err(err) = self.compile(&candidate) {
+                error!("add_instruction: cannot compile transaction: {}", err);
+                return Err(err.into());
+            }
+        } else if self.max_transaction_size > 0 {
+            let current = self.current_instruction_pack.get().unwrap();
+            let mut candidate = current.clone();
+            candidate.push(instruction.clone());
+            match self
+                .compile(&candidate)
+                .map(|transaction| transaction_size(&transaction))
+            {
+                Ok(tx_size_candidate) if tx_size_candidate <= self.max_transaction_size => {}
+                Ok(tx_size_candidate) => {
+                    // Transaction is too big to add new instruction
+                    let tx_size_current = self
+                        .compile(current)
+                        .map(|transaction| transaction_size(&transaction))
+                        .unwrap_or_default();
+                    error!(
+                        "add_instruction: too big transaction, tx size with added transaction: {}, original tx size: {},  max size: {}",
+                        tx_size_candidate,  tx_size_current, self.max_transaction_size);
+                    return Err(TransactionBuildError::TooBigTransaction.into());
+                }
+                Err(err) => {
+                    error!("add_instruction: cannot compile transaction: {}", err);
+                    return Err(err.into());
+                }
+            }
         }
 
+        self.current_instruction_pack
+            .get_mut()
+            .unwrap()
+            .push(instruction);
         Ok(self)
     }
 
+    fn compile(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<VersionedTransaction, TransactionBuildError> {
+        let instructions = transaction_instructions(
+            &self.fee_payer,
+            &self.durable_nonce,
+            &self.compute_budget,
+            &self.tip,
+            instructions,
+        );
+        compile_transaction(
+            &self.fee_payer,
+            &instructions,
+            &self.address_lookup_tables,
+            Hash::default(),
+        )
+    }
+
+    /// The instructions compile and the transaction is within the size limit when there is one.
+    fn fits_transaction(&self, instructions: &[Instruction]) -> bool {
+        match self.compile(instructions) {
+            Ok(transaction) => {
+                self.max_transaction_size == 0
+                    || transaction_size(&transaction) <= self.max_transaction_size
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Prepares the transaction from the next `number_of_packs` instruction packs.
+    fn prepare(
+        &mut self,
+        instructions: Vec<Instruction>,
+        number_of_packs: usize,
+    ) -> PreparedTransaction {
+        let instruction_packs: Vec<usize> = (self.built_instruction_packs
+            ..self.built_instruction_packs + number_of_packs)
+            .collect();
+        self.built_instruction_packs += number_of_packs;
+        let source = TransactionSource {
+            fee_payer: self.fee_payer,
+            instructions,
+            compute_budget: self.compute_budget,
+            address_lookup_tables: self.address_lookup_tables.clone(),
+            max_transaction_size: self.max_transaction_size,
+            durable_nonce: self.durable_nonce,
+            tip: self.tip,
+        };
+        let transaction = source
+            .compile(Hash::default())
+            .expect("Instruction packs are combined only when they compile");
+        let prepared_transaction = if self.is_check_signers() {
+            PreparedTransaction::new(transaction, &self.signature_builder)
+                .expect("Signature keys must be checked when instruction added")
+        } else {
+            PreparedTransaction::new_no_signers(transaction)
+        };
+        prepared_transaction
+            .with_source(source)
+            .with_instruction_packs(instruction_packs)
+    }
+
     pub fn build_next(&mut self) -> Option<PreparedTransaction> {
         if !self.is_current_pack_empty() {
             self.finish_instruction_pack()
@@ -206,15 +422,7 @@ impl TransactionBuilder {
         if !self.instruction_packs.is_empty() {
             let instructions: Vec<Instruction> =
                 self.instruction_packs.remove(0).into_iter().collect();
-            let transaction = Transaction::new_with_payer(&instructions, Some(&self.fee_payer));
-            if self.is_check_signers() {
-                Some(
-                    PreparedTransaction::new(transaction, &self.signature_builder)
-                        .expect("Signature keys must be checked when instruction added"),
-                )
-            } else {
-                Some(PreparedTransaction::new_no_signers(transaction))
-            }
+            Some(self.prepare(instructions, 1))
         } else {
             None
         }
@@ -238,44 +446,35 @@ impl TransactionBuilder {
             return None;
         }
 
-        let transaction = if self.max_transaction_size == 0 {
-            let instructions: Vec<Instruction> =
-                self.instruction_packs.drain(..).flatten().collect();
-            Transaction::new_with_payer(&instructions, Some(&self.fee_payer))
-        } else {
-            // One pack must fit transaction anyways
-            let mut instructions: Vec<Instruction> =
-                self.instruction_packs.remove(0).into_iter().collect();
-            let mut transaction = Transaction::new_with_payer(&instructions, Some(&self.fee_payer));
-            while let Some(next_pack) = self.instruction_packs.get(0) {
-                let next_instructions: Vec<Instruction> = next_pack.to_vec();
-                // Try to add next pack
-                instructions.extend(next_instructions.into_iter());
-                let transaction_candidate =
-                    Transaction::new_with_payer(&instructions, Some(&self.fee_payer));
-
-                if bincode::serialize(&transaction_candidate).unwrap().len()
-                    <= self.max_transaction_size
-                {
-                    // Accept it
-                    transaction = transaction_candidate;
-                    // and move to the next pack
-                    self.instruction_packs.remove(0);
-                } else {
-                    // Stop trying
-                    break;
+        let mut number_of_packs = 1;
+        let instructions =
+            if self.max_transaction_size == 0 && self.address_lookup_tables.is_empty() {
+                // legacy message compiles from any instructions
+                number_of_packs = self.instruction_packs.len();
+                self.instruction_packs.drain(..).flatten().collect()
+            } else {
+                // One pack must fit transaction anyways
+                let mut instructions: Vec<Instruction> =
+                    self.instruction_packs.remove(0).into_iter().collect();
+                while let Some(next_pack) = self.instruction_packs.get(0) {
+                    let next_instructions: Vec<Instruction> = next_pack.to_vec();
+                    // Try to add next pack
+                    let mut instructions_candidate = instructions.clone();
+                    instructions_candidate.extend(next_instructions.into_iter());
+                    if self.fits_transaction(&instructions_candidate) {
+                        // Accept it
+                        instructions = instructions_candidate;
+                        // and move to the next pack
+                        self.instruction_packs.remove(0);
+                        number_of_packs += 1;
+                    } else {
+                        // Stop trying
+                        break;
+                    }
                 }
-            }
-            transaction
-        };
-        if self.is_check_signers() {
-            Some(
-                PreparedTransaction::new(transaction, &self.signature_builder)
-                    .expect("Signature keys must be checked when instruction added"),
-            )
-        } else {
-            Some(PreparedTransaction::new_no_signers(transaction))
-        }
+                instructions
+            };
+        Some(self.prepare(instructions, number_of_packs))
     }
 
     pub fn build_single_combined(&mut self) -> Option<PreparedTransaction> {
@@ -297,8 +496,20 @@ impl TransactionBuilder {
 
     pub fn fits_single_transaction(&self) -> bool {
         let instructions: Vec<Instruction> = self.instructions();
-        let transaction = Transaction::new_with_payer(&instructions, Some(&self.fee_payer));
-        bincode::serialize(&transaction).unwrap().len() <= self.max_transaction_size
+        self.compile(&instructions)
+            .map(|transaction| transaction_size(&transaction) <= self.max_transaction_size)
+            .unwrap_or(false)
+    }
+
+    /// Instruction packs not built yet, the current pack is included when not empty.
+    pub fn instruction_packs(&self) -> Vec<Vec<Instruction>> {
+        let mut instruction_packs = self.instruction_packs.clone();
+        if let Some(current_instructions) = self.current_instruction_pack.get() {
+            if !current_instructions.is_empty() {
+                instruction_packs.push(current_instructions.clone());
+            }
+        }
+        instruction_packs
     }
 
     pub fn instructions(&self) -> Vec<Instruction> {
@@ -338,12 +549,14 @@ impl<'a> Iterator for CombinedSequence<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transactions::compute_budget::ComputeUnitLimitEstimation;
+    use solana_sdk::compute_budget;
     use solana_sdk::instruction::AccountMeta;
     use solana_sdk::signature::Keypair;
 
     #[test]
     fn test_with_no_signers_check_preserves_fee_payer() {
-        let fee_payer: Arc<Keypair> = Arc::new(Keypair::new());
+        let fee_payer: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
         let fee_payer_pubkey = fee_payer.pubkey();
         let tx_builder = TransactionBuilder::limited(fee_payer).with_no_signers_check();
         assert_eq!(tx_builder.fee_payer(), fee_payer_pubkey);
@@ -353,8 +566,8 @@ mod tests {
 
     #[test]
     fn test_add_signer() {
-        let signer1: Arc<Keypair> = Arc::new(Keypair::new());
-        let signer2: Arc<Keypair> = Arc::new(Keypair::new());
+        let signer1: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let signer2: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
         let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()));
         tx_builder.add_signer_checked(&signer1);
         tx_builder.add_signer_checked(&signer2);
@@ -387,4 +600,295 @@ mod tests {
         };
         assert!(tx_builder.check_signers(&ix).is_ok());
     }
+
+    #[test]
+    fn test_address_lookup_tables_fit_more_instructions() {
+        let shared_accounts: Vec<Pubkey> = (0..13).map(|_| Pubkey::new_unique()).collect();
+        let program_id = Pubkey::new_unique();
+        let instruction = |stake_account: Pubkey| Instruction {
+            program_id,
+            accounts: shared_accounts
+                .iter()
+                .map(|pubkey| AccountMeta::new(*pubkey, false))
+                .chain(std::iter::once(AccountMeta::new(stake_account, false)))
+                .collect(),
+            data: vec![0; 16],
+        };
+        let count_fitting = |tx_builder: &mut TransactionBuilder| {
+            let mut count = 0;
+            while tx_builder
+                .add_instruction(instruction(Pubkey::new_unique()))
+                .is_ok()
+            {
+                count += 1;
+            }
+            count
+        };
+
+        let fee_payer: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let legacy_count = count_fitting(&mut TransactionBuilder::limited(fee_payer.clone()));
+        let mut v0_builder =
+            TransactionBuilder::limited(fee_payer).with_address_lookup_tables(vec![
+                AddressLookupTableAccount {
+                    key: Pubkey::new_unique(),
+                    addresses: shared_accounts.clone(),
+                },
+            ]);
+        let v0_count = count_fitting(&mut v0_builder);
+        assert!(v0_count > legacy_count);
+
+        let prepared_transaction = v0_builder.build_one();
+        assert!(matches!(
+            prepared_transaction.transaction.message,
+            VersionedMessage::V0(_)
+        ));
+        assert!(transaction_size(&prepared_transaction.transaction) <= PACKET_DATA_SIZE);
+    }
+
+    #[test]
+    fn test_unlimited_builder_splits_packs_not_compiling_together() {
+        let instruction = |accounts: usize| Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: (0..accounts)
+                .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+                .collect(),
+            data: vec![],
+        };
+        let mut tx_builder = TransactionBuilder::unlimited(Arc::new(Keypair::new()))
+            .with_address_lookup_tables(vec![AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![Pubkey::new_unique()],
+            }]);
+        // more than 256 account indexes cannot be compiled into the v0 message
+        assert!(matches!(
+            tx_builder.add_instruction(instruction(300)),
+            Err(MarinadeClientError::TransactionBuild(
+                TransactionBuildError::MessageCompile(_)
+            ))
+        ));
+        for _ in 0..3 {
+            tx_builder.add_instruction(instruction(100)).unwrap();
+            tx_builder.finish_instruction_pack();
+        }
+        let instruction_packs: Vec<Vec<usize>> = tx_builder
+            .sequence_combined()
+            .map(|prepared_transaction| prepared_transaction.instruction_packs)
+            .collect();
+        assert_eq!(instruction_packs, vec![vec![0, 1], vec![2]]);
+    }
+
+    #[test]
+    fn test_compute_budget_instructions_prepended() {
+        let ix_big = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![],
+            data: vec![0; 900],
+        };
+        let ix_small = Instruction {
+            data: vec![0; 140],
+            ..ix_big.clone()
+        };
+
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()));
+        tx_builder.add_instruction(ix_big.clone()).unwrap();
+        assert!(tx_builder.add_instruction(ix_small.clone()).is_ok());
+
+        // the compute budget instructions take space from the transaction size budget
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()))
+            .with_compute_unit_price(Some(1_000))
+            .with_compute_unit_limit(Some(300_000));
+        tx_builder.add_instruction(ix_big).unwrap();
+        assert!(tx_builder.add_instruction(ix_small).is_err());
+
+        let prepared_transaction = tx_builder.build_one();
+        let message = &prepared_transaction.transaction.message;
+        let program_ids: Vec<Pubkey> = message
+            .instructions()
+            .iter()
+            .map(|compiled| message.static_account_keys()[compiled.program_id_index as usize])
+            .collect();
+        assert_eq!(program_ids.len(), 3);
+        assert_eq!(program_ids[0], compute_budget::id());
+        assert_eq!(program_ids[1], compute_budget::id());
+    }
+
+    #[test]
+    fn test_set_compute_unit_limit_rebuilds_transaction() {
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()))
+            .with_compute_budget(ComputeBudgetConfig {
+                compute_unit_limit_estimation: Some(ComputeUnitLimitEstimation {
+                    margin_percent: 10,
+                }),
+                ..ComputeBudgetConfig::default()
+            });
+        tx_builder
+            .add_instruction(Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![0; 100],
+            })
+            .unwrap();
+        let mut prepared_transaction = tx_builder.build_one();
+        let size_with_placeholder = transaction_size(&prepared_transaction.transaction);
+
+        prepared_transaction.set_compute_unit_limit(55_000).unwrap();
+        assert_eq!(
+            transaction_size(&prepared_transaction.transaction),
+            size_with_placeholder
+        );
+        let compute_budget = prepared_transaction.compute_budget().unwrap();
+        assert_eq!(compute_budget.compute_unit_limit, Some(55_000));
+        assert!(compute_budget.compute_unit_limit_estimation.is_none());
+    }
+
+    #[test]
+    fn test_durable_nonce_advance_is_first_instruction() {
+        let nonce_account = Pubkey::new_unique();
+        let nonce_authority: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()))
+            .with_compute_unit_price(Some(1_000))
+            .with_durable_nonce(nonce_account, nonce_authority.clone());
+        tx_builder
+            .add_instruction(Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![0; 100],
+            })
+            .unwrap();
+        let mut prepared_transaction = tx_builder.build_one();
+        assert_eq!(
+            prepared_transaction.durable_nonce(),
+            Some(&DurableNonce::new(nonce_account, nonce_authority.pubkey()))
+        );
+
+        let message = &prepared_transaction.transaction.message;
+        let first_instruction = &message.instructions()[0];
+        assert_eq!(
+            message.static_account_keys()[first_instruction.program_id_index as usize],
+            solana_sdk::system_program::id()
+        );
+        assert_eq!(
+            message.static_account_keys()[first_instruction.accounts[0] as usize],
+            nonce_account
+        );
+
+        // the nonce authority signs together with the fee payer
+        let nonce = Hash::new_unique();
+        let transaction = prepared_transaction.sign(nonce).unwrap();
+        assert_eq!(transaction.signatures.len(), 2);
+        assert_eq!(*transaction.message.recent_blockhash(), nonce);
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[test]
+    fn test_tip_is_last_instruction() {
+        let tip_account = Pubkey::new_unique();
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()))
+            .with_compute_unit_price(Some(1_000))
+            .with_tip(Some(TipConfig::new(tip_account, 10_000)));
+        tx_builder
+            .add_instruction(Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![0; 100],
+            })
+            .unwrap();
+        let mut prepared_transaction = tx_builder.build_one();
+        let message = &prepared_transaction.transaction.message;
+        assert_eq!(message.instructions().len(), 3);
+        let last_instruction = message.instructions().last().unwrap();
+        assert_eq!(
+            message.static_account_keys()[last_instruction.program_id_index as usize],
+            solana_sdk::system_program::id()
+        );
+        assert_eq!(
+            message.static_account_keys()[last_instruction.accounts[1] as usize],
+            tip_account
+        );
+
+        prepared_transaction.set_tip(None).unwrap();
+        assert_eq!(
+            prepared_transaction
+                .transaction
+                .message
+                .instructions()
+                .len(),
+            2
+        );
+    }
+
+    struct InteractiveSigner {
+        keypair: Keypair,
+        sign_count: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Signer for InteractiveSigner {
+        fn try_pubkey(&self) -> Result<Pubkey, solana_sdk::signer::SignerError> {
+            Ok(self.keypair.pubkey())
+        }
+
+        fn try_sign_message(
+            &self,
+            message: &[u8],
+        ) -> Result<Signature, solana_sdk::signer::SignerError> {
+            self.sign_count
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.keypair.try_sign_message(message)
+        }
+
+        fn is_interactive(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_interactive_signer_asked_once_per_message() {
+        let signer = Arc::new(InteractiveSigner {
+            keypair: Keypair::new(),
+            sign_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let mut tx_builder = TransactionBuilder::limited(signer.clone());
+        assert!(tx_builder.signature_builder.is_interactive());
+        tx_builder
+            .add_instruction(Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![AccountMeta::new_readonly(signer.pubkey(), true)],
+                data: vec![],
+            })
+            .unwrap();
+        let mut prepared_transaction = tx_builder.build_one();
+        let blockhash = Hash::new_unique();
+        prepared_transaction.partial_sign(blockhash);
+        prepared_transaction.sign(blockhash).unwrap();
+        assert_eq!(
+            signer.sign_count.load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+
+        prepared_transaction.sign(Hash::new_unique()).unwrap();
+        assert_eq!(
+            signer.sign_count.load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+    }
+
+    #[test]
+    fn test_combined_transactions_report_instruction_packs() {
+        let mut tx_builder = TransactionBuilder::limited(Arc::new(Keypair::new()));
+        for _ in 0..5 {
+            tx_builder
+                .add_instruction(Instruction {
+                    program_id: Pubkey::new_unique(),
+                    accounts: vec![],
+                    data: vec![0; 400],
+                })
+                .unwrap();
+            tx_builder.finish_instruction_pack();
+        }
+        let instruction_packs: Vec<Vec<usize>> = tx_builder
+            .sequence_combined()
+            .map(|prepared_transaction| prepared_transaction.instruction_packs)
+            .collect();
+        assert_eq!(instruction_packs, vec![vec![0, 1], vec![2, 3], vec![4]]);
+    }
 }
@@ -0,0 +1,583 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::Discriminator;
+use marinade_finance::error::MarinadeError;
+use marinade_finance::instruction as marinade_finance_instruction;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError as SolanaClientError;
+use solana_client::client_error::ClientErrorKind;
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::transaction::TransactionError;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+const MARINADE_INSTRUCTIONS: [([u8; 8], &str); 27] = [
+    (
+        marinade_finance_instruction::Initialize::DISCRIMINATOR,
+        "Initialize",
+    ),
+    (
+        marinade_finance_instruction::ChangeAuthority::DISCRIMINATOR,
+        "ChangeAuthority",
+    ),
+    (
+        marinade_finance_instruction::AddValidator::DISCRIMINATOR,
+        "AddValidator",
+    ),
+    (
+        marinade_finance_instruction::RemoveValidator::DISCRIMINATOR,
+        "RemoveValidator",
+    ),
+    (
+        marinade_finance_instruction::SetValidatorScore::DISCRIMINATOR,
+        "SetValidatorScore",
+    ),
+    (
+        marinade_finance_instruction::ConfigValidatorSystem::DISCRIMINATOR,
+        "ConfigValidatorSystem",
+    ),
+    (
+        marinade_finance_instruction::Deposit::DISCRIMINATOR,
+        "Deposit",
+    ),
+    (
+        marinade_finance_instruction::DepositStakeAccount::DISCRIMINATOR,
+        "DepositStakeAccount",
+    ),
+    (
+        marinade_finance_instruction::LiquidUnstake::DISCRIMINATOR,
+        "LiquidUnstake",
+    ),
+    (
+        marinade_finance_instruction::AddLiquidity::DISCRIMINATOR,
+        "AddLiquidity",
+    ),
+    (
+        marinade_finance_instruction::RemoveLiquidity::DISCRIMINATOR,
+        "RemoveLiquidity",
+    ),
+    (
+        marinade_finance_instruction::ConfigLp::DISCRIMINATOR,
+        "ConfigLp",
+    ),
+    (
+        marinade_finance_instruction::ConfigMarinade::DISCRIMINATOR,
+        "ConfigMarinade",
+    ),
+    (
+        marinade_finance_instruction::OrderUnstake::DISCRIMINATOR,
+        "OrderUnstake",
+    ),
+    (marinade_finance_instruction::Claim::DISCRIMINATOR, "Claim"),
+    (
+        marinade_finance_instruction::StakeReserve::DISCRIMINATOR,
+        "StakeReserve",
+    ),
+    (
+        marinade_finance_instruction::UpdateActive::DISCRIMINATOR,
+        "UpdateActive",
+    ),
+    (
+        marinade_finance_instruction::UpdateDeactivated::DISCRIMINATOR,
+        "UpdateDeactivated",
+    ),
+    (
+        marinade_finance_instruction::DeactivateStake::DISCRIMINATOR,
+        "DeactivateStake",
+    ),
+    (
+        marinade_finance_instruction::EmergencyUnstake::DISCRIMINATOR,
+        "EmergencyUnstake",
+    ),
+    (
+        marinade_finance_instruction::PartialUnstake::DISCRIMINATOR,
+        "PartialUnstake",
+    ),
+    (
+        marinade_finance_instruction::MergeStakes::DISCRIMINATOR,
+        "MergeStakes",
+    ),
+    (
+        marinade_finance_instruction::CreateCanonicalStake::DISCRIMINATOR,
+        "CreateCanonicalStake",
+    ),
+    (marinade_finance_instruction::Pause::DISCRIMINATOR, "Pause"),
+    (
+        marinade_finance_instruction::Resume::DISCRIMINATOR,
+        "Resume",
+    ),
+    (
+        marinade_finance_instruction::WithdrawStakeAccount::DISCRIMINATOR,
+        "WithdrawStakeAccount",
+    ),
+    (
+        marinade_finance_instruction::FinalizeDelinquentUpgrade::DISCRIMINATOR,
+        "FinalizeDelinquentUpgrade",
+    ),
+];
+
+/// Errors of the Marinade program, the error code is the variant number offset by [ERROR_CODE_OFFSET].
+const MARINADE_ERRORS: [MarinadeError; 87] = [
+    MarinadeError::WrongReserveOwner,
+    MarinadeError::NonEmptyReserveData,
+    MarinadeError::InvalidInitialReserveLamports,
+    MarinadeError::ZeroValidatorChunkSize,
+    MarinadeError::TooBigValidatorChunkSize,
+    MarinadeError::ZeroCreditChunkSize,
+    MarinadeError::TooBigCreditChunkSize,
+    MarinadeError::TooLowCreditFee,
+    MarinadeError::InvalidMintAuthority,
+    MarinadeError::MintHasInitialSupply,
+    MarinadeError::InvalidOwnerFeeState,
+    MarinadeError::InvalidProgramId,
+    MarinadeError::UnexpectedAccount,
+    MarinadeError::CalculationFailure,
+    MarinadeError::StakeAccountWithLockup,
+    MarinadeError::MinStakeIsTooLow,
+    MarinadeError::LpMaxFeeIsTooHigh,
+    MarinadeError::BasisPointsOverflow,
+    MarinadeError::LpFeesAreWrongWayRound,
+    MarinadeError::LiquidityTargetTooLow,
+    MarinadeError::TicketNotDue,
+    MarinadeError::TicketNotReady,
+    MarinadeError::WrongBeneficiary,
+    MarinadeError::StakeAccountNotUpdatedYet,
+    MarinadeError::StakeNotDelegated,
+    MarinadeError::StakeAccountIsEmergencyUnstaking,
+    MarinadeError::InsufficientLiquidity,
+    MarinadeError::NotUsed6027,
+    MarinadeError::InvalidAdminAuthority,
+    MarinadeError::InvalidValidatorManager,
+    MarinadeError::InvalidStakeListDiscriminator,
+    MarinadeError::InvalidValidatorListDiscriminator,
+    MarinadeError::TreasuryCutIsTooHigh,
+    MarinadeError::RewardsFeeIsTooHigh,
+    MarinadeError::StakingIsCapped,
+    MarinadeError::LiquidityIsCapped,
+    MarinadeError::UpdateWindowIsTooLow,
+    MarinadeError::MinWithdrawIsTooHigh,
+    MarinadeError::WithdrawAmountIsTooLow,
+    MarinadeError::DepositAmountIsTooLow,
+    MarinadeError::NotEnoughUserFunds,
+    MarinadeError::WrongTokenOwnerOrDelegate,
+    MarinadeError::TooEarlyForStakeDelta,
+    MarinadeError::RequiredDelegatedStake,
+    MarinadeError::RequiredActiveStake,
+    MarinadeError::RequiredDeactivatingStake,
+    MarinadeError::DepositingNotActivatedStake,
+    MarinadeError::TooLowDelegationInDepositingStake,
+    MarinadeError::WrongStakeBalance,
+    MarinadeError::WrongValidatorAccountOrIndex,
+    MarinadeError::WrongStakeAccountOrIndex,
+    MarinadeError::UnstakingOnPositiveDelta,
+    MarinadeError::StakingOnNegativeDelta,
+    MarinadeError::MovingStakeIsCapped,
+    MarinadeError::StakeMustBeUninitialized,
+    MarinadeError::DestinationStakeMustBeDelegated,
+    MarinadeError::DestinationStakeMustNotBeDeactivating,
+    MarinadeError::DestinationStakeMustBeUpdated,
+    MarinadeError::InvalidDestinationStakeDelegation,
+    MarinadeError::SourceStakeMustBeDelegated,
+    MarinadeError::SourceStakeMustNotBeDeactivating,
+    MarinadeError::SourceStakeMustBeUpdated,
+    MarinadeError::InvalidSourceStakeDelegation,
+    MarinadeError::InvalidDelayedUnstakeTicket,
+    MarinadeError::ReusingDelayedUnstakeTicket,
+    MarinadeError::EmergencyUnstakingFromNonZeroScoredValidator,
+    MarinadeError::WrongValidatorDuplicationFlag,
+    MarinadeError::RedepositingMarinadeStake,
+    MarinadeError::RemovingValidatorWithBalance,
+    MarinadeError::RedelegateOverTarget,
+    MarinadeError::SourceAndDestValidatorsAreTheSame,
+    MarinadeError::UnregisteredMsolMinted,
+    MarinadeError::UnregisteredLPMinted,
+    MarinadeError::ListIndexOutOfBounds,
+    MarinadeError::ListOverflow,
+    MarinadeError::AlreadyPaused,
+    MarinadeError::NotPaused,
+    MarinadeError::ProgramIsPaused,
+    MarinadeError::InvalidPauseAuthority,
+    MarinadeError::SelectedStakeAccountHasNotEnoughFunds,
+    MarinadeError::BasisPointCentsOverflow,
+    MarinadeError::WithdrawStakeAccountIsNotEnabled,
+    MarinadeError::WithdrawStakeAccountFeeIsTooHigh,
+    MarinadeError::DelayedUnstakeFeeIsTooHigh,
+    MarinadeError::WithdrawStakeLamportsIsTooLow,
+    MarinadeError::StakeAccountRemainderTooLow,
+    MarinadeError::ShrinkingListWithDeletingContents,
+];
+
+/// Name of the Marinade instruction by the discriminator of the instruction data.
+pub fn marinade_instruction_name(data: &[u8]) -> Option<&'static str> {
+    let discriminator = data.get(..8)?;
+    MARINADE_INSTRUCTIONS
+        .iter()
+        .find(|(instruction_discriminator, _)| instruction_discriminator == discriminator)
+        .map(|(_, name)| *name)
+}
+
+/// Marinade program error of the custom program error code.
+pub fn marinade_error(error_code: u32) -> Option<MarinadeError> {
+    MARINADE_ERRORS
+        .iter()
+        .find(|error| u32::from(**error) == error_code)
+        .copied()
+}
+
+/// Typed description of the failed transaction, the program error is taken from the `AnchorError`
+/// program log line that carries the error variant name of the program (e.g., of `marinade_finance::error`)
+/// or of the Anchor framework. Without the logs the custom error code of the Marinade program is decoded,
+/// see [TransactionDiagnostic::with_message].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionDiagnostic {
+    pub error: String,
+    pub instruction_index: Option<u8>,
+    pub program_id: Option<String>,
+    pub instruction_name: Option<String>,
+    pub error_code: Option<u32>,
+    pub error_name: Option<String>,
+    pub error_message: Option<String>,
+    /// Account that the Anchor account constraint failed for.
+    pub error_account: Option<String>,
+    /// Program logs of the failed instruction, all the logs when the instruction is not known.
+    pub logs: Vec<String>,
+}
+
+impl TransactionDiagnostic {
+    pub fn new(transaction_error: &TransactionError, logs: &[String]) -> Self {
+        let (instruction_index, error_code) = match transaction_error {
+            TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+                (Some(*index), Some(*code))
+            }
+            TransactionError::InstructionError(index, _) => (Some(*index), None),
+            _ => (None, None),
+        };
+        let instruction_logs = match instruction_index {
+            Some(index) => instruction_logs(logs, index as usize),
+            None => logs.to_vec(),
+        };
+        let mut diagnostic = Self {
+            error: transaction_error.to_string(),
+            instruction_index,
+            program_id: instruction_logs
+                .first()
+                .and_then(|line| parse_invoke(line))
+                .map(|(program_id, _)| program_id.to_string()),
+            instruction_name: top_level_instruction_name(&instruction_logs),
+            error_code,
+            logs: instruction_logs,
+            ..Self::default()
+        };
+        if let Some(anchor_error) = diagnostic
+            .logs
+            .iter()
+            .find_map(|line| AnchorErrorLog::parse(line))
+        {
+            diagnostic.error_code = diagnostic.error_code.or(Some(anchor_error.number));
+            diagnostic.error_name = Some(anchor_error.code);
+            diagnostic.error_message = Some(anchor_error.message);
+            diagnostic.error_account = anchor_error.account;
+        } else if diagnostic.program_id == Some(marinade_finance::ID.to_string()) {
+            diagnostic.set_marinade_error();
+        }
+        diagnostic
+    }
+
+    /// Diagnostic of the failed simulation, `None` when the simulation succeeded.
+    pub fn from_simulation(simulation_result: &RpcSimulateTransactionResult) -> Option<Self> {
+        simulation_result
+            .err
+            .as_ref()
+            .map(|err| Self::new(err, simulation_result.logs.as_deref().unwrap_or_default()))
+    }
+
+    /// Diagnostic of the client error that carries a transaction error, the logs are available
+    /// only when the preflight simulation failed.
+    pub fn from_client_error(client_error: &SolanaClientError) -> Option<Self> {
+        match client_error.kind() {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation_result),
+                ..
+            }) => Self::from_simulation(simulation_result),
+            ClientErrorKind::TransactionError(err) => Some(Self::new(err, &[])),
+            _ => None,
+        }
+    }
+
+    /// Fills the program, the Marinade instruction name and the Marinade program error
+    /// from the transaction message when they are not known from the logs.
+    pub fn with_message(mut self, message: &VersionedMessage) -> Self {
+        let instruction = match self
+            .instruction_index
+            .and_then(|index| message.instructions().get(index as usize))
+        {
+            Some(instruction) => instruction,
+            None => return self,
+        };
+        let program_id = message
+            .static_account_keys()
+            .get(instruction.program_id_index as usize);
+        if self.program_id.is_none() {
+            self.program_id = program_id.map(|program_id| program_id.to_string());
+        }
+        if self.instruction_name.is_none() && program_id == Some(&marinade_finance::ID) {
+            self.instruction_name =
+                marinade_instruction_name(&instruction.data).map(|name| name.to_string());
+        }
+        if self.error_name.is_none() && program_id == Some(&marinade_finance::ID) {
+            self.set_marinade_error();
+        }
+        self
+    }
+
+    /// The custom error code of the Marinade program instruction is the Marinade error.
+    fn set_marinade_error(&mut self) {
+        if let Some(error) = self.error_code.and_then(marinade_error) {
+            self.error_name = Some(error.name());
+            self.error_message = Some(error.to_string());
+        }
+    }
+
+    /// The error is defined by the Anchor framework (e.g., account constraint), not by the program.
+    pub fn is_anchor_framework_error(&self) -> bool {
+        matches!(self.error_code, Some(code) if code < ERROR_CODE_OFFSET)
+    }
+}
+
+impl Display for TransactionDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(index) = self.instruction_index {
+            write!(f, "instruction #{}", index)?;
+            if let Some(instruction_name) = &self.instruction_name {
+                write!(f, " {}", instruction_name)?;
+            }
+            if let Some(program_id) = &self.program_id {
+                write!(f, " of program {}", program_id)?;
+            }
+            write!(f, ": ")?;
+        }
+        match (&self.error_name, &self.error_code) {
+            (Some(error_name), Some(error_code)) => {
+                write!(f, "{} ({})", error_name, error_code)?;
+                if let Some(error_message) = &self.error_message {
+                    write!(f, " {}", error_message)?;
+                }
+                if let Some(error_account) = &self.error_account {
+                    write!(f, " [account: {}]", error_account)?;
+                }
+                write!(f, "; {}", self.error)
+            }
+            _ => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// Error of the executors for the failed transaction.
+#[derive(Debug, Clone, Error)]
+pub enum TransactionFailure {
+    #[error("Transaction simulation failed: {0}")]
+    Simulation(TransactionDiagnostic),
+    #[error("Transaction failed: {0}")]
+    Execution(TransactionDiagnostic),
+    /// The error does not carry a transaction error, e.g., the RPC node is not reachable.
+    #[error("Transaction error: {0}")]
+    Client(String),
+}
+
+impl TransactionFailure {
+    pub fn diagnostic(&self) -> Option<&TransactionDiagnostic> {
+        match self {
+            TransactionFailure::Simulation(diagnostic)
+            | TransactionFailure::Execution(diagnostic) => Some(diagnostic),
+            TransactionFailure::Client(_) => None,
+        }
+    }
+}
+
+/// Errors of the executors that a [TransactionDiagnostic] can be derived from.
+pub trait DiagnoseTransactionError {
+    fn diagnostic(&self) -> Option<TransactionDiagnostic>;
+}
+
+impl DiagnoseTransactionError for SolanaClientError {
+    fn diagnostic(&self) -> Option<TransactionDiagnostic> {
+        TransactionDiagnostic::from_client_error(self)
+    }
+}
+
+impl DiagnoseTransactionError for anchor_client::ClientError {
+    fn diagnostic(&self) -> Option<TransactionDiagnostic> {
+        match self {
+            anchor_client::ClientError::SolanaClientError(err) => err.diagnostic(),
+            _ => None,
+        }
+    }
+}
+
+struct AnchorErrorLog {
+    code: String,
+    number: u32,
+    message: String,
+    account: Option<String>,
+}
+
+impl AnchorErrorLog {
+    /// Parses the log line
+    /// `Program log: AnchorError <origin>. Error Code: <name>. Error Number: <number>. Error Message: <message>.`
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.strip_prefix("Program log: AnchorError")?;
+        let (origin, rest) = line.split_once(". Error Code: ")?;
+        let (code, rest) = rest.split_once(". Error Number: ")?;
+        let (number, message) = rest.split_once(". Error Message: ")?;
+        Some(Self {
+            code: code.to_string(),
+            number: number.trim().parse().ok()?,
+            message: message.trim_end_matches('.').to_string(),
+            account: origin
+                .trim()
+                .strip_prefix("caused by account: ")
+                .map(|account| account.to_string()),
+        })
+    }
+}
+
+/// Parses the log line `Program <program id> invoke [<depth>]`.
+fn parse_invoke(line: &str) -> Option<(&str, usize)> {
+    let rest = line.strip_prefix("Program ")?;
+    let (program_id, depth) = rest.split_once(" invoke [")?;
+    Some((program_id, depth.strip_suffix(']')?.parse().ok()?))
+}
+
+/// Logs of the top level instruction with the index, every top level instruction starts with `invoke [1]`.
+fn instruction_logs(logs: &[String], instruction_index: usize) -> Vec<String> {
+    let mut top_level_index = None;
+    let mut instruction_logs = Vec::new();
+    for line in logs {
+        if let Some((_, 1)) = parse_invoke(line) {
+            top_level_index = Some(top_level_index.map_or(0, |index| index + 1));
+        }
+        if top_level_index == Some(instruction_index) {
+            instruction_logs.push(line.clone());
+        }
+    }
+    instruction_logs
+}
+
+/// Anchor programs log the name of the executed instruction as `Program log: Instruction: <name>`.
+fn top_level_instruction_name(instruction_logs: &[String]) -> Option<String> {
+    let mut depth = 0_usize;
+    for line in instruction_logs {
+        if let Some((_, invoke_depth)) = parse_invoke(line) {
+            depth = invoke_depth;
+        } else if line.starts_with("Program ")
+            && (line.ends_with(" success") || line.contains(" failed: "))
+        {
+            depth = depth.saturating_sub(1);
+        } else if depth == 1 {
+            if let Some(name) = line.strip_prefix("Program log: Instruction: ") {
+                return Some(name.to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_diagnostic_from_anchor_error_logs() {
+        let logs: Vec<String> = [
+            "Program ComputeBudget111111111111111111111111111111 invoke [1]",
+            "Program ComputeBudget111111111111111111111111111111 success",
+            "Program MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD invoke [1]",
+            "Program log: Instruction: Deposit",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program log: AnchorError thrown in programs/marinade-finance/src/instructions/user/deposit.rs:93. Error Code: DepositAmountIsTooLow. Error Number: 6039. Error Message: Deposit amount is too low.",
+            "Program MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD consumed 12345 of 200000 compute units",
+            "Program MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD failed: custom program error: 0x1797",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        let diagnostic = TransactionDiagnostic::new(
+            &TransactionError::InstructionError(1, InstructionError::Custom(6039)),
+            &logs,
+        );
+        assert_eq!(diagnostic.instruction_index, Some(1));
+        assert_eq!(
+            diagnostic.program_id.as_deref(),
+            Some("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD")
+        );
+        assert_eq!(diagnostic.instruction_name.as_deref(), Some("Deposit"));
+        assert_eq!(diagnostic.error_code, Some(6039));
+        assert_eq!(
+            diagnostic.error_name.as_deref(),
+            Some("DepositAmountIsTooLow")
+        );
+        assert_eq!(
+            diagnostic.error_message.as_deref(),
+            Some("Deposit amount is too low")
+        );
+        assert_eq!(diagnostic.error_account, None);
+        assert!(!diagnostic.is_anchor_framework_error());
+        assert_eq!(diagnostic.logs, logs[2..].to_vec());
+
+        let logs = vec!["Program log: AnchorError caused by account: state. Error Code: AccountDiscriminatorMismatch. Error Number: 3002. Error Message: 8 byte discriminator did not match what was expected.".to_string()];
+        let diagnostic = TransactionDiagnostic::new(&TransactionError::AccountInUse, &logs);
+        assert_eq!(diagnostic.instruction_index, None);
+        assert_eq!(diagnostic.error_code, Some(3002));
+        assert_eq!(diagnostic.error_account.as_deref(), Some("state"));
+        assert!(diagnostic.is_anchor_framework_error());
+    }
+
+    #[test]
+    fn test_diagnostic_from_message_without_logs() {
+        let fee_payer = Pubkey::new_unique();
+        let message = VersionedMessage::Legacy(Message::new(
+            &[Instruction::new_with_bytes(
+                marinade_finance::ID,
+                &marinade_finance_instruction::Deposit::DISCRIMINATOR,
+                vec![AccountMeta::new(fee_payer, true)],
+            )],
+            Some(&fee_payer),
+        ));
+        let error_code = u32::from(MarinadeError::DepositAmountIsTooLow);
+        let diagnostic = TransactionDiagnostic::new(
+            &TransactionError::InstructionError(0, InstructionError::Custom(error_code)),
+            &[],
+        )
+        .with_message(&message);
+        assert_eq!(
+            diagnostic.program_id,
+            Some(marinade_finance::ID.to_string())
+        );
+        assert_eq!(diagnostic.instruction_name.as_deref(), Some("Deposit"));
+        assert_eq!(
+            diagnostic.error_name.as_deref(),
+            Some("DepositAmountIsTooLow")
+        );
+        assert_eq!(
+            diagnostic.error_message,
+            Some(MarinadeError::DepositAmountIsTooLow.to_string())
+        );
+        assert!(marinade_error(ERROR_CODE_OFFSET - 1).is_none());
+    }
+
+    #[test]
+    fn test_marinade_instruction_name() {
+        assert_eq!(
+            marinade_instruction_name(&marinade_finance_instruction::Deposit::DISCRIMINATOR),
+            Some("Deposit")
+        );
+        assert_eq!(marinade_instruction_name(&[0; 8]), None);
+        assert_eq!(marinade_instruction_name(&[1, 2]), None);
+    }
+}
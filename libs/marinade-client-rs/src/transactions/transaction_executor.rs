@@ -0,0 +1,709 @@
+use crate::error::MarinadeClientError;
+use crate::marinade::accounts_diff::AccountsDiffFormat;
+use crate::transactions::chained_simulation::{
+    chained_simulation_report, simulate_prepared_transaction_chained, AccountsOverlay,
+    ChainedSimulator,
+};
+use crate::transactions::execution_report::{ExecutionReport, TransactionReport};
+use crate::transactions::explorer::{print_prepared_message, ExplorerCluster};
+use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::transactions::retry_policy::RetryPolicy;
+use crate::transactions::transaction_builder::TransactionBuilder;
+use crate::transactions::transaction_executors::{
+    execute_anchor_request_with_sender, execute_prepared_transaction_with_sender, log_execution,
+    log_simulation, simulate_prepared_transaction, simulation_config, TransactionSimulator,
+};
+use crate::transactions::transaction_instruction::{
+    print_export, MultisigExport, SplGovernanceExport,
+};
+use anchor_client::RequestBuilder;
+use log::warn;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::compute_budget;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::fs::File;
+use std::io::Write;
+use std::ops::Deref;
+
+/// Anchor request the executors work with, implemented for [RequestBuilder].
+pub trait AnchorRequest: TransactionSimulator {
+    fn request_instructions(&self) -> Result<Vec<Instruction>, anchor_client::ClientError>;
+
+    /// Transaction signed by the request signers with the provided blockhash.
+    fn signed_request_transaction(
+        &self,
+        blockhash: Hash,
+    ) -> Result<Transaction, anchor_client::ClientError>;
+}
+
+impl<'a, C: Deref<Target = impl Signer> + Clone> AnchorRequest for RequestBuilder<'a, C> {
+    fn request_instructions(&self) -> Result<Vec<Instruction>, anchor_client::ClientError> {
+        self.instructions()
+    }
+
+    fn signed_request_transaction(
+        &self,
+        blockhash: Hash,
+    ) -> Result<Transaction, anchor_client::ClientError> {
+        self.signed_transaction_with_blockhash(blockhash)
+    }
+}
+
+/// Defines what happens with every transaction, e.g., it is sent, simulated or printed.
+/// The transaction failure is returned in the report, the error is returned when the executor
+/// itself cannot proceed (e.g., the export file cannot be written).
+pub trait TransactionExecutor {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError>;
+
+    /// The instruction packs of the anchor request are the index of the anchor builder.
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError>;
+
+    /// Called instead of the execution when a previous transaction failed.
+    fn skip_prepared(
+        &mut self,
+        prepared_transaction: &PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        Ok(TransactionReport::skipped(
+            prepared_transaction.instruction_packs.clone(),
+        ))
+    }
+
+    /// Called instead of the execution when a previous anchor request failed.
+    fn skip_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        _anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        Ok(TransactionReport::skipped(instruction_packs))
+    }
+}
+
+/// Sends the transactions to the RPC node, optionally through the sender RPC.
+pub struct RpcExecutor<'a> {
+    rpc_client: &'a RpcClient,
+    sender_rpc_client: Option<&'a RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: RetryPolicy,
+}
+
+impl<'a> RpcExecutor<'a> {
+    pub fn new(
+        rpc_client: &'a RpcClient,
+        preflight_config: RpcSendTransactionConfig,
+        blockhash_commitment: CommitmentLevel,
+    ) -> Self {
+        Self {
+            rpc_client,
+            sender_rpc_client: None,
+            preflight_config,
+            blockhash_commitment,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_sender(mut self, sender_rpc_client: Option<&'a RpcClient>) -> Self {
+        self.sender_rpc_client = sender_rpc_client;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+}
+
+impl<'a> TransactionExecutor for RpcExecutor<'a> {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        let execution_result = execute_prepared_transaction_with_sender(
+            prepared_transaction,
+            self.rpc_client,
+            self.sender_rpc_client,
+            self.preflight_config,
+            self.blockhash_commitment,
+            &self.retry_policy,
+        );
+        let _ = log_execution(&execution_result);
+        Ok(TransactionReport::from_execution(
+            prepared_transaction.instruction_packs.clone(),
+            &execution_result,
+        )
+        .with_message(&prepared_transaction.transaction.message)
+        .load_details(self.rpc_client))
+    }
+
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        let execution_result = execute_anchor_request_with_sender(
+            anchor_request,
+            self.rpc_client,
+            self.sender_rpc_client,
+            self.preflight_config,
+            self.blockhash_commitment,
+            &self.retry_policy,
+        );
+        let _ = log_execution(&execution_result);
+        Ok(
+            TransactionReport::from_execution(instruction_packs, &execution_result)
+                .load_details(self.rpc_client),
+        )
+    }
+}
+
+/// Simulates the transactions, nothing is sent.
+pub struct SimulateExecutor<'a> {
+    rpc_client: &'a RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    sig_verify: bool,
+    first_only: bool,
+    chained: Option<(Box<dyn ChainedSimulator + 'a>, AccountsOverlay)>,
+    accounts_diff: Option<AccountsDiffFormat>,
+    simulated: usize,
+}
+
+impl<'a> SimulateExecutor<'a> {
+    pub fn new(
+        rpc_client: &'a RpcClient,
+        preflight_config: RpcSendTransactionConfig,
+        blockhash_commitment: CommitmentLevel,
+    ) -> Self {
+        Self {
+            rpc_client,
+            preflight_config,
+            blockhash_commitment,
+            sig_verify: true,
+            first_only: false,
+            chained: None,
+            accounts_diff: None,
+            simulated: 0,
+        }
+    }
+
+    /// With no signature verification the transactions with unknown signers can be simulated (e.g., in print mode).
+    pub fn with_sig_verify(mut self, sig_verify: bool) -> Self {
+        self.sig_verify = sig_verify;
+        self
+    }
+
+    /// Only the first transaction is simulated, the rest is skipped as the transactions
+    /// may depend on the result of the previous ones.
+    pub fn with_first_only(mut self) -> Self {
+        self.first_only = true;
+        self
+    }
+
+    /// Every transaction is simulated over the account changes of the previously simulated ones,
+    /// see [simulate_prepared_transaction_chained].
+    pub fn with_chained(mut self, simulator: Box<dyn ChainedSimulator + 'a>) -> Self {
+        self.chained = Some((simulator, AccountsOverlay::default()));
+        self
+    }
+
+    /// Changes of the writable accounts made by every simulated transaction are printed in the format.
+    pub fn with_accounts_diff(mut self, accounts_diff: Option<AccountsDiffFormat>) -> Self {
+        self.accounts_diff = accounts_diff;
+        self
+    }
+
+    fn skip_next(&mut self) -> bool {
+        self.simulated += 1;
+        if self.first_only && self.simulated == 2 {
+            warn!("Simulation mode: only the first bunch of transactions was simulated, the rest was not simulated.");
+        }
+        self.first_only && self.simulated > 1
+    }
+}
+
+impl<'a> TransactionExecutor for SimulateExecutor<'a> {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        if self.skip_next() {
+            return self.skip_prepared(prepared_transaction);
+        }
+        let simulate_config = simulation_config(&self.preflight_config, self.sig_verify);
+        let simulation = match self.chained.as_mut() {
+            Some((simulator, overlay)) => Some(simulate_prepared_transaction_chained(
+                prepared_transaction,
+                self.rpc_client,
+                simulator.as_ref(),
+                simulate_config.clone(),
+                self.blockhash_commitment,
+                overlay,
+                self.accounts_diff.is_some(),
+            )),
+            // the accounts diff is taken from a single simulation against the cluster state
+            None if self.accounts_diff.is_some() => Some(simulate_prepared_transaction_chained(
+                prepared_transaction,
+                self.rpc_client,
+                self.rpc_client,
+                simulate_config.clone(),
+                self.blockhash_commitment,
+                &mut AccountsOverlay::default(),
+                true,
+            )),
+            None => None,
+        };
+        if let Some(simulation) = simulation {
+            let _ = log_simulation(&simulation.result);
+            if let (Some(format), Some(accounts_diff)) =
+                (self.accounts_diff, simulation.accounts_diff.as_ref())
+            {
+                format.print(accounts_diff);
+            }
+            return Ok(chained_simulation_report(
+                prepared_transaction.instruction_packs.clone(),
+                &simulation,
+            )
+            .with_message(&prepared_transaction.transaction.message));
+        }
+        let simulation_result = simulate_prepared_transaction(
+            prepared_transaction,
+            self.rpc_client,
+            simulate_config,
+            self.blockhash_commitment,
+        );
+        // failures are kept in the report
+        let _ = log_simulation(&simulation_result);
+        Ok(TransactionReport::from_simulation(
+            prepared_transaction.instruction_packs.clone(),
+            &simulation_result,
+        )
+        .with_message(&prepared_transaction.transaction.message))
+    }
+
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        if self.skip_next() {
+            return self.skip_anchor(instruction_packs, anchor_request);
+        }
+        let simulation_result = anchor_request.simulate(self.rpc_client, self.sig_verify);
+        let _ = log_simulation(&simulation_result);
+        Ok(TransactionReport::from_simulation(
+            instruction_packs,
+            &simulation_result,
+        ))
+    }
+}
+
+/// Prints the instructions of the transactions in the multisig export format, nothing is sent.
+/// The transactions skipped after a failure are printed as well.
+pub struct PrintExecutor {
+    export: Box<dyn MultisigExport>,
+    cluster: Option<ExplorerCluster>,
+    print_instructions: bool,
+}
+
+impl Default for PrintExecutor {
+    fn default() -> Self {
+        Self {
+            export: Box::new(SplGovernanceExport),
+            cluster: None,
+            print_instructions: true,
+        }
+    }
+}
+
+impl PrintExecutor {
+    pub fn with_export(mut self, export: Box<dyn MultisigExport>) -> Self {
+        self.export = export;
+        self
+    }
+
+    /// The message of the prepared transaction is printed with the explorer inspector URL.
+    pub fn with_inspector(mut self, cluster: ExplorerCluster) -> Self {
+        self.cluster = Some(cluster);
+        self
+    }
+
+    /// The instructions of the prepared transaction are not printed, e.g., when the instructions
+    /// of the whole builder are printed before the execution.
+    pub fn without_instructions(mut self) -> Self {
+        self.print_instructions = false;
+        self
+    }
+
+    fn print_prepared(
+        &self,
+        prepared_transaction: &PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        if let Some(source) = prepared_transaction
+            .source
+            .as_ref()
+            .filter(|_| self.print_instructions)
+        {
+            print_export(&source.instructions, self.export.as_ref())?;
+        }
+        if let Some(cluster) = &self.cluster {
+            print_prepared_message(prepared_transaction, cluster);
+        }
+        Ok(TransactionReport::printed(
+            prepared_transaction.instruction_packs.clone(),
+        ))
+    }
+
+    fn print_anchor(
+        &self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        print_export(
+            &without_compute_budget(anchor_request.request_instructions()?),
+            self.export.as_ref(),
+        )?;
+        Ok(TransactionReport::printed(instruction_packs))
+    }
+}
+
+impl TransactionExecutor for PrintExecutor {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        self.print_prepared(prepared_transaction)
+    }
+
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        self.print_anchor(instruction_packs, anchor_request)
+    }
+
+    fn skip_prepared(
+        &mut self,
+        prepared_transaction: &PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        self.print_prepared(prepared_transaction)?;
+        Ok(TransactionReport::skipped(
+            prepared_transaction.instruction_packs.clone(),
+        ))
+    }
+
+    fn skip_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        self.print_anchor(instruction_packs.clone(), anchor_request)?;
+        Ok(TransactionReport::skipped(instruction_packs))
+    }
+}
+
+/// Writes the instructions of the transactions in the multisig export format to the file, nothing is sent.
+pub struct FileExportExecutor {
+    file: File,
+    export: Box<dyn MultisigExport>,
+}
+
+impl FileExportExecutor {
+    pub fn create(
+        path: &str,
+        export: Box<dyn MultisigExport>,
+    ) -> Result<Self, MarinadeClientError> {
+        Ok(Self {
+            file: File::create(path)?,
+            export,
+        })
+    }
+
+    fn write(&mut self, instructions: &[Instruction]) -> Result<(), MarinadeClientError> {
+        for item in self.export.export(instructions)? {
+            writeln!(self.file, "{}\n  {}", item.title, item.data)?;
+        }
+        Ok(())
+    }
+}
+
+impl TransactionExecutor for FileExportExecutor {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        if let Some(source) = &prepared_transaction.source {
+            self.write(&source.instructions)?;
+        }
+        Ok(TransactionReport::printed(
+            prepared_transaction.instruction_packs.clone(),
+        ))
+    }
+
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        self.write(&without_compute_budget(
+            anchor_request.request_instructions()?,
+        ))?;
+        Ok(TransactionReport::printed(instruction_packs))
+    }
+}
+
+/// Runs the executors one by one for every transaction, e.g., print then send. The report
+/// of the last executor is returned, the failed report stops the chain.
+pub struct CompositeExecutor<'a> {
+    executors: Vec<Box<dyn TransactionExecutor + 'a>>,
+}
+
+impl<'a> CompositeExecutor<'a> {
+    pub fn new(executors: Vec<Box<dyn TransactionExecutor + 'a>>) -> Self {
+        Self { executors }
+    }
+
+    pub fn print_then_send(print: PrintExecutor, send: RpcExecutor<'a>) -> Self {
+        Self::new(vec![Box::new(print), Box::new(send)])
+    }
+}
+
+impl<'a> TransactionExecutor for CompositeExecutor<'a> {
+    fn execute_prepared(
+        &mut self,
+        prepared_transaction: &mut PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        let mut report = TransactionReport::skipped(prepared_transaction.instruction_packs.clone());
+        for executor in self.executors.iter_mut() {
+            report = executor.execute_prepared(prepared_transaction)?;
+            if report.is_failed() {
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    fn execute_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        let mut report = TransactionReport::skipped(instruction_packs.clone());
+        for executor in self.executors.iter_mut() {
+            report = executor.execute_anchor(instruction_packs.clone(), anchor_request)?;
+            if report.is_failed() {
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    fn skip_prepared(
+        &mut self,
+        prepared_transaction: &PreparedTransaction,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        for executor in self.executors.iter_mut() {
+            executor.skip_prepared(prepared_transaction)?;
+        }
+        Ok(TransactionReport::skipped(
+            prepared_transaction.instruction_packs.clone(),
+        ))
+    }
+
+    fn skip_anchor(
+        &mut self,
+        instruction_packs: Vec<usize>,
+        anchor_request: &dyn AnchorRequest,
+    ) -> Result<TransactionReport, MarinadeClientError> {
+        for executor in self.executors.iter_mut() {
+            executor.skip_anchor(instruction_packs.clone(), anchor_request)?;
+        }
+        Ok(TransactionReport::skipped(instruction_packs))
+    }
+}
+
+// compute budget of the printed transactions is set by the multisig executing them
+fn without_compute_budget(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    instructions
+        .into_iter()
+        .filter(|instruction| instruction.program_id != compute_budget::ID)
+        .collect()
+}
+
+/// Runs the executor for the prepared transactions one by one, after the failed transaction the rest is skipped.
+pub fn execute_prepared_transactions_with_executor<I: IntoIterator<Item = PreparedTransaction>>(
+    prepared_transactions: I,
+    executor: &mut dyn TransactionExecutor,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    let mut report = ExecutionReport::default();
+    for mut prepared_transaction in prepared_transactions {
+        let transaction_report = if report.is_success() {
+            executor.execute_prepared(&mut prepared_transaction)?
+        } else {
+            executor.skip_prepared(&prepared_transaction)?
+        };
+        report.push(transaction_report);
+    }
+    Ok(report)
+}
+
+/// Runs the executor for the combined transactions of the builder, see [execute_prepared_transactions_with_executor].
+pub fn execute_transaction_builder_with_executor(
+    transaction_builder: &mut TransactionBuilder,
+    executor: &mut dyn TransactionExecutor,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_prepared_transactions_with_executor(transaction_builder.sequence_combined(), executor)
+}
+
+/// Runs the executor for the anchor builders one by one, after the failed request the rest is skipped.
+/// The instruction pack of the report entry is the builder index.
+pub fn execute_anchor_builders_with_executor<'a, C: Deref<Target = impl Signer> + Clone>(
+    anchor_builders: impl IntoIterator<Item = RequestBuilder<'a, C>>,
+    executor: &mut dyn TransactionExecutor,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_prepared_anchor_builders_with_executor(anchor_builders, executor, &mut |_, builder| {
+        Ok(builder)
+    })
+}
+
+/// Runs the executor for the anchor builders as [execute_anchor_builders_with_executor],
+/// every builder is updated by `prepare` before it is executed (e.g., to set its compute budget).
+pub(crate) fn execute_prepared_anchor_builders_with_executor<
+    'a,
+    C: Deref<Target = impl Signer> + Clone,
+>(
+    anchor_builders: impl IntoIterator<Item = RequestBuilder<'a, C>>,
+    executor: &mut dyn TransactionExecutor,
+    prepare: &mut dyn FnMut(
+        usize,
+        RequestBuilder<'a, C>,
+    ) -> Result<RequestBuilder<'a, C>, MarinadeClientError>,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    let mut report = ExecutionReport::default();
+    for (index, builder) in anchor_builders.into_iter().enumerate() {
+        let transaction_report = if report.is_success() {
+            let builder = prepare(index, builder)?;
+            executor.execute_anchor(vec![index], &builder)?
+        } else {
+            executor.skip_anchor(vec![index], &builder)?
+        };
+        report.push(transaction_report);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::execution_report::TransactionStatus;
+    use solana_sdk::instruction::AccountMeta;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Keypair;
+    use std::sync::Arc;
+
+    /// Records the executed instruction packs, the transaction of the failing pack fails.
+    struct RecordingExecutor {
+        executed: Vec<Vec<usize>>,
+        skipped: Vec<Vec<usize>>,
+        failing_pack: Option<usize>,
+    }
+
+    impl TransactionExecutor for RecordingExecutor {
+        fn execute_prepared(
+            &mut self,
+            prepared_transaction: &mut PreparedTransaction,
+        ) -> Result<TransactionReport, MarinadeClientError> {
+            let instruction_packs = prepared_transaction.instruction_packs.clone();
+            self.executed.push(instruction_packs.clone());
+            if self
+                .failing_pack
+                .map(|pack| instruction_packs.contains(&pack))
+                .unwrap_or(false)
+            {
+                return Ok(TransactionReport::from_execution(
+                    instruction_packs,
+                    &Err::<solana_sdk::signature::Signature, _>(
+                        anchor_client::ClientError::LogParseError("failed".to_string()),
+                    ),
+                ));
+            }
+            Ok(TransactionReport::printed(instruction_packs))
+        }
+
+        fn execute_anchor(
+            &mut self,
+            instruction_packs: Vec<usize>,
+            _anchor_request: &dyn AnchorRequest,
+        ) -> Result<TransactionReport, MarinadeClientError> {
+            self.executed.push(instruction_packs.clone());
+            Ok(TransactionReport::printed(instruction_packs))
+        }
+
+        fn skip_prepared(
+            &mut self,
+            prepared_transaction: &PreparedTransaction,
+        ) -> Result<TransactionReport, MarinadeClientError> {
+            self.skipped
+                .push(prepared_transaction.instruction_packs.clone());
+            Ok(TransactionReport::skipped(
+                prepared_transaction.instruction_packs.clone(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_execute_with_executor() {
+        let fee_payer: Arc<dyn Signer + Send + Sync> = Arc::new(Keypair::new());
+        let mut transaction_builder = TransactionBuilder::limited(fee_payer.clone());
+        for _ in 0..3 {
+            // every pack is too big to be combined with another one
+            transaction_builder
+                .add_instruction(Instruction::new_with_bytes(
+                    Pubkey::new_unique(),
+                    &[0; 600],
+                    vec![AccountMeta::new(fee_payer.pubkey(), true)],
+                ))
+                .unwrap();
+            transaction_builder.finish_instruction_pack();
+        }
+        let prepared_transactions: Vec<PreparedTransaction> =
+            transaction_builder.sequence_combined().collect();
+        assert_eq!(prepared_transactions.len(), 3);
+
+        let mut executor = RecordingExecutor {
+            executed: vec![],
+            skipped: vec![],
+            failing_pack: Some(1),
+        };
+        let report =
+            execute_prepared_transactions_with_executor(prepared_transactions, &mut executor)
+                .unwrap();
+        assert_eq!(executor.executed, vec![vec![0], vec![1]]);
+        assert_eq!(executor.skipped, vec![vec![2]]);
+        assert_eq!(
+            report
+                .transactions
+                .iter()
+                .map(|transaction| transaction.status)
+                .collect::<Vec<_>>(),
+            vec![
+                TransactionStatus::Printed,
+                TransactionStatus::Failed,
+                TransactionStatus::Skipped
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
+}
@@ -1,55 +1,86 @@
-use crate::transactions::prepared_transaction::PreparedTransaction;
+use crate::error::MarinadeClientError;
+use crate::marinade::accounts_diff::AccountsDiffFormat;
+#[cfg(feature = "bank-simulator")]
+use crate::transactions::bank_simulator::BankSimulator;
+use crate::transactions::compute_budget::{
+    ComputeBudgetConfig, ComputeUnitLimitEstimation, ComputeUnitPrice,
+};
+use crate::transactions::durable_nonce::durable_nonce_account;
+use crate::transactions::execution_cost::{confirm_execution, estimate_execution_cost};
+use crate::transactions::execution_journal::{
+    instructions_hash, ExecutionJournal, JournalEntry, JournalStatus,
+};
+use crate::transactions::execution_report::{ExecutionReport, TransactionReport};
+use crate::transactions::explorer::{print_prepared_message, ExplorerCluster};
+use crate::transactions::prepared_transaction::{writable_accounts, PreparedTransaction};
+use crate::transactions::rebroadcast::{
+    confirm_transaction_with_rebroadcast, is_expired_transaction_error, is_nonce_advanced,
+    rebroadcast_config,
+};
+use crate::transactions::retry_policy::{
+    classify_confirm_error, classify_send_error, ErrorClass, RetryPolicy, RetryState,
+};
+use crate::transactions::sender::send_transaction_with_sender;
 use crate::transactions::transaction_builder::TransactionBuilder;
+use crate::transactions::transaction_diagnostic::{
+    DiagnoseTransactionError, TransactionDiagnostic, TransactionFailure,
+};
+use crate::transactions::transaction_executor::{
+    execute_prepared_anchor_builders_with_executor, execute_prepared_transactions_with_executor,
+    execute_transaction_builder_with_executor, AnchorRequest, CompositeExecutor, PrintExecutor,
+    RpcExecutor, SimulateExecutor, TransactionExecutor,
+};
 use crate::transactions::transaction_instruction::print_base64;
 use anchor_client::RequestBuilder;
-use anyhow::bail;
 use log::{debug, error, info, warn};
 use solana_client::client_error::ClientError as SolanaClientError;
 use solana_client::client_error::ClientErrorKind;
 use solana_client::rpc_client::RpcClient;
 use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig};
 use solana_client::rpc_request::RpcError::ForUser;
-use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
 use solana_client::rpc_response::{RpcResult, RpcSimulateTransactionResult};
+use solana_sdk::clock::MAX_PROCESSING_AGE;
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
-use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
+use solana_transaction_status::TransactionStatus;
+use std::collections::VecDeque;
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
+/// Logs the result of the execution, the failure is returned as the typed [TransactionFailure].
 pub fn log_execution(
-    execution_result: &anyhow::Result<Signature, anchor_client::ClientError>,
-) -> anyhow::Result<()> {
+    execution_result: &Result<Signature, anchor_client::ClientError>,
+) -> Result<(), TransactionFailure> {
     match execution_result {
         Ok(signature) => info!("Transaction {}", signature),
         Err(err) => {
-            if let anchor_client::ClientError::SolanaClientError(ce) = &err {
-                if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
-                    data:
-                        RpcResponseErrorData::SendTransactionPreflightFailure(
-                            RpcSimulateTransactionResult {
-                                err: _,
-                                logs: Some(logs),
-                                accounts: _,
-                                return_data: _,
-                                units_consumed: _,
-                            },
-                        ),
-                    ..
-                }) = ce.kind()
-                {
-                    error!("Solana client error: {}", ce);
-                    for log in logs {
-                        error!("Log: {}", log);
-                    }
+            let diagnostic = match err.diagnostic() {
+                Some(diagnostic) => diagnostic,
+                None => {
+                    error!("Transaction error: {:?}", err);
+                    return Err(TransactionFailure::Client(err.to_string()));
                 }
-            }
-            bail!("Transaction error: {:?}", err);
+            };
+            error!("Solana client error: {}", err);
+            log_diagnostic(&diagnostic);
+            return Err(TransactionFailure::Execution(diagnostic));
         }
     }
     Ok(())
 }
 
+fn log_diagnostic(diagnostic: &TransactionDiagnostic) {
+    for log in &diagnostic.logs {
+        error!("Log: {}", log);
+    }
+    error!("Transaction ERR {}", diagnostic);
+}
+
 pub trait TransactionSimulator {
     fn simulate(
         &self,
@@ -98,45 +129,32 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> TransactionSimulator for Reques
     }
 }
 
+/// Logs the result of the simulation, the failure is returned as the typed [TransactionFailure].
 pub fn log_simulation(
     simulation_result: &RpcResult<RpcSimulateTransactionResult>,
-) -> anyhow::Result<()> {
+) -> Result<(), TransactionFailure> {
     match simulation_result {
         Ok(result) => {
+            if let Some(diagnostic) = TransactionDiagnostic::from_simulation(&result.value) {
+                log_diagnostic(&diagnostic);
+                return Err(TransactionFailure::Simulation(diagnostic));
+            }
             if let Some(logs) = &result.value.logs {
                 for log in logs {
                     debug!("Log: {}", log);
                 }
             }
-            if result.value.err.is_some() {
-                error!("Transaction ERR {:?}", result);
-                bail!("Transaction error: {}", result.value.err.as_ref().unwrap());
-            } else {
-                info!("Transaction simulation Ok");
-            }
+            info!("Transaction simulation Ok");
         }
         Err(err) => {
             error!("Transaction error: {}", err);
-            if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
-                data:
-                    RpcResponseErrorData::SendTransactionPreflightFailure(
-                        RpcSimulateTransactionResult {
-                            err: _,
-                            logs: Some(logs),
-                            accounts: _,
-                            units_consumed: _,
-                            return_data: _,
-                        },
-                    ),
-                ..
-            }) = err.kind()
-            {
-                for log in logs {
-                    error!("Log: {}", log);
+            return Err(match err.diagnostic() {
+                Some(diagnostic) => {
+                    log_diagnostic(&diagnostic);
+                    TransactionFailure::Simulation(diagnostic)
                 }
-                error!("Transaction ERR {:?}", err);
-            }
-            bail!("Transaction error: {}", err);
+                None => TransactionFailure::Client(err.to_string()),
+            });
         }
     }
     Ok(())
@@ -146,41 +164,127 @@ pub fn execute_anchor_builders_with_config<'a, I, C>(
     anchor_builders: I,
     rpc_client: &RpcClient,
     preflight_config: RpcSendTransactionConfig,
+    compute_budget: ComputeBudgetConfig,
+    simulate: bool,
+    print: bool,
+) -> Result<ExecutionReport, MarinadeClientError>
+where
+    I: IntoIterator<Item = RequestBuilder<'a, C>>,
+    C: Deref<Target = dynsigner::DynSigner> + Clone,
+{
+    execute_anchor_builders_report(
+        anchor_builders,
+        rpc_client,
+        preflight_config,
+        compute_budget,
+        simulate,
+        print,
+    )?
+    .into_result()
+}
+
+/// Executes the anchor builders, the failure of the transaction is reported in the returned report
+/// and the rest of the builders is skipped. The instruction pack of the report entry is the builder index.
+pub fn execute_anchor_builders_report<'a, I, C>(
+    anchor_builders: I,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    compute_budget: ComputeBudgetConfig,
     simulate: bool,
     print: bool,
-) -> anyhow::Result<()>
+) -> Result<ExecutionReport, MarinadeClientError>
 where
     I: IntoIterator<Item = RequestBuilder<'a, C>>,
     C: Deref<Target = dynsigner::DynSigner> + Clone,
 {
     warn_text_simulate_print(simulate, print);
+    if compute_budget.compute_unit_limit_estimation.is_some() {
+        warn!("Compute unit limit estimation is not supported for anchor builders, the limit is not estimated.");
+    }
+    let compute_budget = ComputeBudgetConfig {
+        compute_unit_limit_estimation: None,
+        ..compute_budget
+    };
 
-    if simulate {
-        for builder in anchor_builders {
-            if print {
-                print_base64(&builder.instructions()?)?;
-            }
-            log_simulation(&builder.simulate(rpc_client, !print))?;
-        }
+    let blockhash_commitment = rpc_client.commitment().commitment;
+    let executor: Box<dyn TransactionExecutor + '_> = if simulate {
+        Box::new(
+            SimulateExecutor::new(rpc_client, preflight_config, blockhash_commitment)
+                .with_sig_verify(!print),
+        )
     } else {
-        anchor_builders.into_iter().try_for_each(|builder| {
-            if print {
-                print_base64(&builder.instructions()?)?;
-            }
-            log_execution(&builder.send_with_spinner_and_config(preflight_config))
-        })?;
-    }
+        Box::new(RpcExecutor::new(
+            rpc_client,
+            preflight_config,
+            blockhash_commitment,
+        ))
+    };
+    let mut executor = with_print_executor(executor, print);
+    execute_prepared_anchor_builders_with_executor(
+        anchor_builders,
+        executor.as_mut(),
+        &mut |_, builder| {
+            let instructions = builder.instructions()?;
+            let compute_budget = resolve_compute_unit_price(
+                compute_budget,
+                &writable_accounts(&instructions),
+                rpc_client,
+            )?;
+            Ok(compute_budget.apply_to_anchor_builder(builder))
+        },
+    )
+}
 
-    Ok(())
+/// Executes the anchor builders as [execute_anchor_builders_with_config] after the estimated cost
+/// of the execution paid by the `fee_payer` is confirmed, see [confirm_execution].
+#[allow(clippy::too_many_arguments)]
+pub fn execute_anchor_builders_confirmed<'a, I, C>(
+    anchor_builders: I,
+    rpc_client: &RpcClient,
+    fee_payer: &Pubkey,
+    preflight_config: RpcSendTransactionConfig,
+    compute_budget: ComputeBudgetConfig,
+    simulate: bool,
+    print: bool,
+    assume_yes: bool,
+) -> Result<ExecutionReport, MarinadeClientError>
+where
+    I: IntoIterator<Item = RequestBuilder<'a, C>>,
+    C: Deref<Target = dynsigner::DynSigner> + Clone,
+{
+    let anchor_builders: Vec<RequestBuilder<'a, C>> = anchor_builders.into_iter().collect();
+    if !simulate {
+        let transactions_instructions = anchor_builders
+            .iter()
+            .map(|builder| builder.instructions())
+            .collect::<Result<Vec<_>, _>>()?;
+        let estimate = estimate_execution_cost(
+            &transactions_instructions,
+            fee_payer,
+            &compute_budget,
+            None,
+            rpc_client,
+        )?;
+        confirm_execution(&estimate, assume_yes)?;
+    }
+    execute_anchor_builders_with_config(
+        anchor_builders,
+        rpc_client,
+        preflight_config,
+        compute_budget,
+        simulate,
+        print,
+    )
 }
 
 pub fn execute_anchor_builders<'a, I, C>(
     anchor_builders: I,
     rpc_client: &RpcClient,
     skip_preflight: bool,
+    compute_budget: ComputeBudgetConfig,
     simulate: bool,
     print: bool,
-) -> anyhow::Result<()>
+) -> Result<ExecutionReport, MarinadeClientError>
 where
     I: IntoIterator<Item = RequestBuilder<'a, C>>,
     C: Deref<Target = dynsigner::DynSigner> + Clone,
@@ -192,6 +296,7 @@ where
             skip_preflight,
             ..RpcSendTransactionConfig::default()
         },
+        compute_budget,
         simulate,
         print,
     )
@@ -201,13 +306,15 @@ pub fn execute_anchor_builder_with_config<C: Deref<Target = dynsigner::DynSigner
     anchor_builder: RequestBuilder<C>,
     rpc_client: &RpcClient,
     preflight_config: RpcSendTransactionConfig,
+    compute_budget: ComputeBudgetConfig,
     simulate: bool,
     print: bool,
-) -> anyhow::Result<()> {
+) -> Result<ExecutionReport, MarinadeClientError> {
     execute_anchor_builders_with_config(
         std::iter::once(anchor_builder),
         rpc_client,
         preflight_config,
+        compute_budget,
         simulate,
         print,
     )
@@ -217,13 +324,15 @@ pub fn execute_anchor_builder<C: Deref<Target = dynsigner::DynSigner> + Clone>(
     anchor_builder: RequestBuilder<C>,
     rpc_client: &RpcClient,
     skip_preflight: bool,
+    compute_budget: ComputeBudgetConfig,
     simulate: bool,
     print: bool,
-) -> anyhow::Result<()> {
+) -> Result<ExecutionReport, MarinadeClientError> {
     execute_anchor_builders(
         std::iter::once(anchor_builder),
         rpc_client,
         skip_preflight,
+        compute_budget,
         simulate,
         print,
     )
@@ -236,177 +345,1101 @@ pub fn execute_transaction_builder(
     blockhash_commitment: CommitmentLevel,
     simulate: bool,
     print: bool,
-    blockhash_failure_retries: Option<u16>,
-) -> anyhow::Result<()> {
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_transaction_builder_report(
+        transaction_builder,
+        rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        simulate,
+        print,
+        retry_policy,
+    )?
+    .into_result()
+}
+
+/// Executes the builder transactions sending them through the sender RPC, see [send_transaction_with_sender].
+/// Blockhashes are loaded and transactions are confirmed through the `rpc_client`.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_transaction_builder_with_sender(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_transaction_builder_report_with_sender(
+        transaction_builder,
+        rpc_client,
+        sender_rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        simulate,
+        print,
+        retry_policy,
+    )?
+    .into_result()
+}
+
+/// Executes the builder transactions, the failure of the transaction is reported in the returned report
+/// and the rest of the transactions is skipped.
+pub fn execute_transaction_builder_report(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    execute_transaction_builder_report_with_sender(
+        transaction_builder,
+        rpc_client,
+        None,
+        preflight_config,
+        blockhash_commitment,
+        simulate,
+        print,
+        retry_policy,
+    )
+}
+
+/// Executes the builder transactions as [execute_transaction_builder_report],
+/// when the sender RPC is provided the transactions are sent through it.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_transaction_builder_report_with_sender(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    warn_text_simulate_print(simulate, print);
+
+    let executor: Box<dyn TransactionExecutor + '_> = if simulate {
+        Box::new(chained_simulate_executor(
+            transaction_builder,
+            rpc_client,
+            preflight_config,
+            blockhash_commitment,
+            print,
+        ))
+    } else {
+        Box::new(
+            RpcExecutor::new(rpc_client, preflight_config, blockhash_commitment)
+                .with_sender(sender_rpc_client)
+                .with_retry_policy(*retry_policy),
+        )
+    };
+    let mut executor =
+        with_builder_print_executor(transaction_builder, executor, print, rpc_client)?;
+    execute_transaction_builder_with_executor(transaction_builder, executor.as_mut())
+}
+
+/// Simulates the builder transactions as [execute_transaction_builder_report] in the simulate mode,
+/// the changes of the writable accounts made by every transaction are printed in the format.
+pub fn simulate_transaction_builder_accounts_diff(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    print: bool,
+    accounts_diff: AccountsDiffFormat,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    warn_text_simulate_print(true, print);
+
+    let executor = chained_simulate_executor(
+        transaction_builder,
+        rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        print,
+    )
+    .with_accounts_diff(Some(accounts_diff));
+    let mut executor =
+        with_builder_print_executor(transaction_builder, Box::new(executor), print, rpc_client)?;
+    execute_transaction_builder_with_executor(transaction_builder, executor.as_mut())
+}
+
+/// Expecting the instructions are dependent one to each other,
+/// every transaction is simulated over the account changes of the previous ones.
+/// Without the `bank-simulator` feature the transactions are simulated by the RPC against the cluster state
+/// and the accounts changed by the previous transactions are reported as stale.
+fn chained_simulate_executor<'a>(
+    transaction_builder: &TransactionBuilder,
+    rpc_client: &'a RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    print: bool,
+) -> SimulateExecutor<'a> {
+    #[cfg(feature = "bank-simulator")]
+    let simulator = Box::new(BankSimulator::new(rpc_client));
+    #[cfg(not(feature = "bank-simulator"))]
+    let simulator = Box::new(rpc_client);
+    SimulateExecutor::new(rpc_client, preflight_config, blockhash_commitment)
+        .with_sig_verify(!print && transaction_builder.is_check_signers())
+        .with_chained(simulator)
+}
+
+/// With `print` the instructions of every transaction are printed before it is passed to the executor.
+fn with_print_executor<'a>(
+    executor: Box<dyn TransactionExecutor + 'a>,
+    print: bool,
+) -> Box<dyn TransactionExecutor + 'a> {
+    if !print {
+        return executor;
+    }
+    Box::new(CompositeExecutor::new(vec![
+        Box::new(PrintExecutor::default()),
+        executor,
+    ]))
+}
+
+/// With `print` the instructions of the whole builder are printed at once, then the message of every transaction
+/// is printed with the explorer inspector URL before it is passed to the executor.
+fn with_builder_print_executor<'a>(
+    transaction_builder: &TransactionBuilder,
+    executor: Box<dyn TransactionExecutor + 'a>,
+    print: bool,
+    rpc_client: &RpcClient,
+) -> Result<Box<dyn TransactionExecutor + 'a>, MarinadeClientError> {
+    if !print {
+        return Ok(executor);
+    }
+    print_base64(&transaction_builder.instructions())?;
+    let print_executor = PrintExecutor::default()
+        .with_inspector(ExplorerCluster::from_rpc_url(&rpc_client.url()))
+        .without_instructions();
+    Ok(Box::new(CompositeExecutor::new(vec![
+        Box::new(print_executor),
+        executor,
+    ])))
+}
+
+/// Executes the builder transactions as [execute_transaction_builder_with_sender], before anything is sent
+/// the estimated cost is printed, the fee payer balance is checked and the execution has to be confirmed,
+/// see [confirm_execution]. The simulation is run with no confirmation.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_transaction_builder_confirmed(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    retry_policy: &RetryPolicy,
+    assume_yes: bool,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    if simulate {
+        return execute_transaction_builder_with_sender(
+            transaction_builder,
+            rpc_client,
+            sender_rpc_client,
+            preflight_config,
+            blockhash_commitment,
+            simulate,
+            print,
+            retry_policy,
+        );
+    }
+    warn_text_simulate_print(simulate, print);
+
+    // the instructions of the builder are printed before its transactions are prepared
+    let executor = Box::new(
+        RpcExecutor::new(rpc_client, preflight_config, blockhash_commitment)
+            .with_sender(sender_rpc_client)
+            .with_retry_policy(*retry_policy),
+    );
+    let mut executor =
+        with_builder_print_executor(transaction_builder, executor, print, rpc_client)?;
+
+    let prepared_transactions: Vec<PreparedTransaction> =
+        transaction_builder.sequence_combined().collect();
+    let transactions_instructions: Vec<Vec<Instruction>> = prepared_transactions
+        .iter()
+        .filter_map(|prepared_transaction| prepared_transaction.source.as_ref())
+        .map(|source| source.instructions.clone())
+        .collect();
+    let estimate = estimate_execution_cost(
+        &transactions_instructions,
+        &transaction_builder.fee_payer(),
+        transaction_builder.compute_budget(),
+        transaction_builder.tip(),
+        rpc_client,
+    )?;
+    confirm_execution(&estimate, assume_yes)?;
+
+    execute_prepared_transactions_with_executor(prepared_transactions, executor.as_mut())?
+        .into_result()
+}
+
+/// Executes the builder transactions as [execute_transaction_builder_report_with_sender] recording every transaction
+/// to the journal. The signature is recorded with the blockhash expiry before the transaction is sent.
+/// When the operation is run again, the recorded transactions that may still land are awaited
+/// and the transactions that landed are not sent again.
+pub fn execute_transaction_builder_with_journal(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: &RetryPolicy,
+    journal: &mut ExecutionJournal,
+) -> Result<ExecutionReport, MarinadeClientError> {
+    let mut report = ExecutionReport::default();
+    for mut prepared_transaction in transaction_builder.sequence_combined() {
+        if !report.is_success() {
+            report.push(TransactionReport::skipped(
+                prepared_transaction.instruction_packs,
+            ));
+            continue;
+        }
+        let instructions_hash = instructions_hash(&prepared_transaction).to_string();
+        if let Some(signature) = journal.await_landed(
+            &prepared_transaction.instruction_packs,
+            &instructions_hash,
+            rpc_client,
+        )? {
+            info!(
+                "Instruction packs {:?} already landed in transaction {}, skipping",
+                prepared_transaction.instruction_packs, signature
+            );
+            report.push(
+                TransactionReport::from_execution(
+                    prepared_transaction.instruction_packs,
+                    &Ok::<Signature, SolanaClientError>(signature),
+                )
+                .load_details(rpc_client),
+            );
+            continue;
+        }
+
+        let instruction_packs = prepared_transaction.instruction_packs.clone();
+        let mut journal_result = Ok(());
+        let execution_result = execute_prepared_transaction_with_sender_internal(
+            &mut prepared_transaction,
+            rpc_client,
+            sender_rpc_client,
+            preflight_config,
+            blockhash_commitment,
+            retry_policy,
+            &mut |transaction, last_valid_block_height| {
+                if journal_result.is_ok() {
+                    journal_result = journal.record(JournalEntry {
+                        instruction_packs: instruction_packs.clone(),
+                        instructions_hash: instructions_hash.clone(),
+                        signature: Some(transaction.signatures[0].to_string()),
+                        blockhash: Some(transaction.message.recent_blockhash().to_string()),
+                        last_valid_block_height,
+                        status: JournalStatus::Sent,
+                        error: None,
+                    });
+                }
+            },
+        );
+        journal_result?;
+        journal.record(JournalEntry {
+            instruction_packs: instruction_packs.clone(),
+            instructions_hash,
+            signature: execution_result
+                .as_ref()
+                .ok()
+                .map(|signature| signature.to_string()),
+            blockhash: None,
+            last_valid_block_height: None,
+            status: if execution_result.is_ok() {
+                JournalStatus::Confirmed
+            } else {
+                JournalStatus::Failed
+            },
+            error: execution_result
+                .as_ref()
+                .err()
+                .map(|err| format!("{:?}", err)),
+        })?;
+        let _ = log_execution(&execution_result);
+        report.push(
+            TransactionReport::from_execution(instruction_packs, &execution_result)
+                .with_message(&prepared_transaction.transaction.message)
+                .load_details(rpc_client),
+        );
+    }
+
+    Ok(report)
+}
+
+/// Sends the combined transactions of the builder in parallel, see [execute_prepared_transactions_parallel].
+/// The instruction packs must not depend one on each other as the order of execution is not guaranteed.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_transaction_builder_parallel(
+    transaction_builder: &mut TransactionBuilder,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    simulate: bool,
+    print: bool,
+    max_in_flight: usize,
+    retry_policy: &RetryPolicy,
+) -> Result<ExecutionReport, MarinadeClientError> {
     warn_text_simulate_print(simulate, print);
 
     if print {
         print_base64(&transaction_builder.instructions())?;
     }
 
+    let is_checked_signers = transaction_builder.is_check_signers();
+    let mut prepared_transactions: Vec<PreparedTransaction> =
+        transaction_builder.sequence_combined().collect();
+    if print {
+        let cluster = ExplorerCluster::from_rpc_url(&rpc_client.url());
+        for prepared_transaction in &prepared_transactions {
+            print_prepared_message(prepared_transaction, &cluster);
+        }
+    }
+    let mut report = ExecutionReport::default();
     if simulate {
-        // expecting the instructions are dependent one to each other
-        // the result of the first can be used in the next one, for that simulation is run only for the fist bunch
-        let mut number_of_transactions = 0_u32;
-        let is_checked_signers = transaction_builder.is_check_signers();
-        for mut prepared_transaction in transaction_builder.sequence_combined() {
-            number_of_transactions += 1;
-            if number_of_transactions > 1 {
-                // only the first bunch is simulated
-                // need to drain whole sequence to find the number of transaction bunches
-                continue;
-            }
-            let simulation_config_default = RpcSimulateTransactionConfig::default();
-            let simulation_commitment = if preflight_config.preflight_commitment.is_some() {
-                Some(CommitmentConfig {
-                    commitment: preflight_config.preflight_commitment.unwrap(),
-                })
-            } else {
-                simulation_config_default.commitment
-            };
+        // the transactions are independent, every one of them can be simulated
+        for prepared_transaction in prepared_transactions.iter_mut() {
             let simulation_result = simulate_prepared_transaction(
-                &mut prepared_transaction,
+                prepared_transaction,
                 rpc_client,
-                RpcSimulateTransactionConfig {
-                    sig_verify: !print && is_checked_signers,
-                    commitment: simulation_commitment,
-                    encoding: preflight_config.encoding,
-                    min_context_slot: preflight_config.min_context_slot,
-                    ..simulation_config_default
-                },
+                simulation_config(&preflight_config, !print && is_checked_signers),
                 blockhash_commitment,
             );
-            log_simulation(&simulation_result)?;
-        }
-        if number_of_transactions > 1 {
-            warn!("Simulation mode: only the first bunch of transactions was simulated, the rest was not simulated.");
+            let _ = log_simulation(&simulation_result);
+            report.push(
+                TransactionReport::from_simulation(
+                    prepared_transaction.instruction_packs.clone(),
+                    &simulation_result,
+                )
+                .with_message(&prepared_transaction.transaction.message),
+            );
         }
     } else {
-        for mut prepared_transaction in transaction_builder.sequence_combined() {
-            let execution_result = execute_prepared_transaction_blockhash_retry(
-                &mut prepared_transaction,
-                rpc_client,
-                preflight_config,
-                blockhash_commitment,
-                blockhash_failure_retries,
+        let execution_results = execute_prepared_transactions_parallel(
+            &mut prepared_transactions,
+            rpc_client,
+            preflight_config,
+            blockhash_commitment,
+            max_in_flight,
+            retry_policy,
+        );
+        for (prepared_transaction, execution_result) in
+            prepared_transactions.iter().zip(execution_results.iter())
+        {
+            let _ = log_execution(execution_result);
+            report.push(
+                TransactionReport::from_execution(
+                    prepared_transaction.instruction_packs.clone(),
+                    execution_result,
+                )
+                .with_message(&prepared_transaction.transaction.message)
+                .load_details(rpc_client),
+            );
+        }
+        let failed = report.transactions.iter().filter(|t| t.is_failed()).count();
+        if failed > 0 {
+            error!(
+                "Parallel execution: {} of {} transactions failed",
+                failed,
+                report.transactions.len()
             );
-            log_execution(&execution_result)?;
         }
     }
 
-    Ok(())
+    report.into_result()
+}
+
+const PARALLEL_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// in-flight transactions are failed when their statuses cannot be polled for this time
+const PARALLEL_POLL_FAILURE_TIMEOUT: Duration = Duration::from_secs(60);
+// rpc.get_signature_statuses() has a max of 256 signatures
+const SIGNATURE_STATUSES_BATCH_SIZE: usize = 256;
+
+struct PendingTransaction {
+    index: usize,
+    /// The transaction is not sent before this time, the backoff of the retry.
+    send_after: Instant,
+}
+
+struct InFlightTransaction {
+    index: usize,
+    signature: Signature,
+    /// `None` for the durable nonce transaction, it expires when the nonce is advanced.
+    last_valid_block_height: Option<u64>,
+    last_sent: Instant,
+}
+
+/// Sends up to `max_in_flight` prepared transactions at once and confirms them together
+/// by polling the signature statuses. A transaction that expires before it is confirmed is re-signed
+/// with a fresh blockhash (the current nonce when its durable nonce was advanced) and sent again,
+/// the send errors are retried as the retry policy permits.
+/// The transactions using the same durable nonce account are sent one by one.
+/// The results are returned in the order of the prepared transactions.
+pub fn execute_prepared_transactions_parallel(
+    prepared_transactions: &mut [PreparedTransaction],
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    max_in_flight: usize,
+    retry_policy: &RetryPolicy,
+) -> Vec<Result<Signature, anchor_client::ClientError>> {
+    let max_in_flight = max_in_flight.max(1);
+    let blockhash_commitment = CommitmentConfig {
+        commitment: blockhash_commitment,
+    };
+    let mut results: Vec<Option<Result<Signature, anchor_client::ClientError>>> =
+        (0..prepared_transactions.len()).map(|_| None).collect();
+    let mut retry_states: Vec<RetryState> = (0..prepared_transactions.len())
+        .map(|_| retry_policy.start())
+        .collect();
+    let mut pending: VecDeque<PendingTransaction> = VecDeque::new();
+    for (index, prepared_transaction) in prepared_transactions.iter_mut().enumerate() {
+        match estimate_compute_unit_price(prepared_transaction, rpc_client)
+            .and_then(|_| estimate_compute_unit_limit(prepared_transaction, rpc_client))
+        {
+            Ok(_) => pending.push_back(PendingTransaction {
+                index,
+                send_after: Instant::now(),
+            }),
+            Err(err) => results[index] = Some(Err(err.into())),
+        }
+    }
+    let mut in_flight: Vec<InFlightTransaction> = Vec::new();
+    let mut poll_failing_since: Option<Instant> = None;
+
+    while !pending.is_empty() || !in_flight.is_empty() {
+        let mut latest_blockhash: Option<(Hash, u64)> = None;
+        while in_flight.len() < max_in_flight {
+            let index = if let Some(index) =
+                pop_ready_transaction(&mut pending, &in_flight, prepared_transactions)
+            {
+                index
+            } else {
+                break;
+            };
+            // the durable nonce transaction does not expire by block height
+            let durable_nonce = prepared_transactions[index].durable_nonce().copied();
+            let (blockhash, last_valid_block_height) = match durable_nonce {
+                Some(durable_nonce) => match durable_nonce.get_blockhash(rpc_client) {
+                    Ok(nonce_blockhash) => (nonce_blockhash, None),
+                    Err(err) => {
+                        results[index] = Some(Err(err.into()));
+                        continue;
+                    }
+                },
+                None => match latest_blockhash {
+                    Some((blockhash, last_valid_block_height)) => {
+                        (blockhash, Some(last_valid_block_height))
+                    }
+                    None => match rpc_client
+                        .get_latest_blockhash_with_commitment(blockhash_commitment)
+                    {
+                        Ok((blockhash, last_valid_block_height)) => {
+                            latest_blockhash = Some((blockhash, last_valid_block_height));
+                            (blockhash, Some(last_valid_block_height))
+                        }
+                        Err(err) => {
+                            error!("Parallel execution: cannot get latest blockhash: {:?}", err);
+                            // nothing can be sent without a blockhash, failing all not sent transactions
+                            for index in std::iter::once(index)
+                                .chain(pending.drain(..).map(|pending| pending.index))
+                            {
+                                results[index] =
+                                    Some(Err(anchor_client::ClientError::SolanaClientError(
+                                        SolanaClientError::from(ClientErrorKind::Custom(format!(
+                                            "Cannot get latest blockhash: {}",
+                                            err
+                                        ))),
+                                    )));
+                            }
+                            break;
+                        }
+                    },
+                },
+            };
+            let send_result = prepared_transactions[index]
+                .sign(blockhash)
+                .map_err(SolanaClientError::from)
+                .and_then(|transaction| {
+                    rpc_client.send_transaction_with_config(transaction, preflight_config)
+                });
+            match send_result {
+                Ok(signature) => {
+                    debug!(
+                        "Parallel execution: transaction #{} sent {}",
+                        index, signature
+                    );
+                    in_flight.push(InFlightTransaction {
+                        index,
+                        signature,
+                        last_valid_block_height,
+                        last_sent: Instant::now(),
+                    })
+                }
+                Err(err) => {
+                    let error_class = classify_send_error(&err, &prepared_transactions[index]);
+                    if let Some(delay) = retry_states[index].retry_after(error_class) {
+                        debug!(
+                            "Parallel execution: transaction #{} re-sending attempt #{} in {:?} after {:?} error: {:?}",
+                            index, retry_states[index].attempts(), delay, error_class, err
+                        );
+                        // the other transactions are sent and confirmed while this one is waiting
+                        pending.push_back(PendingTransaction {
+                            index,
+                            send_after: Instant::now() + delay,
+                        });
+                    } else {
+                        error!(
+                            "Parallel execution: transaction #{} failed to be sent: {:?}",
+                            index, err
+                        );
+                        results[index] = Some(Err(err.into()));
+                    }
+                }
+            }
+        }
+
+        if in_flight.is_empty() {
+            // waiting for the backoff of the next pending transaction
+            if let Some(send_after) = pending.iter().map(|pending| pending.send_after).min() {
+                std::thread::sleep(send_after.saturating_duration_since(Instant::now()));
+            }
+            continue;
+        }
+        std::thread::sleep(PARALLEL_STATUS_POLL_INTERVAL);
+        let (statuses, block_height) = match poll_in_flight_statuses(&in_flight, rpc_client) {
+            Ok(polled) => {
+                poll_failing_since = None;
+                polled
+            }
+            Err(err) => {
+                warn!(
+                    "Parallel execution: cannot get signature statuses and block height: {:?}",
+                    err
+                );
+                let failing_since = *poll_failing_since.get_or_insert_with(Instant::now);
+                if failing_since.elapsed() > PARALLEL_POLL_FAILURE_TIMEOUT {
+                    error!(
+                        "Parallel execution: statuses cannot be polled for {:?}, failing {} in-flight transactions",
+                        failing_since.elapsed(),
+                        in_flight.len()
+                    );
+                    for transaction in in_flight.drain(..) {
+                        results[transaction.index] =
+                            Some(Err(anchor_client::ClientError::SolanaClientError(
+                                SolanaClientError::from(ClientErrorKind::Custom(format!(
+                                    "Cannot confirm transaction {}, its status is unknown: {}",
+                                    transaction.signature, err
+                                ))),
+                            )));
+                    }
+                }
+                continue;
+            }
+        };
+
+        let mut still_in_flight: Vec<InFlightTransaction> = Vec::new();
+        for (transaction, status) in in_flight.drain(..).zip(statuses) {
+            match status {
+                Some(status) if status.err.is_some() => {
+                    results[transaction.index] =
+                        Some(Err(anchor_client::ClientError::SolanaClientError(
+                            SolanaClientError::from(status.err.unwrap()),
+                        )));
+                }
+                Some(status) if status.satisfies_commitment(rpc_client.commitment()) => {
+                    results[transaction.index] = Some(Ok(transaction.signature));
+                }
+                None if is_in_flight_expired(
+                    &transaction,
+                    block_height,
+                    &prepared_transactions[transaction.index],
+                    rpc_client,
+                ) =>
+                {
+                    // the blockhash expired or the nonce was advanced, the transaction cannot be processed anymore
+                    if retry_states[transaction.index]
+                        .retry_after(ErrorClass::RefreshState)
+                        .is_some()
+                    {
+                        debug!(
+                            "Parallel execution: transaction #{} {} expired, re-sending attempt #{}/{}",
+                            transaction.index,
+                            transaction.signature,
+                            retry_states[transaction.index].attempts(),
+                            retry_policy.max_attempts
+                        );
+                        pending.push_back(PendingTransaction {
+                            index: transaction.index,
+                            send_after: Instant::now(),
+                        });
+                    } else {
+                        results[transaction.index] =
+                            Some(Err(anchor_client::ClientError::SolanaClientError(
+                                SolanaClientError::from(TransactionError::BlockhashNotFound),
+                            )));
+                    }
+                }
+                _ => still_in_flight.push(transaction),
+            }
+        }
+        if let Some(rebroadcast_interval) = retry_policy.rebroadcast_interval {
+            for transaction in still_in_flight
+                .iter_mut()
+                .filter(|transaction| transaction.last_sent.elapsed() >= rebroadcast_interval)
+            {
+                transaction.last_sent = Instant::now();
+                if let Err(err) = rpc_client.send_transaction_with_config(
+                    &prepared_transactions[transaction.index].transaction,
+                    rebroadcast_config(preflight_config),
+                ) {
+                    warn!(
+                        "Parallel execution: transaction #{} {} rebroadcast failed: {:?}",
+                        transaction.index, transaction.signature, err
+                    );
+                }
+            }
+        }
+        in_flight = still_in_flight;
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every transaction is finished"))
+        .collect()
+}
+
+/// The in-flight transaction with no status expires when the block height passes its last valid block height,
+/// the durable nonce transaction when the nonce stored in its nonce account is advanced.
+fn is_in_flight_expired(
+    transaction: &InFlightTransaction,
+    block_height: u64,
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> bool {
+    match (
+        transaction.last_valid_block_height,
+        prepared_transaction.durable_nonce(),
+    ) {
+        (Some(last_valid_block_height), _) => block_height > last_valid_block_height,
+        (None, Some(durable_nonce)) => {
+            is_nonce_advanced(
+                rpc_client,
+                &prepared_transaction.transaction,
+                &durable_nonce.nonce_account,
+            )
+            // the transaction may land in between, the status is checked once more
+            && matches!(
+                rpc_client
+                    .get_signature_statuses(&[transaction.signature])
+                    .map(|response| response.value[0].is_none()),
+                Ok(true)
+            )
+        }
+        (None, None) => false,
+    }
+}
+
+/// Takes the first pending transaction with its backoff elapsed. The transaction using the durable nonce
+/// waits while another one with the same nonce account is in flight, only one of them could land with the nonce.
+fn pop_ready_transaction(
+    pending: &mut VecDeque<PendingTransaction>,
+    in_flight: &[InFlightTransaction],
+    prepared_transactions: &[PreparedTransaction],
+) -> Option<usize> {
+    let nonce_account = |index: usize| {
+        prepared_transactions[index]
+            .durable_nonce()
+            .map(|durable_nonce| durable_nonce.nonce_account)
+    };
+    let now = Instant::now();
+    let position = pending.iter().position(|pending| {
+        pending.send_after <= now
+            && nonce_account(pending.index)
+                .map(|nonce| {
+                    !in_flight
+                        .iter()
+                        .any(|transaction| nonce_account(transaction.index) == Some(nonce))
+                })
+                .unwrap_or(true)
+    })?;
+    pending.remove(position).map(|pending| pending.index)
+}
+
+/// Statuses of the in-flight transactions in their order and the current block height.
+fn poll_in_flight_statuses(
+    in_flight: &[InFlightTransaction],
+    rpc_client: &RpcClient,
+) -> Result<(Vec<Option<TransactionStatus>>, u64), SolanaClientError> {
+    let mut statuses = Vec::with_capacity(in_flight.len());
+    for chunk in in_flight.chunks(SIGNATURE_STATUSES_BATCH_SIZE) {
+        statuses.extend(
+            rpc_client
+                .get_signature_statuses(
+                    &chunk
+                        .iter()
+                        .map(|transaction| transaction.signature)
+                        .collect::<Vec<_>>(),
+                )?
+                .value,
+        );
+    }
+    Ok((statuses, rpc_client.get_block_height()?))
+}
+
+/// Simulation config matching the commitment and encoding of the preflight config used on sending.
+pub(crate) fn simulation_config(
+    preflight_config: &RpcSendTransactionConfig,
+    sig_verify: bool,
+) -> RpcSimulateTransactionConfig {
+    let simulation_config_default = RpcSimulateTransactionConfig::default();
+    let simulation_commitment = if preflight_config.preflight_commitment.is_some() {
+        Some(CommitmentConfig {
+            commitment: preflight_config.preflight_commitment.unwrap(),
+        })
+    } else {
+        simulation_config_default.commitment
+    };
+    RpcSimulateTransactionConfig {
+        sig_verify,
+        commitment: simulation_commitment,
+        encoding: preflight_config.encoding,
+        min_context_slot: preflight_config.min_context_slot,
+        ..simulation_config_default
+    }
 }
 
-fn execute_prepared_transaction_internal(
+/// The nonce stored in the nonce account for the durable nonce transaction, otherwise the latest blockhash.
+pub fn get_transaction_blockhash(
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<Hash, SolanaClientError> {
+    match prepared_transaction.durable_nonce() {
+        Some(durable_nonce) => durable_nonce.get_blockhash(rpc_client),
+        None => rpc_client.get_latest_blockhash(),
+    }
+}
+
+/// The nonce stored in the nonce account for the durable nonce transaction with no expiry,
+/// otherwise the latest blockhash with its last valid block height.
+pub fn get_transaction_blockhash_with_expiry(
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<(Hash, Option<u64>), SolanaClientError> {
+    match prepared_transaction.durable_nonce() {
+        Some(durable_nonce) => Ok((durable_nonce.get_blockhash(rpc_client)?, None)),
+        None => {
+            let (latest_hash, last_valid_block_height) =
+                rpc_client.get_latest_blockhash_with_commitment(rpc_client.commitment())?;
+            Ok((latest_hash, Some(last_valid_block_height)))
+        }
+    }
+}
+
+/// Signs the transaction before it is sent, returns the signed transaction with its last valid block height.
+type SignTransaction<'s> = dyn FnMut(
+        &mut PreparedTransaction,
+        &RpcClient,
+    ) -> Result<(VersionedTransaction, Option<u64>), SolanaClientError>
+    + 's;
+
+/// Signs the transaction by its signers with the latest blockhash or the durable nonce.
+fn sign_prepared_transaction(
     prepared_transaction: &mut PreparedTransaction,
     rpc_client: &RpcClient,
+) -> Result<(VersionedTransaction, Option<u64>), SolanaClientError> {
+    let (latest_hash, last_valid_block_height) =
+        get_transaction_blockhash_with_expiry(prepared_transaction, rpc_client)?;
+    let tx = prepared_transaction
+        .sign(latest_hash)
+        .map_err(|e| {
+            error!(
+                "execute_prepared_transaction: error signing transaction with blockhash: {}: {:?}",
+                latest_hash, e
+            );
+            SolanaClientError::from(e)
+        })?
+        .clone();
+    Ok((tx, last_valid_block_height))
+}
+
+/// Signs the transaction and sends it, `on_signed` is called with the signed transaction and its last valid block height
+/// before the transaction is sent.
+/// Returns the signed transaction with its last valid block height and the RPC client that accepted it.
+/// On error the RPC did not accept the transaction and it may be sent again.
+fn sign_and_send_prepared_transaction<'a>(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &'a RpcClient,
+    sender_rpc_client: Option<&'a RpcClient>,
     preflight_config: RpcSendTransactionConfig,
-) -> Result<Signature, solana_client::client_error::ClientError> {
-    let latest_hash = rpc_client.get_latest_blockhash()?;
-    let tx = prepared_transaction.sign(latest_hash).map_err(|e| {
+    sign: &mut SignTransaction<'_>,
+    on_signed: &mut dyn FnMut(&VersionedTransaction, Option<u64>),
+) -> Result<(VersionedTransaction, Option<u64>, &'a RpcClient), SolanaClientError> {
+    let (tx, last_valid_block_height) = sign(prepared_transaction, rpc_client)?;
+    on_signed(&tx, last_valid_block_height);
+
+    let send_rpc_client = match sender_rpc_client {
+        Some(sender_rpc_client) => {
+            send_transaction_with_sender(rpc_client, sender_rpc_client, &tx, preflight_config)?
+        }
+        None => {
+            rpc_client.send_transaction_with_config(&tx, preflight_config)?;
+            rpc_client
+        }
+    };
+    info!(
+        "Transaction {} sent to {}",
+        tx.signatures[0],
+        send_rpc_client.url()
+    );
+    Ok((tx, last_valid_block_height, send_rpc_client))
+}
+
+fn send_failed_error(
+    err: SolanaClientError,
+    error_class: ErrorClass,
+    retry_state: &RetryState,
+) -> anchor_client::ClientError {
+    // Return the error as-is so downstream `err.kind()` matches (e.g.
+    // log_execution's preflight-logs branch) keep working. Log the retry
+    // exhaustion context separately rather than rewrapping into a String variant.
+    if error_class == ErrorClass::Fatal {
+        error!("Transaction ERR send_transaction: {:?}", err);
+    } else {
         error!(
-            "execute_prepared_transaction: error signing transaction with blockhash: {}: {:?}",
-            latest_hash, e
+            "Transaction ERR send_transaction: retry exhausted after {} attempt(s); last error: {:?}",
+            retry_state.attempts(),
+            err
         );
-        SolanaClientError::from(e)
-    })?;
-
-    rpc_client.send_and_confirm_transaction_with_spinner_and_config(
-        tx,
-        rpc_client.commitment(),
-        preflight_config,
-    )
+    }
+    anchor_client::ClientError::SolanaClientError(err)
 }
 
-fn execute_prepared_transaction_retry_blockhash_internal(
+/// Sends the transaction and confirms it. Only the transaction the RPC did not accept is sent again
+/// as the retry policy permits. The accepted transaction may still land, its signature is confirmed
+/// until its last valid block height passes, only then it is re-signed with a fresh blockhash.
+fn execute_prepared_transaction_retry_internal(
     prepared_transaction: &mut PreparedTransaction,
     rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
     preflight_config: RpcSendTransactionConfig,
-    blockhash_failure_retries: Option<u16>,
+    retry_policy: &RetryPolicy,
+    sign: &mut SignTransaction<'_>,
+    on_signed: &mut dyn FnMut(&VersionedTransaction, Option<u64>),
 ) -> Result<Signature, anchor_client::ClientError> {
-    let mut retry_count: u16 = 0;
-    let blockhash_failure_retries = blockhash_failure_retries.unwrap_or(0);
-    let mut last_error = anchor_client::ClientError::SolanaClientError(SolanaClientError::from(
-        RpcError::RpcRequestError("send_transaction: unknown retry failure".to_string()),
-    ));
-    while retry_count <= blockhash_failure_retries {
-        let send_result = execute_prepared_transaction_internal(
+    let mut retry_state = retry_policy.start();
+    loop {
+        let send_result = sign_and_send_prepared_transaction(
             prepared_transaction,
             rpc_client,
+            sender_rpc_client,
             preflight_config,
+            sign,
+            on_signed,
         );
-        match send_result {
-            Ok(signature) => {
-                return Ok(signature);
-            }
+        let (err, error_class) = match send_result {
             Err(err) => {
-                last_error = anchor_client::ClientError::SolanaClientError(err);
-                if let anchor_client::ClientError::SolanaClientError(ce) = &last_error {
-                    let to_check_err: Option<&TransactionError> = match ce.kind() {
-                        ClientErrorKind::RpcError(RpcError::RpcResponseError {
-                            data:
-                                RpcResponseErrorData::SendTransactionPreflightFailure(
-                                    RpcSimulateTransactionResult {
-                                        err: transaction_error,
-                                        logs,
-                                        accounts,
-                                        ..
-                                    },
-                                ),
-                            ..
-                        }) => {
-                            debug!(
-                                "Failed to send transaction: {:?}, logs: {:?}, accounts: {:?}",
-                                transaction_error, logs, accounts
-                            );
-                            transaction_error.as_ref()
-                        }
-                        ClientErrorKind::RpcError(ForUser(message)) => {
-                            // unable to confirm transaction. This can happen in situations such as transaction expiration and insufficient fee-payer funds
-                            if message
-                                .to_lowercase()
-                                .contains("unable to confirm transaction")
-                            {
-                                Some(&TransactionError::BlockhashNotFound)
-                            } else {
-                                None
-                            }
-                        }
-                        ClientErrorKind::TransactionError(te) => Some(te),
-                        _ => None,
-                    };
-
-                    if let Some(tx_err) = to_check_err {
-                        if *tx_err == TransactionError::BlockhashNotFound {
-                            debug!(
-                                "Retried attempt #{}/{} to send transaction with error: {:?} ",
-                                retry_count, blockhash_failure_retries, tx_err
-                            );
-                            // retry
-                            retry_count += 1;
-                            continue;
-                        }
+                let error_class = classify_send_error(&err, prepared_transaction);
+                (err, error_class)
+            }
+            Ok((tx, last_valid_block_height, send_rpc_client)) => loop {
+                let err = match confirm_transaction_with_rebroadcast(
+                    rpc_client,
+                    send_rpc_client,
+                    &tx,
+                    preflight_config,
+                    last_valid_block_height,
+                    retry_policy.rebroadcast_interval,
+                ) {
+                    Ok(()) => return Ok(tx.signatures[0]),
+                    Err(err) => err,
+                };
+                let error_class = classify_confirm_error(&err, prepared_transaction);
+                if error_class != ErrorClass::Retryable {
+                    break (err, error_class);
+                }
+                // the transaction is not sent again, its status is awaited after the backoff
+                match retry_state.confirm_retry_after(error_class) {
+                    Some(delay) => {
+                        debug!(
+                            "Confirming transaction {} again in {:?} after error: {:?}",
+                            tx.signatures[0], delay, err
+                        );
+                        std::thread::sleep(delay);
                     }
-                    // No Error to retry, let's break the loop and use the last error
-                    break;
+                    None => return Err(send_failed_error(err, error_class, &retry_state)),
                 }
+            },
+        };
+        match retry_state.retry_after(error_class) {
+            Some(delay) => {
+                debug!(
+                    "Retrying attempt #{}/{} to send transaction in {:?} after {:?} error: {:?}",
+                    retry_state.attempts(),
+                    retry_policy.max_attempts,
+                    delay,
+                    error_class,
+                    err
+                );
+                std::thread::sleep(delay);
             }
+            None => return Err(send_failed_error(err, error_class, &retry_state)),
         }
     }
-    // Return last_error as-is so downstream `err.kind()` matches (e.g.
-    // log_execution's preflight-logs branch) keep working. Log the retry
-    // exhaustion context separately rather than rewrapping into a String
-    // variant. The while-condition-exit path (retry_count > limit) means
-    // retries were truly exhausted; `break` paths leave retry_count <= limit.
-    if retry_count > blockhash_failure_retries {
+}
+
+/// Loads the recent prioritization fees paid for the writable accounts and derives the compute unit price
+/// by the configured strategy. The fixed price is returned with no RPC call.
+pub fn get_compute_unit_price(
+    compute_unit_price: &ComputeUnitPrice,
+    writable_accounts: &[Pubkey],
+    rpc_client: &RpcClient,
+) -> Result<u64, SolanaClientError> {
+    if let ComputeUnitPrice::Fixed(price) = compute_unit_price {
+        return Ok(*price);
+    }
+    let recent_fees = rpc_client
+        .get_recent_prioritization_fees(writable_accounts)?
+        .iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect::<Vec<u64>>();
+    let price = compute_unit_price.from_recent_fees(&recent_fees);
+    debug!(
+        "get_compute_unit_price: strategy {:?}, {} recent fees for accounts {:?}, compute unit price {}",
+        compute_unit_price,
+        recent_fees.len(),
+        writable_accounts,
+        price
+    );
+    Ok(price)
+}
+
+fn resolve_compute_unit_price(
+    compute_budget: ComputeBudgetConfig,
+    writable_accounts: &[Pubkey],
+    rpc_client: &RpcClient,
+) -> Result<ComputeBudgetConfig, SolanaClientError> {
+    match compute_budget.compute_unit_price {
+        Some(compute_unit_price) if !compute_unit_price.is_fixed() => Ok(ComputeBudgetConfig {
+            compute_unit_price: Some(ComputeUnitPrice::Fixed(get_compute_unit_price(
+                &compute_unit_price,
+                writable_accounts,
+                rpc_client,
+            )?)),
+            ..compute_budget
+        }),
+        _ => Ok(compute_budget),
+    }
+}
+
+/// When the compute unit price of the transaction is derived from the recent prioritization fees,
+/// the fees are loaded for the writable accounts of the transaction and the price is set to the transaction.
+pub fn estimate_compute_unit_price(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<(), SolanaClientError> {
+    let compute_unit_price = match prepared_transaction
+        .compute_budget()
+        .and_then(|compute_budget| compute_budget.compute_unit_price)
+    {
+        Some(compute_unit_price) if !compute_unit_price.is_fixed() => compute_unit_price,
+        _ => return Ok(()),
+    };
+    let price = get_compute_unit_price(
+        &compute_unit_price,
+        &prepared_transaction.writable_accounts(),
+        rpc_client,
+    )?;
+    prepared_transaction
+        .set_compute_unit_price(price)
+        .map_err(|e| {
+            error!(
+                "estimate_compute_unit_price: cannot set compute unit price {}: {:?}",
+                price, e
+            );
+            SolanaClientError::from(ClientErrorKind::Custom(format!(
+                "Setting compute unit price error: {}",
+                e
+            )))
+        })
+}
+
+/// When the compute unit limit estimation is configured for the transaction, the transaction is simulated
+/// and the compute unit limit is set to the consumed units increased by the configured margin.
+pub fn estimate_compute_unit_limit(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+) -> Result<(), SolanaClientError> {
+    let estimation = if let Some(estimation) = prepared_transaction
+        .compute_budget()
+        .and_then(|compute_budget| compute_budget.compute_unit_limit_estimation)
+    {
+        estimation
+    } else {
+        return Ok(());
+    };
+
+    // no signatures are needed for the simulation, the blockhash is replaced by the RPC node
+    let simulation_result = rpc_client
+        .simulate_transaction_with_config(
+            &prepared_transaction.transaction,
+            estimation_simulation_config(),
+        )?
+        .value;
+    let compute_unit_limit = estimated_compute_unit_limit(estimation, simulation_result)?;
+    prepared_transaction
+        .set_compute_unit_limit(compute_unit_limit)
+        .map_err(|e| {
+            error!(
+                "estimate_compute_unit_limit: cannot set compute unit limit {}: {:?}",
+                compute_unit_limit, e
+            );
+            SolanaClientError::from(ClientErrorKind::Custom(format!(
+                "Setting compute unit limit error: {}",
+                e
+            )))
+        })
+}
+
+pub(crate) fn estimation_simulation_config() -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+pub(crate) fn estimated_compute_unit_limit(
+    estimation: ComputeUnitLimitEstimation,
+    simulation_result: RpcSimulateTransactionResult,
+) -> Result<u32, SolanaClientError> {
+    if let Some(err) = simulation_result.err {
         error!(
-            "Transaction ERR send_transaction: blockhash retry exhausted after {} attempt(s); last error: {:?}",
-            retry_count, last_error
+            "estimate_compute_unit_limit: transaction simulation failed: {:?}, logs: {:?}",
+            err, simulation_result.logs
         );
-    } else {
-        error!("Transaction ERR send_transaction: {:?}", last_error);
+        return Err(err.into());
     }
-    Err(last_error)
+    let units_consumed = simulation_result.units_consumed.ok_or_else(|| {
+        SolanaClientError::from(ClientErrorKind::Custom(
+            "estimate_compute_unit_limit: simulation does not report consumed units".to_string(),
+        ))
+    })?;
+    let compute_unit_limit = estimation.compute_unit_limit(units_consumed);
+    debug!(
+        "estimate_compute_unit_limit: units consumed {}, setting compute unit limit {}",
+        units_consumed, compute_unit_limit
+    );
+    Ok(compute_unit_limit)
 }
 
 pub fn execute_prepared_transaction(
@@ -415,20 +1448,26 @@ pub fn execute_prepared_transaction(
     preflight_config: RpcSendTransactionConfig,
     blockhash_commitment: CommitmentLevel,
 ) -> Result<Signature, anchor_client::ClientError> {
+    estimate_compute_unit_price(prepared_transaction, rpc_client)?;
+    estimate_compute_unit_limit(prepared_transaction, rpc_client)?;
     let rpc_client_blockhash = RpcClient::new_with_commitment(
         rpc_client.url(),
         CommitmentConfig {
             commitment: blockhash_commitment,
         },
     );
-    execute_prepared_transaction_internal(
+    execute_prepared_transaction_retry_internal(
         prepared_transaction,
         &rpc_client_blockhash,
+        None,
         preflight_config,
+        &RetryPolicy::no_retry(),
+        &mut sign_prepared_transaction,
+        &mut |_, _| {},
     ).map_err(|e|{
         error!("execute_prepared_transaction: error send_and_confirm transaction '{:?}', signers: '{:?}': {:?}",
                 prepared_transaction.transaction, prepared_transaction.signers.iter().map(|s| s.pubkey()), e);
-        e.into()
+        e
     })
 }
 
@@ -439,20 +1478,192 @@ pub fn execute_prepared_transaction_blockhash_retry(
     blockhash_commitment: CommitmentLevel,
     blockhash_failure_retries: Option<u16>,
 ) -> Result<Signature, anchor_client::ClientError> {
+    execute_prepared_transaction_with_sender(
+        prepared_transaction,
+        rpc_client,
+        None,
+        preflight_config,
+        blockhash_commitment,
+        &RetryPolicy::blockhash_not_found_retries(blockhash_failure_retries),
+    )
+}
+
+/// Sends the transaction through the sender RPC when provided, otherwise through the `rpc_client`.
+/// The transaction the RPC did not accept is sent again as the retry policy permits,
+/// the accepted one is re-signed with a fresh blockhash only when it expired with no status.
+pub fn execute_prepared_transaction_with_sender(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: &RetryPolicy,
+) -> Result<Signature, anchor_client::ClientError> {
+    execute_prepared_transaction_with_sender_internal(
+        prepared_transaction,
+        rpc_client,
+        sender_rpc_client,
+        preflight_config,
+        blockhash_commitment,
+        retry_policy,
+        &mut |_, _| {},
+    )
+}
+
+fn execute_prepared_transaction_with_sender_internal(
+    prepared_transaction: &mut PreparedTransaction,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: &RetryPolicy,
+    on_signed: &mut dyn FnMut(&VersionedTransaction, Option<u64>),
+) -> Result<Signature, anchor_client::ClientError> {
+    estimate_compute_unit_price(prepared_transaction, rpc_client)?;
+    estimate_compute_unit_limit(prepared_transaction, rpc_client)?;
     let rpc_client_blockhash = RpcClient::new_with_commitment(
         rpc_client.url(),
         CommitmentConfig {
             commitment: blockhash_commitment,
         },
     );
-    execute_prepared_transaction_retry_blockhash_internal(
+    execute_prepared_transaction_retry_internal(
         prepared_transaction,
         &rpc_client_blockhash,
+        sender_rpc_client,
+        preflight_config,
+        retry_policy,
+        &mut sign_prepared_transaction,
+        on_signed,
+    )
+}
+
+/// Sends the anchor request as [execute_prepared_transaction_with_sender]. The request signers are held
+/// by the anchor builder, for every send attempt the request is signed again by them with the latest blockhash
+/// loaded together with its last valid block height.
+pub fn execute_anchor_request_with_sender(
+    anchor_request: &dyn AnchorRequest,
+    rpc_client: &RpcClient,
+    sender_rpc_client: Option<&RpcClient>,
+    preflight_config: RpcSendTransactionConfig,
+    blockhash_commitment: CommitmentLevel,
+    retry_policy: &RetryPolicy,
+) -> Result<Signature, anchor_client::ClientError> {
+    let rpc_client_blockhash = RpcClient::new_with_commitment(
+        rpc_client.url(),
+        CommitmentConfig {
+            commitment: blockhash_commitment,
+        },
+    );
+    let sign_request =
+        |rpc_client: &RpcClient| -> Result<(VersionedTransaction, Option<u64>), SolanaClientError> {
+            let (latest_hash, last_valid_block_height) =
+                rpc_client.get_latest_blockhash_with_commitment(rpc_client.commitment())?;
+            let transaction = anchor_request
+                .signed_request_transaction(latest_hash)
+                .map_err(|err| match err {
+                    anchor_client::ClientError::SolanaClientError(err) => err,
+                    err => SolanaClientError::from(ClientErrorKind::Custom(format!(
+                        "Signing anchor request error: {}",
+                        err
+                    ))),
+                })?;
+            Ok((transaction.into(), Some(last_valid_block_height)))
+        };
+    let (transaction, last_valid_block_height) = sign_request(&rpc_client_blockhash)?;
+    let mut prepared_transaction = PreparedTransaction::new_no_signers(transaction.clone());
+    // the first attempt sends the request signed above, the retries sign it again
+    let mut signed_request = Some((transaction, last_valid_block_height));
+    execute_prepared_transaction_retry_internal(
+        &mut prepared_transaction,
+        &rpc_client_blockhash,
+        sender_rpc_client,
+        preflight_config,
+        retry_policy,
+        &mut |prepared_transaction, rpc_client| {
+            let (transaction, last_valid_block_height) = match signed_request.take() {
+                Some(signed_request) => signed_request,
+                None => sign_request(rpc_client)?,
+            };
+            prepared_transaction.transaction = transaction.clone();
+            Ok((transaction, last_valid_block_height))
+        },
+        &mut |_, _| {},
+    )
+}
+
+/// Sends the transaction with the signatures collected offline, the transaction is not signed again.
+/// It fails when some of the required signatures are missing.
+pub fn execute_signed_transaction(
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+) -> Result<Signature, anchor_client::ClientError> {
+    execute_signed_transaction_with_sender(
+        prepared_transaction,
+        rpc_client,
+        rpc_client,
         preflight_config,
-        blockhash_failure_retries,
+        None,
     )
 }
 
+/// Sends the transaction signed beforehand (e.g., offline) through the sender RPC and confirms it with rebroadcast,
+/// see [confirm_transaction_with_rebroadcast]. The transaction cannot be re-signed, its expiry is a fatal error.
+pub fn execute_signed_transaction_with_sender(
+    prepared_transaction: &PreparedTransaction,
+    rpc_client: &RpcClient,
+    sender_rpc_client: &RpcClient,
+    preflight_config: RpcSendTransactionConfig,
+    rebroadcast_interval: Option<Duration>,
+) -> Result<Signature, anchor_client::ClientError> {
+    prepared_transaction
+        .check_signatures_complete()
+        .map_err(|e| {
+            error!("execute_signed_transaction: {}", e);
+            SolanaClientError::from(ClientErrorKind::Custom(e.to_string()))
+        })?;
+    let transaction = &prepared_transaction.transaction;
+    // the blockhash is not older than the current block, it cannot be valid longer than the latest blockhash
+    let last_valid_block_height = if durable_nonce_account(transaction).is_some() {
+        None
+    } else {
+        Some(rpc_client.get_block_height()? + MAX_PROCESSING_AGE as u64)
+    };
+    let send_rpc_client =
+        send_transaction_with_sender(rpc_client, sender_rpc_client, transaction, preflight_config)
+            .map_err(|e| {
+                error!(
+                    "execute_signed_transaction: error sending transaction '{:?}': {:?}",
+                    transaction, e
+                );
+                e
+            })?;
+    confirm_transaction_with_rebroadcast(
+        rpc_client,
+        send_rpc_client,
+        transaction,
+        preflight_config,
+        last_valid_block_height,
+        rebroadcast_interval,
+    )
+    .map_err(|e| {
+        error!(
+            "execute_signed_transaction: error confirming transaction '{:?}': {:?}",
+            transaction, e
+        );
+        if is_expired_transaction_error(&e) {
+            SolanaClientError::from(ClientErrorKind::Custom(format!(
+                "Signed transaction {} expired, it cannot be re-signed with a new blockhash",
+                transaction.signatures[0]
+            )))
+        } else {
+            e
+        }
+    })?;
+    Ok(transaction.signatures[0])
+}
+
 pub fn simulate_prepared_transaction(
     prepared_transaction: &mut PreparedTransaction,
     rpc_client: &RpcClient,
@@ -465,7 +1676,7 @@ pub fn simulate_prepared_transaction(
             commitment: blockhash_commitment,
         },
     );
-    let latest_blockhash = rpc_client_blockhash.get_latest_blockhash()?;
+    let latest_blockhash = get_transaction_blockhash(prepared_transaction, &rpc_client_blockhash)?;
     let tx = if simulate_config.sig_verify {
         prepared_transaction.sign(latest_blockhash).map_err(|e| {
             error!(
@@ -481,7 +1692,7 @@ pub fn simulate_prepared_transaction(
     rpc_client.simulate_transaction_with_config(tx, simulate_config)
 }
 
-fn warn_text_simulate_print(simulate: bool, print: bool) {
+pub(crate) fn warn_text_simulate_print(simulate: bool, print: bool) {
     if simulate {
         warn!("Simulation mode: transactions will not be executed, only simulated.");
     }
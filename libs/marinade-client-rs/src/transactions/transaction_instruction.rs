@@ -1,6 +1,10 @@
+use crate::error::MarinadeClientError;
+use crate::transactions::transaction_builder::TransactionBuildError;
 use anchor_lang::prelude::{AnchorDeserialize, AnchorSerialize};
 use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
 
 // Set of struct wrappers that can be used to deserialize instruction.
 // For marinade client it's the base64 format which is used in multisig like SPL Governance.
@@ -15,6 +19,16 @@ pub struct TransactionInstruction {
     pub data: Vec<u8>,
 }
 
+impl TransactionInstruction {
+    /// Decodes the instruction from the base64 format printed by [print_base64].
+    pub fn from_base64(base64: &str) -> Result<Self, MarinadeClientError> {
+        let data = anchor_lang::__private::base64::decode(base64.trim())
+            .map_err(|e| MarinadeClientError::deserialization("base64 instruction", e))?;
+        Self::try_from_slice(&data)
+            .map_err(|e| MarinadeClientError::deserialization("transaction instruction", e))
+    }
+}
+
 impl From<&TransactionInstruction> for Instruction {
     fn from(tx: &TransactionInstruction) -> Instruction {
         Instruction {
@@ -51,9 +65,9 @@ impl From<&AccountMeta> for TransactionAccount {
     }
 }
 
-pub fn print_base64(instructions: &Vec<Instruction>) -> anyhow::Result<()> {
-    for instruction in instructions {
-        let transaction_instruction = TransactionInstruction {
+impl From<&Instruction> for TransactionInstruction {
+    fn from(instruction: &Instruction) -> TransactionInstruction {
+        TransactionInstruction {
             program_id: instruction.program_id,
             accounts: instruction
                 .accounts
@@ -61,12 +75,258 @@ pub fn print_base64(instructions: &Vec<Instruction>) -> anyhow::Result<()> {
                 .map(TransactionAccount::from)
                 .collect(),
             data: instruction.data.clone(),
+        }
+    }
+}
+
+/// Exported instructions in the format to be loaded to a multisig, e.g., as a proposal transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigExportItem {
+    pub title: String,
+    pub data: String,
+}
+
+/// Format of the instructions to be loaded to a multisig.
+pub trait MultisigExport {
+    fn export(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<MultisigExportItem>, MarinadeClientError>;
+}
+
+/// Base64 of the Borsh serialized [TransactionInstruction] per instruction,
+/// the instruction format of the SPL Governance proposal transactions.
+pub struct SplGovernanceExport;
+
+impl MultisigExport for SplGovernanceExport {
+    fn export(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<MultisigExportItem>, MarinadeClientError> {
+        instructions
+            .iter()
+            .map(|instruction| {
+                Ok(MultisigExportItem {
+                    title: format!("program: {}", instruction.program_id),
+                    data: anchor_lang::__private::base64::encode(
+                        TransactionInstruction::from(instruction).try_to_vec()?,
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Base64 of the Squads vault transaction message of all the instructions,
+/// the vault is the payer and the signing authority of the message.
+pub struct SquadsVaultExport {
+    pub vault: Pubkey,
+}
+
+impl MultisigExport for SquadsVaultExport {
+    fn export(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<MultisigExportItem>, MarinadeClientError> {
+        let message = Message::new(instructions, Some(&self.vault));
+        Ok(vec![MultisigExportItem {
+            title: format!("squads vault {} transaction message", self.vault),
+            data: anchor_lang::__private::base64::encode(squads_transaction_message(&message)?),
+        }])
+    }
+}
+
+/// Serializes the message in the Squads `TransactionMessage` layout where the vectors
+/// are prefixed with u8 length (u16 for the instruction data) instead of the compact-u16.
+fn squads_transaction_message(message: &Message) -> Result<Vec<u8>, TransactionBuildError> {
+    fn u8_len<T>(items: &[T], name: &str) -> Result<u8, TransactionBuildError> {
+        u8::try_from(items.len()).map_err(|_| {
+            TransactionBuildError::MessageCompile(format!(
+                "Squads transaction message supports max {} {}, got {}",
+                u8::MAX,
+                name,
+                items.len()
+            ))
+        })
+    }
+
+    let header = &message.header;
+    let num_signers = header.num_required_signatures;
+    let num_writable_signers = num_signers - header.num_readonly_signed_accounts;
+    let num_writable_non_signers = u8_len(&message.account_keys, "accounts")?
+        - num_signers
+        - header.num_readonly_unsigned_accounts;
+
+    let mut data = vec![num_signers, num_writable_signers, num_writable_non_signers];
+    data.push(u8_len(&message.account_keys, "accounts")?);
+    for account_key in &message.account_keys {
+        data.extend_from_slice(account_key.as_ref());
+    }
+    data.push(u8_len(&message.instructions, "instructions")?);
+    for instruction in &message.instructions {
+        data.push(instruction.program_id_index);
+        data.push(u8_len(&instruction.accounts, "instruction accounts")?);
+        data.extend_from_slice(&instruction.accounts);
+        let data_len = u16::try_from(instruction.data.len()).map_err(|_| {
+            TransactionBuildError::MessageCompile(format!(
+                "Squads transaction message supports max {} bytes of instruction data, got {}",
+                u16::MAX,
+                instruction.data.len()
+            ))
+        })?;
+        data.extend_from_slice(&data_len.to_le_bytes());
+        data.extend_from_slice(&instruction.data);
+    }
+    // no address table lookups
+    data.push(0);
+    Ok(data)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageEncoding {
+    Base58,
+    Base64,
+}
+
+/// Serialized legacy [Message] of all the instructions.
+pub struct MessageExport {
+    pub payer: Option<Pubkey>,
+    pub encoding: MessageEncoding,
+}
+
+impl MultisigExport for MessageExport {
+    fn export(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<MultisigExportItem>, MarinadeClientError> {
+        let message = Message::new(instructions, self.payer.as_ref());
+        let (encoding, data) = match self.encoding {
+            MessageEncoding::Base58 => (
+                "base58",
+                solana_sdk::bs58::encode(message.serialize()).into_string(),
+            ),
+            MessageEncoding::Base64 => (
+                "base64",
+                anchor_lang::__private::base64::encode(message.serialize()),
+            ),
         };
-        println!(
-            "program: {}\n  {}",
-            instruction.program_id,
-            anchor_lang::__private::base64::encode(transaction_instruction.try_to_vec()?)
-        );
+        Ok(vec![MultisigExportItem {
+            title: format!("message ({})", encoding),
+            data,
+        }])
+    }
+}
+
+/// Multisig export formats selectable by name, see [MultisigExportFormat::FORMATS].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultisigExportFormat {
+    SplGovernance,
+    Squads,
+    MessageBase58,
+    MessageBase64,
+}
+
+impl MultisigExportFormat {
+    pub const FORMATS: [&'static str; 4] = [
+        "spl-governance",
+        "squads",
+        "message-base58",
+        "message-base64",
+    ];
+
+    /// Squads format requires the vault, for the message formats the vault is the payer when provided.
+    pub fn exporter(
+        &self,
+        vault: Option<Pubkey>,
+    ) -> Result<Box<dyn MultisigExport>, MarinadeClientError> {
+        Ok(match self {
+            Self::SplGovernance => Box::new(SplGovernanceExport),
+            Self::Squads => Box::new(SquadsVaultExport {
+                vault: vault.ok_or_else(|| {
+                    TransactionBuildError::MessageCompile(
+                        "Squads export requires the vault address".to_string(),
+                    )
+                })?,
+            }),
+            Self::MessageBase58 => Box::new(MessageExport {
+                payer: vault,
+                encoding: MessageEncoding::Base58,
+            }),
+            Self::MessageBase64 => Box::new(MessageExport {
+                payer: vault,
+                encoding: MessageEncoding::Base64,
+            }),
+        })
+    }
+}
+
+impl FromStr for MultisigExportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "spl-governance" => Ok(Self::SplGovernance),
+            "squads" => Ok(Self::Squads),
+            "message-base58" => Ok(Self::MessageBase58),
+            "message-base64" => Ok(Self::MessageBase64),
+            _ => Err(format!(
+                "Unknown multisig export format '{}', expected one of {:?}",
+                format,
+                Self::FORMATS
+            )),
+        }
+    }
+}
+
+pub fn print_export(
+    instructions: &[Instruction],
+    export: &dyn MultisigExport,
+) -> Result<(), MarinadeClientError> {
+    for item in export.export(instructions)? {
+        println!("{}\n  {}", item.title, item.data);
     }
     Ok(())
 }
+
+pub fn print_base64(instructions: &Vec<Instruction>) -> Result<(), MarinadeClientError> {
+    print_export(instructions, &SplGovernanceExport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_squads_transaction_message() {
+        let vault = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let writable = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[7, 8, 9],
+            vec![
+                AccountMeta::new_readonly(vault, true),
+                AccountMeta::new(writable, false),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&vault));
+        let data = squads_transaction_message(&message).unwrap();
+
+        // vault signer, writable account, program
+        assert_eq!(&data[..4], &[1, 1, 1, 3]);
+        assert_eq!(&data[4..36], vault.as_ref());
+        assert_eq!(&data[36..68], writable.as_ref());
+        assert_eq!(&data[68..100], program_id.as_ref());
+        // one instruction: program index, 2 accounts, 3 bytes of data with u16 length
+        assert_eq!(&data[100..], &[1, 2, 2, 0, 1, 3, 0, 7, 8, 9, 0]);
+    }
+
+    #[test]
+    fn test_multisig_export_format() {
+        for format in MultisigExportFormat::FORMATS {
+            assert!(MultisigExportFormat::from_str(format).is_ok());
+        }
+        assert!(MultisigExportFormat::from_str("unknown").is_err());
+        assert!(MultisigExportFormat::Squads.exporter(None).is_err());
+    }
+}
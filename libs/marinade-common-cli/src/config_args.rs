@@ -42,6 +42,56 @@ pub fn simulate_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(SIMULATE_ARG.help)
 }
 
+pub const YES_ARG: ArgConstant<'static> = ArgConstant {
+    name: "yes",
+    long: "yes",
+    help:
+        "Execution is confirmed with no prompt. The estimated cost of the transactions is printed \
+           and confirmation is asked before sending, in non-interactive mode the flag is required.",
+};
+pub fn yes_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(YES_ARG.name)
+        .long(YES_ARG.long)
+        .short("y")
+        .takes_value(false)
+        .conflicts_with(SIMULATE_ARG.name)
+        .help(YES_ARG.help)
+}
+
+pub const ACCOUNTS_DIFF_ARG: ArgConstant<'static> = ArgConstant {
+    name: "accounts_diff",
+    long: "accounts-diff",
+    help: "With simulation, the writable accounts changed by the transactions are printed \
+           (lamports, token balances, mint supply and Marinade state fields) in the format.",
+};
+pub const ACCOUNTS_DIFF_FORMATS: [&str; 2] = ["text", "json"];
+pub fn accounts_diff_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ACCOUNTS_DIFF_ARG.name)
+        .long(ACCOUNTS_DIFF_ARG.long)
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&ACCOUNTS_DIFF_FORMATS)
+        .requires(SIMULATE_ARG.name)
+        .help(ACCOUNTS_DIFF_ARG.help)
+}
+
+pub const DECODE_FORMAT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "decode_format",
+    long: "decode-format",
+    help: "Format of the decoded base64 instructions: Marinade instruction name, args \
+           and the account roles.",
+};
+pub const DECODE_FORMATS: [&str; 2] = ["text", "json"];
+pub fn decode_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(DECODE_FORMAT_ARG.name)
+        .long(DECODE_FORMAT_ARG.long)
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&DECODE_FORMATS)
+        .default_value("text")
+        .help(DECODE_FORMAT_ARG.help)
+}
+
 pub const SKIP_PREFLIGHT_ARG: ArgConstant<'static> = ArgConstant {
     name: "skip_preflight",
     long: "skip-preflight",
@@ -88,6 +138,46 @@ pub fn sender_rpc_url_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(SENDER_RPC_URL_ARG.help)
 }
 
+pub const SENDER_TIP_ARG: ArgConstant<'static> = ArgConstant {
+    name: "sender_tip",
+    long: "sender-tip",
+    help: "Lamports transferred from the fee payer to the tip account within every transaction. \
+           When not provided, no tip instruction is added.",
+};
+pub fn sender_tip_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SENDER_TIP_ARG.name)
+        .long(SENDER_TIP_ARG.long)
+        .value_name("LAMPORTS")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .requires(SENDER_TIP_ACCOUNT_ARG.name)
+        .help(SENDER_TIP_ARG.help)
+}
+
+pub const SENDER_TIP_ACCOUNT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "sender_tip_account",
+    long: "sender-tip-account",
+    help: "Account the tip is transferred to, e.g., one of the Jito tip accounts.",
+};
+pub fn sender_tip_account_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SENDER_TIP_ACCOUNT_ARG.name)
+        .long(SENDER_TIP_ACCOUNT_ARG.long)
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .validator(input_validators::is_valid_pubkey)
+        .requires(SENDER_TIP_ARG.name)
+        .help(SENDER_TIP_ACCOUNT_ARG.help)
+}
+
+/// Arguments to send the transactions through the sender RPC with an optional tip.
+pub fn sender_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        sender_rpc_url_arg(),
+        sender_tip_arg(),
+        sender_tip_account_arg(),
+    ]
+}
+
 pub const RENT_PAYER_ARG: ArgConstant<'static> = ArgConstant {
     name: "rent_payer",
     long: "rent-payer",
@@ -104,6 +194,38 @@ pub fn rent_payer_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(RENT_PAYER_ARG.help)
 }
 
+pub const INSTRUCTIONS_FILE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "instructions_file",
+    long: "instructions-file",
+    help:
+        "File with the instructions to execute, '-' reads stdin. One base64 instruction per line \
+           as printed by --print, or a JSON array of the base64 instructions \
+           or of objects with program_id, accounts and base64 data.",
+};
+pub fn instructions_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(INSTRUCTIONS_FILE_ARG.name)
+        .long(INSTRUCTIONS_FILE_ARG.long)
+        .value_name("PATH")
+        .takes_value(true)
+        .help(INSTRUCTIONS_FILE_ARG.help)
+}
+
+pub const SIGNER_ARG: ArgConstant<'static> = ArgConstant {
+    name: "signer",
+    long: "signer",
+    help: "Signer required by the executed instructions, the argument may be repeated.",
+};
+pub fn signer_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SIGNER_ARG.name)
+        .long(SIGNER_ARG.long)
+        .value_name("KEYPAIR")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .validator(input_validators::is_valid_signer)
+        .help(SIGNER_ARG.help)
+}
+
 pub const VALIDATOR_MANAGER_ARG: ArgConstant<'static> = ArgConstant {
     name: "validator_manager_authority",
     long: "validator-manager-authority",
@@ -164,6 +286,45 @@ pub fn print_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(PRINT_ARG.help)
 }
 
+pub const PRINT_FORMAT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "print_format",
+    long: "print-format",
+    help: "Format of the --print output: 'spl-governance' base64 instructions for SPL Gov, \
+           'squads' vault transaction message (requires --multisig-vault) or the serialized transaction \
+           message 'message-base58', 'message-base64'.",
+};
+pub const PRINT_FORMATS: [&str; 4] = [
+    "spl-governance",
+    "squads",
+    "message-base58",
+    "message-base64",
+];
+pub fn print_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(PRINT_FORMAT_ARG.name)
+        .long(PRINT_FORMAT_ARG.long)
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&PRINT_FORMATS)
+        .default_value("spl-governance")
+        .help(PRINT_FORMAT_ARG.help)
+}
+
+pub const MULTISIG_VAULT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "multisig_vault",
+    long: "multisig-vault",
+    help:
+        "Multisig vault executing the printed instructions, it is the payer of the printed message.",
+};
+pub fn multisig_vault_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(MULTISIG_VAULT_ARG.name)
+        .long(MULTISIG_VAULT_ARG.long)
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .validator(input_validators::is_valid_pubkey)
+        .required_if(PRINT_FORMAT_ARG.name, "squads")
+        .help(MULTISIG_VAULT_ARG.help)
+}
+
 pub const WITH_COMPUTE_UNIT_PRICE_ARG: ArgConstant<'static> = ArgConstant {
     name: "with_compute_unit_price",
     long: "with-compute-unit-price",
@@ -178,6 +339,120 @@ pub fn with_compute_unit_price<'a, 'b>() -> Arg<'a, 'b> {
         .default_value("0")
 }
 
+pub const WITH_COMPUTE_UNIT_LIMIT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "with_compute_unit_limit",
+    long: "with-compute-unit-limit",
+    help: "Set compute unit limit for transaction. When not provided, the cluster default limit is used.",
+};
+pub fn with_compute_unit_limit<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(WITH_COMPUTE_UNIT_LIMIT_ARG.name)
+        .value_name("COMPUTE-UNIT-LIMIT")
+        .takes_value(true)
+        .long(WITH_COMPUTE_UNIT_LIMIT_ARG.long)
+        .validator(input_validators::is_parsable::<u32>)
+        .help(WITH_COMPUTE_UNIT_LIMIT_ARG.help)
+}
+
+pub const ESTIMATE_COMPUTE_UNIT_LIMIT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "estimate_compute_unit_limit",
+    long: "estimate-compute-unit-limit",
+    help: "Transactions are simulated before sending and the compute unit limit is set to the consumed units \
+           increased by the provided safety margin in percents.",
+};
+pub fn estimate_compute_unit_limit_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ESTIMATE_COMPUTE_UNIT_LIMIT_ARG.name)
+        .long(ESTIMATE_COMPUTE_UNIT_LIMIT_ARG.long)
+        .value_name("MARGIN-PERCENT")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u32>)
+        .conflicts_with(WITH_COMPUTE_UNIT_LIMIT_ARG.name)
+        .help(ESTIMATE_COMPUTE_UNIT_LIMIT_ARG.help)
+}
+
+pub const PRIORITY_FEE_STRATEGY_ARG: ArgConstant<'static> = ArgConstant {
+    name: "priority_fee_strategy",
+    long: "priority-fee-strategy",
+    help: "Strategy to define the compute unit price. 'fixed' uses --with-compute-unit-price, \
+           'percentile' takes the --priority-fee-percentile of the recent prioritization fees \
+           paid for the writable accounts of the transaction, 'capped-percentile' clamps the percentile \
+           to the --min-compute-unit-price and --max-compute-unit-price.",
+};
+pub const PRIORITY_FEE_STRATEGIES: [&str; 3] = ["fixed", "percentile", "capped-percentile"];
+pub fn priority_fee_strategy_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(PRIORITY_FEE_STRATEGY_ARG.name)
+        .long(PRIORITY_FEE_STRATEGY_ARG.long)
+        .value_name("STRATEGY")
+        .takes_value(true)
+        .possible_values(&PRIORITY_FEE_STRATEGIES)
+        .default_value("fixed")
+        .help(PRIORITY_FEE_STRATEGY_ARG.help)
+}
+
+pub const PRIORITY_FEE_PERCENTILE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "priority_fee_percentile",
+    long: "priority-fee-percentile",
+    help: "Percentile (0-100) of the recent prioritization fees used as the compute unit price.",
+};
+pub fn priority_fee_percentile_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(PRIORITY_FEE_PERCENTILE_ARG.name)
+        .long(PRIORITY_FEE_PERCENTILE_ARG.long)
+        .value_name("PERCENTILE")
+        .takes_value(true)
+        .validator(is_percentile)
+        .default_value("75")
+        .help(PRIORITY_FEE_PERCENTILE_ARG.help)
+}
+
+pub const MIN_COMPUTE_UNIT_PRICE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "min_compute_unit_price",
+    long: "min-compute-unit-price",
+    help: "Minimal compute unit price for the 'capped-percentile' priority fee strategy.",
+};
+pub fn min_compute_unit_price_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(MIN_COMPUTE_UNIT_PRICE_ARG.name)
+        .long(MIN_COMPUTE_UNIT_PRICE_ARG.long)
+        .value_name("COMPUTE-UNIT-PRICE")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .default_value("0")
+        .help(MIN_COMPUTE_UNIT_PRICE_ARG.help)
+}
+
+pub const MAX_COMPUTE_UNIT_PRICE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "max_compute_unit_price",
+    long: "max-compute-unit-price",
+    help: "Maximal compute unit price for the 'capped-percentile' priority fee strategy.",
+};
+pub fn max_compute_unit_price_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(MAX_COMPUTE_UNIT_PRICE_ARG.name)
+        .long(MAX_COMPUTE_UNIT_PRICE_ARG.long)
+        .value_name("COMPUTE-UNIT-PRICE")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .required_if(PRIORITY_FEE_STRATEGY_ARG.name, "capped-percentile")
+        .help(MAX_COMPUTE_UNIT_PRICE_ARG.help)
+}
+
+/// Arguments to configure the priority fee strategy of the transactions.
+pub fn priority_fee_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        priority_fee_strategy_arg(),
+        priority_fee_percentile_arg(),
+        min_compute_unit_price_arg(),
+        max_compute_unit_price_arg(),
+    ]
+}
+
+fn is_percentile(value: String) -> Result<(), String> {
+    match value.parse::<u8>() {
+        Ok(percentile) if percentile <= 100 => Ok(()),
+        _ => Err(format!(
+            "Percentile must be a number between 0 and 100, got: {}",
+            value
+        )),
+    }
+}
+
 pub const BLOCKHASH_NOT_FOUND_RETRIES_ARG: ArgConstant<'static> = ArgConstant {
     name: "blockhash_not_found_retries",
     long: "blockhash-not-found-retries",
@@ -192,3 +467,136 @@ pub fn blockhash_not_found_retries_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(BLOCKHASH_NOT_FOUND_RETRIES_ARG.help)
         .default_value("0")
 }
+
+pub const RETRY_MAX_ATTEMPTS_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_max_attempts",
+    long: "retry-max-attempts",
+    help: "Maximal number of attempts to send a transaction, the first attempt included. \
+           When not provided, all configured retries are permitted, \
+           i.e., one attempt more than the transient and blockhash not found retries.",
+};
+pub fn retry_max_attempts_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_MAX_ATTEMPTS_ARG.name)
+        .long(RETRY_MAX_ATTEMPTS_ARG.long)
+        .value_name("NUMBER")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u32>)
+        .help(RETRY_MAX_ATTEMPTS_ARG.help)
+}
+
+pub const RETRY_TRANSIENT_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_transient",
+    long: "retry-transient",
+    help: "Number of retries after transient errors, e.g., rate limiting (HTTP 429), node behind, \
+           connection reset or account in use.",
+};
+pub fn retry_transient_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_TRANSIENT_ARG.name)
+        .long(RETRY_TRANSIENT_ARG.long)
+        .value_name("NUMBER")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u32>)
+        .default_value("0")
+        .help(RETRY_TRANSIENT_ARG.help)
+}
+
+pub const RETRY_BACKOFF_MS_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_backoff_ms",
+    long: "retry-backoff-ms",
+    help: "Backoff in milliseconds before the first retry after a transient error, doubled for every next retry.",
+};
+pub fn retry_backoff_ms_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_BACKOFF_MS_ARG.name)
+        .long(RETRY_BACKOFF_MS_ARG.long)
+        .value_name("MILLISECONDS")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .default_value("500")
+        .help(RETRY_BACKOFF_MS_ARG.help)
+}
+
+pub const RETRY_MAX_BACKOFF_MS_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_max_backoff_ms",
+    long: "retry-max-backoff-ms",
+    help: "Maximal backoff in milliseconds between retries.",
+};
+pub fn retry_max_backoff_ms_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_MAX_BACKOFF_MS_ARG.name)
+        .long(RETRY_MAX_BACKOFF_MS_ARG.long)
+        .value_name("MILLISECONDS")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .default_value("10000")
+        .help(RETRY_MAX_BACKOFF_MS_ARG.help)
+}
+
+pub const RETRY_JITTER_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_jitter",
+    long: "retry-jitter",
+    help: "Fraction of the backoff (0.0 - 1.0) randomly added to or subtracted from it.",
+};
+pub fn retry_jitter_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_JITTER_ARG.name)
+        .long(RETRY_JITTER_ARG.long)
+        .value_name("FRACTION")
+        .takes_value(true)
+        .validator(is_fraction)
+        .default_value("0.2")
+        .help(RETRY_JITTER_ARG.help)
+}
+
+pub const RETRY_DEADLINE_SECS_ARG: ArgConstant<'static> = ArgConstant {
+    name: "retry_deadline_secs",
+    long: "retry-deadline-secs",
+    help:
+        "No retry of a transaction is started after this number of seconds from its first attempt.",
+};
+pub fn retry_deadline_secs_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(RETRY_DEADLINE_SECS_ARG.name)
+        .long(RETRY_DEADLINE_SECS_ARG.long)
+        .value_name("SECONDS")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .help(RETRY_DEADLINE_SECS_ARG.help)
+}
+
+pub const REBROADCAST_INTERVAL_MS_ARG: ArgConstant<'static> = ArgConstant {
+    name: "rebroadcast_interval_ms",
+    long: "rebroadcast-interval-ms",
+    help: "Sent transaction is rebroadcast in this interval (milliseconds) until it is confirmed \
+           or its blockhash expires. \
+           When not provided, the transaction is sent once and its status is awaited.",
+};
+pub fn rebroadcast_interval_ms_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(REBROADCAST_INTERVAL_MS_ARG.name)
+        .long(REBROADCAST_INTERVAL_MS_ARG.long)
+        .value_name("MILLISECONDS")
+        .takes_value(true)
+        .validator(input_validators::is_parsable::<u64>)
+        .help(REBROADCAST_INTERVAL_MS_ARG.help)
+}
+
+/// Arguments to configure the retry policy of sending the transactions,
+/// the retries of expired transactions are configured by [blockhash_not_found_retries_arg].
+pub fn retry_policy_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        blockhash_not_found_retries_arg(),
+        retry_max_attempts_arg(),
+        retry_transient_arg(),
+        retry_backoff_ms_arg(),
+        retry_max_backoff_ms_arg(),
+        retry_jitter_arg(),
+        retry_deadline_secs_arg(),
+        rebroadcast_interval_ms_arg(),
+    ]
+}
+
+fn is_fraction(value: String) -> Result<(), String> {
+    match value.parse::<f64>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(()),
+        _ => Err(format!(
+            "Value must be a number between 0.0 and 1.0, got: {}",
+            value
+        )),
+    }
+}
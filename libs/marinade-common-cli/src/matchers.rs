@@ -1,14 +1,27 @@
+use crate::config_args::{
+    BLOCKHASH_NOT_FOUND_RETRIES_ARG, ESTIMATE_COMPUTE_UNIT_LIMIT_ARG, MAX_COMPUTE_UNIT_PRICE_ARG,
+    MIN_COMPUTE_UNIT_PRICE_ARG, PRIORITY_FEE_PERCENTILE_ARG, PRIORITY_FEE_STRATEGIES,
+    PRIORITY_FEE_STRATEGY_ARG, REBROADCAST_INTERVAL_MS_ARG, RETRY_BACKOFF_MS_ARG,
+    RETRY_DEADLINE_SECS_ARG, RETRY_JITTER_ARG, RETRY_MAX_ATTEMPTS_ARG, RETRY_MAX_BACKOFF_MS_ARG,
+    RETRY_TRANSIENT_ARG, SENDER_TIP_ACCOUNT_ARG, SENDER_TIP_ARG, WITH_COMPUTE_UNIT_LIMIT_ARG,
+    WITH_COMPUTE_UNIT_PRICE_ARG,
+};
 use anyhow::anyhow;
 use clap::ArgMatches;
-use dynsigner::{PubkeyOrKeypair, PubkeyOrSigner};
+use dynsigner::{PubkeyOrKeypair, PubkeyOrSigner, ThreadSafeSigner};
 use log::debug;
+use marinade_client_rs::transactions::compute_budget::{ComputeBudgetConfig, ComputeUnitPrice};
+use marinade_client_rs::transactions::retry_policy::RetryPolicy;
+use marinade_client_rs::transactions::sender::TipConfig;
 use solana_clap_utils::input_parsers::pubkey_of_signer;
-use solana_clap_utils::keypair::{keypair_from_path, signer_from_path};
+use solana_clap_utils::keypair::{
+    keypair_from_path, parse_signer_source, signer_from_path, SignerSourceKind,
+};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
-use std::{str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 // Getting keypair from the matched name as the keypair path argument, or returns the default signer
 pub fn keypair_from_path_or_default(
@@ -39,18 +52,16 @@ pub fn keypair_from_path_or_default(
 pub fn signer_from_path_or_default(
     matches: &ArgMatches<'_>,
     name: &str,
-    default_signer: &Arc<dyn Signer>,
+    default_signer: &Arc<dyn Signer + Send + Sync>,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
-) -> anyhow::Result<Arc<dyn Signer>> {
+) -> anyhow::Result<Arc<dyn Signer + Send + Sync>> {
     if let Some(location) = matches.value_of(name) {
-        Ok(Arc::from(
-            signer_from_path(matches, location, name, wallet_manager)
-                .map_err(|e| {
-                    debug!("signer_from_path_or_default failed: location {}, keypair name: {}, matches: {:?}: {:?}",
-                        location, name, matches, e);
-                    anyhow!("{}: arg name: {}, location: {}", e, name, location)
-                })?,
-        ))
+        Ok(thread_safe_signer_from_path(matches, location, name, wallet_manager)
+            .map_err(|e| {
+                debug!("signer_from_path_or_default failed: location {}, keypair name: {}, matches: {:?}: {:?}",
+                    location, name, matches, e);
+                anyhow!("{}: arg name: {}, location: {}", e, name, location)
+            })?)
     } else {
         debug!(
             "argument '{}' not provided; using default signer {}",
@@ -61,6 +72,22 @@ pub fn signer_from_path_or_default(
     }
 }
 
+/// Loads the signer that can be shared between threads. The keypair (file, stdin or prompt) is loaded as is,
+/// the hardware wallet and presigner signers are wrapped by [ThreadSafeSigner] and sign only at the current thread.
+fn thread_safe_signer_from_path(
+    matches: &ArgMatches<'_>,
+    location: &str,
+    name: &str,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<Arc<dyn Signer + Send + Sync>, Box<dyn std::error::Error>> {
+    match parse_signer_source(location)?.kind {
+        SignerSourceKind::Usb(_) | SignerSourceKind::Pubkey(_) => Ok(Arc::new(
+            ThreadSafeSigner::new(signer_from_path(matches, location, name, wallet_manager)?),
+        )),
+        _ => Ok(Arc::new(keypair_from_path(matches, location, name, false)?)),
+    }
+}
+
 /// Getting pubkey from the matched name or load it from the signer data, when not provided, return an error
 pub fn pubkey_or_of_signer(
     matches: &ArgMatches<'_>,
@@ -115,6 +142,29 @@ pub fn process_multiple_pubkeys(
     Ok(value_pubkeys)
 }
 
+/// Loading all the signers of the multiple value argument, no signer is returned when the argument is not provided
+pub fn signers_from_paths(
+    matches: &ArgMatches<'_>,
+    name: &str,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> anyhow::Result<Vec<Arc<dyn Signer + Send + Sync>>> {
+    let mut signers: Vec<Arc<dyn Signer + Send + Sync>> = vec![];
+    if let Some(locations) = matches.values_of(name) {
+        for location in locations {
+            let signer = thread_safe_signer_from_path(matches, location, name, wallet_manager)
+                .map_err(|e| {
+                    debug!(
+                        "signers_from_paths failed: location {}, keypair name: {}: {:?}",
+                        location, name, e
+                    );
+                    anyhow!("{}: arg name: {}, location: {}", e, name, location)
+                })?;
+            signers.push(signer);
+        }
+    }
+    Ok(signers)
+}
+
 /// Difference between this and 'pubkey_or_from_signer' method is that this method takes just the `value_or_path`
 /// parameter and tries to find it as a pubkey. On the other hand the `pubkey_or_from_signer` matches the name of
 /// argument and the value of argument first and then it search for pubkey from the value.
@@ -195,6 +245,155 @@ pub fn pubkey_or_signer(
     })
 }
 
+/// Compute unit limit of the transactions, `None` when the cluster default limit is used
+pub fn match_compute_unit_limit(matches: &ArgMatches<'_>) -> anyhow::Result<Option<u32>> {
+    match_u32_option(matches, WITH_COMPUTE_UNIT_LIMIT_ARG.name)
+}
+
+/// Safety margin in percents of the compute unit limit estimation, `None` when no estimation is done
+pub fn match_compute_unit_limit_margin(matches: &ArgMatches<'_>) -> anyhow::Result<Option<u32>> {
+    match_u32_option(matches, ESTIMATE_COMPUTE_UNIT_LIMIT_ARG.name)
+}
+
+/// Compute budget of the transactions: the compute unit price of the priority fee strategy,
+/// the compute unit limit or its estimation by simulation
+pub fn match_compute_budget_config(
+    matches: &ArgMatches<'_>,
+) -> anyhow::Result<ComputeBudgetConfig> {
+    Ok(
+        ComputeBudgetConfig::new(None, match_compute_unit_limit(matches)?)
+            .with_compute_unit_price(match_compute_unit_price(matches)?)
+            .with_compute_unit_limit_estimation(match_compute_unit_limit_margin(matches)?),
+    )
+}
+
+/// Compute unit price of the priority fee strategy, `None` when the fixed price is not provided
+pub fn match_compute_unit_price(
+    matches: &ArgMatches<'_>,
+) -> anyhow::Result<Option<ComputeUnitPrice>> {
+    let strategy = matches
+        .value_of(PRIORITY_FEE_STRATEGY_ARG.name)
+        .unwrap_or(PRIORITY_FEE_STRATEGIES[0]);
+    match strategy {
+        "fixed" => Ok(match_u64_option(matches, WITH_COMPUTE_UNIT_PRICE_ARG.name)?
+            .map(ComputeUnitPrice::Fixed)),
+        "percentile" => Ok(Some(ComputeUnitPrice::Percentile {
+            percentile: match_percentile(matches, PRIORITY_FEE_PERCENTILE_ARG.name)?,
+        })),
+        "capped-percentile" => {
+            let min = match_u64_option(matches, MIN_COMPUTE_UNIT_PRICE_ARG.name)?.unwrap_or(0);
+            let max = match_u64(matches, MAX_COMPUTE_UNIT_PRICE_ARG.name)?;
+            if min > max {
+                return Err(anyhow!(
+                    "Argument {} of value {} is greater than argument {} of value {}",
+                    MIN_COMPUTE_UNIT_PRICE_ARG.name,
+                    min,
+                    MAX_COMPUTE_UNIT_PRICE_ARG.name,
+                    max
+                ));
+            }
+            Ok(Some(ComputeUnitPrice::CappedPercentile {
+                percentile: match_percentile(matches, PRIORITY_FEE_PERCENTILE_ARG.name)?,
+                min,
+                max,
+            }))
+        }
+        _ => Err(anyhow!(
+            "Unknown priority fee strategy '{}', expected one of {:?}",
+            strategy,
+            PRIORITY_FEE_STRATEGIES
+        )),
+    }
+}
+
+fn match_percentile(matches: &ArgMatches<'_>, name: &str) -> anyhow::Result<u8> {
+    let percentile = match_u64(matches, name)?;
+    if percentile > 100 {
+        return Err(anyhow!(
+            "Argument {} of value {} is not a percentile between 0 and 100",
+            name,
+            percentile
+        ));
+    }
+    Ok(percentile as u8)
+}
+
+/// Tip transferred to the sender tip account within every transaction, `None` when no tip is provided
+pub fn match_tip_config(
+    matches: &ArgMatches<'_>,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> anyhow::Result<Option<TipConfig>> {
+    let lamports = match_u64_option(matches, SENDER_TIP_ARG.name)?;
+    let tip_account =
+        pubkey_or_of_signer_optional(matches, SENDER_TIP_ACCOUNT_ARG.name, wallet_manager)?;
+    match (lamports, tip_account) {
+        (Some(lamports), Some(tip_account)) => Ok(Some(TipConfig::new(tip_account, lamports))),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(anyhow!(
+            "Argument {} requires argument {}",
+            SENDER_TIP_ARG.name,
+            SENDER_TIP_ACCOUNT_ARG.name
+        )),
+        (None, Some(_)) => Err(anyhow!(
+            "Argument {} requires argument {}",
+            SENDER_TIP_ACCOUNT_ARG.name,
+            SENDER_TIP_ARG.name
+        )),
+    }
+}
+
+/// Maximal number of attempts to send a transaction, when not provided all configured retries are permitted,
+/// i.e., the first attempt, the transient retries and the blockhash not found retries
+pub fn match_retry_max_attempts(matches: &ArgMatches<'_>) -> anyhow::Result<u32> {
+    if let Some(max_attempts) = match_u32_option(matches, RETRY_MAX_ATTEMPTS_ARG.name)? {
+        return Ok(max_attempts);
+    }
+    let transient_retries = match_u32_option(matches, RETRY_TRANSIENT_ARG.name)?.unwrap_or(0);
+    let blockhash_not_found_retries =
+        match_u16_option(matches, BLOCKHASH_NOT_FOUND_RETRIES_ARG.name)?.unwrap_or(0);
+    Ok(1_u32
+        .saturating_add(transient_retries)
+        .saturating_add(blockhash_not_found_retries as u32))
+}
+
+/// Retry policy of sending the transactions configured by [crate::config_args::retry_policy_args],
+/// the defaults of [RetryPolicy] are used for the arguments not provided
+pub fn match_retry_policy(matches: &ArgMatches<'_>) -> anyhow::Result<RetryPolicy> {
+    let default_policy = RetryPolicy::default();
+    let initial_backoff = match_u64_option(matches, RETRY_BACKOFF_MS_ARG.name)?
+        .map_or(default_policy.initial_backoff, Duration::from_millis);
+    let max_backoff = match_u64_option(matches, RETRY_MAX_BACKOFF_MS_ARG.name)?
+        .map_or(default_policy.max_backoff, Duration::from_millis);
+    let jitter = match_f64_option(matches, RETRY_JITTER_ARG.name)?.unwrap_or(default_policy.jitter);
+    if !(0.0..=1.0).contains(&jitter) {
+        return Err(anyhow!(
+            "Argument {} of value {} is not a fraction between 0.0 and 1.0",
+            RETRY_JITTER_ARG.name,
+            jitter
+        ));
+    }
+    let blockhash_not_found_retries =
+        match_u16_option(matches, BLOCKHASH_NOT_FOUND_RETRIES_ARG.name)?;
+    let transient_retries = match_u32_option(matches, RETRY_TRANSIENT_ARG.name)?.unwrap_or(0);
+    let deadline =
+        match_u64_option(matches, RETRY_DEADLINE_SECS_ARG.name)?.map(Duration::from_secs);
+    let rebroadcast_interval =
+        match_u64_option(matches, REBROADCAST_INTERVAL_MS_ARG.name)?.map(Duration::from_millis);
+    Ok(
+        RetryPolicy::blockhash_not_found_retries(blockhash_not_found_retries)
+            .with_max_transient_retries(transient_retries)
+            .with_max_attempts(match_retry_max_attempts(matches)?)
+            .with_backoff(
+                initial_backoff,
+                max_backoff,
+                default_policy.backoff_multiplier,
+            )
+            .with_jitter(jitter)
+            .with_deadline(deadline)
+            .with_rebroadcast_interval(rebroadcast_interval),
+    )
+}
+
 pub fn match_u16(matches: &ArgMatches<'_>, name: &str) -> anyhow::Result<u16> {
     crate::matchers::match_u16_option(matches, name)?
         .ok_or_else(|| anyhow::Error::msg(format!("match_u16: argument '{}' missing", name)))